@@ -0,0 +1,64 @@
+//! Generates a test for every example in `fixtures/`, so adding one is a matter of dropping
+//! `<name>.txt` and `<name>.answers` into `fixtures/day<n>/`. A test is generated for each
+//! part which has an expected answer, and `src/fixtures.rs` includes them all.
+
+use std::{env, fs, path::Path};
+
+fn has_answer(answers: &str, day: u8, part: u8) -> bool {
+    answers.lines().any(|line| {
+        let Some((key, _)) = line.split_once('=') else {
+            return false;
+        };
+        !line.starts_with('#')
+            && key.split_once('/').map_or(false, |(d, p)| {
+                d.trim().parse() == Ok(day) && p.trim().parse() == Ok(part)
+            })
+    })
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=fixtures");
+    let mut tests = String::new();
+    let mut days: Vec<(u8, String)> = fs::read_dir("fixtures")
+        .map(|dir| {
+            dir.filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                Some((name.strip_prefix("day")?.parse().ok()?, name))
+            })
+            .collect()
+        })
+        .unwrap_or_default();
+    days.sort();
+    for (day, dir) in days {
+        let mut examples: Vec<String> = fs::read_dir(Path::new("fixtures").join(&dir))
+            .unwrap()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                (path.extension()? == "txt")
+                    .then(|| path.file_stem()?.to_str().map(str::to_owned))
+                    .flatten()
+            })
+            .collect();
+        examples.sort();
+        for example in examples {
+            let answers = Path::new("fixtures")
+                .join(&dir)
+                .join(format!("{}.answers", example));
+            let answers = fs::read_to_string(answers).unwrap_or_default();
+            let test_name: String = example
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            for (part, variant) in [(1, "One"), (2, "Two")] {
+                if has_answer(&answers, day, part) {
+                    tests.push_str(&format!(
+                        "#[test]\nfn day{}_{}_part{}() {{\n    check_fixture({}, {:?}, Part::{});\n}}\n\n",
+                        day, test_name, part, day, example, variant
+                    ));
+                }
+            }
+        }
+    }
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("fixture_tests.rs");
+    fs::write(out, tests).unwrap();
+}
@@ -0,0 +1,170 @@
+use crate::cli::Part;
+use std::{collections::BTreeMap, error::Error, fmt, fs, io, path::Path};
+
+#[derive(Debug)]
+pub enum AnswersError {
+    Io(String, io::Error),
+    Syntax { line: usize, message: String },
+}
+
+impl fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnswersError::Io(path, err) => write!(f, "failed to read {}: {}", path, err),
+            AnswersError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for AnswersError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    Incorrect { expected: String },
+    Unknown,
+}
+
+/// Known answers keyed by day and part, read from lines of the form `day/part = answer`.
+/// Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers(BTreeMap<(u8, Part), String>);
+
+pub fn escape(answer: &str) -> String {
+    answer.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(value: &str) -> Result<String, String> {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => return Err(format!("unknown escape `\\{}`", other)),
+            None => return Err("dangling `\\` at end of answer".to_owned()),
+        }
+    }
+    Ok(unescaped)
+}
+
+fn parse_key(key: &str) -> Option<(u8, Part)> {
+    let (day, part) = key.split_once('/')?;
+    Some((day.trim().parse().ok()?, part.trim().parse().ok()?))
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Self, AnswersError> {
+        let mut answers = BTreeMap::new();
+        for (idx, line) in text.lines().enumerate() {
+            let syntax_error = |message: String| AnswersError::Syntax {
+                line: idx + 1,
+                message,
+            };
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| syntax_error("expected `day/part = answer`".to_owned()))?;
+            let key = parse_key(key)
+                .ok_or_else(|| syntax_error(format!("`{}` is not a day/part pair", key.trim())))?;
+            let value = unescape(value.trim()).map_err(syntax_error)?;
+            if answers.insert(key, value).is_some() {
+                return Err(syntax_error(format!(
+                    "duplicate answer for day {} part {}",
+                    key.0, key.1
+                )));
+            }
+        }
+        Ok(Answers(answers))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AnswersError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|err| AnswersError::Io(path.display().to_string(), err))?;
+        Self::parse(&text)
+    }
+
+    pub fn get(&self, day: u8, part: Part) -> Option<&str> {
+        self.0.get(&(day, part)).map(String::as_str)
+    }
+
+    pub fn check(&self, day: u8, part: Part, answer: &str) -> Verdict {
+        match self.get(day, part) {
+            None => Verdict::Unknown,
+            Some(expected) if expected == answer => Verdict::Correct,
+            Some(expected) => Verdict::Incorrect {
+                expected: expected.to_owned(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ((day, part), answer) in self.0.iter() {
+            writeln!(f, "{}/{} = {}", day, part, escape(answer))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_answers() {
+        let answers = Answers::parse(
+            "# comment
+1/1 = 67633
+
+10/2 = \\n#.\\n.#
+25/1 =
+",
+        )
+        .unwrap();
+        assert_eq!(answers.get(1, Part::One), Some("67633"));
+        assert_eq!(answers.get(1, Part::Two), None);
+        assert_eq!(answers.get(10, Part::Two), Some("\n#.\n.#"));
+        assert_eq!(answers.get(25, Part::One), Some(""));
+        assert_eq!(answers.check(1, Part::One, "67633"), Verdict::Correct);
+        assert_eq!(
+            answers.check(1, Part::One, "1"),
+            Verdict::Incorrect {
+                expected: "67633".to_owned()
+            }
+        );
+        assert_eq!(answers.check(2, Part::One, "1"), Verdict::Unknown);
+    }
+
+    #[test]
+    fn round_trip() {
+        let answers = Answers::parse("10/2 = \\n#.\\\\\\n.#\n5/1=CMZ").unwrap();
+        assert_eq!(answers.get(10, Part::Two), Some("\n#.\\\n.#"));
+        assert_eq!(answers.to_string(), "5/1 = CMZ\n10/2 = \\n#.\\\\\\n.#\n");
+        assert_eq!(Answers::parse(&answers.to_string()).unwrap(), answers);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        for (text, line) in [
+            ("1/1 = 2\n1/3 = 4", 2),
+            ("1/1 2", 1),
+            ("\n\nx/1 = 2", 3),
+            ("1/1 = \\t", 1),
+            ("1/1 = 2\n1/1 = 2", 2),
+        ] {
+            match Answers::parse(text) {
+                Err(AnswersError::Syntax { line: l, .. }) => assert_eq!(l, line, "{}", text),
+                other => panic!("expected syntax error for {:?}, got {:?}", text, other),
+            }
+        }
+    }
+}
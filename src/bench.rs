@@ -0,0 +1,365 @@
+use crate::cli::Part;
+use crate::error::format_duration;
+use crate::error::SolveError;
+use crate::solution::Entry;
+use crate::timing::format_rows;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt, fs,
+    hint::black_box,
+    io,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// Every sample repeats the benchmark until it has run for at least this long, so quick
+/// solvers aren't measured below the resolution of the clock.
+const MIN_SAMPLE_TIME: Duration = Duration::from_millis(1);
+const MAX_ITERATIONS: u32 = 1 << 20;
+/// Slow solvers stop taking samples once they have used this much time (and have at least
+/// two samples), so `bench --all` finishes in a reasonable time.
+const MAX_BENCH_TIME: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub mean: Duration,
+    pub median: Duration,
+    pub stddev: Duration,
+    pub samples: usize,
+}
+
+impl Stats {
+    pub fn of(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let n = sorted.len();
+        let median = if n % 2 == 0 {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        } else {
+            sorted[n / 2]
+        };
+        let nanos: Vec<f64> = sorted.iter().map(|d| d.as_nanos() as f64).collect();
+        let mean = nanos.iter().sum::<f64>() / n as f64;
+        let variance = if n > 1 {
+            nanos.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+        } else {
+            0.0
+        };
+        Some(Stats {
+            mean: Duration::from_nanos(mean.round() as u64),
+            median,
+            stddev: Duration::from_nanos(variance.sqrt().round() as u64),
+            samples: n,
+        })
+    }
+}
+
+fn time_batch<T, F: FnMut() -> T>(f: &mut F, iterations: u32) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    start.elapsed()
+}
+
+/// Times `f`, first finding how many iterations make up a sample (which doubles as the
+/// warm-up), then taking up to `samples` samples of the time per iteration.
+pub fn measure<T, F: FnMut() -> T>(mut f: F, samples: usize) -> Stats {
+    let start = Instant::now();
+    let mut iterations = 1;
+    while iterations < MAX_ITERATIONS && time_batch(&mut f, iterations) < MIN_SAMPLE_TIME {
+        iterations *= 2;
+    }
+    let mut times = Vec::with_capacity(samples);
+    while times.len() < samples.max(1) && (times.len() < 2 || start.elapsed() < MAX_BENCH_TIME) {
+        times.push(time_batch(&mut f, iterations) / iterations);
+    }
+    Stats::of(&times).unwrap()
+}
+
+/// One benchmark, named `day<n>/<parse|part1|part2>/<input label>`. A solver which fails
+/// isn't timed; its error is kept instead.
+pub struct BenchResult {
+    pub name: String,
+    pub outcome: Result<Stats, SolveError>,
+}
+
+fn bench_step<F: FnMut() -> Result<T, SolveError>, T>(
+    name: String,
+    mut f: F,
+    samples: usize,
+) -> BenchResult {
+    BenchResult {
+        name,
+        outcome: f().map(|_| measure(f, samples)),
+    }
+}
+
+/// Benchmarks parsing and the requested parts of a day against each labelled input. As
+/// with `--timings`, the part benchmarks include parsing.
+pub fn bench_day(
+    entry: &Entry,
+    parts: &[Part],
+    inputs: &[(&str, &str)],
+    samples: usize,
+) -> Vec<BenchResult> {
+    let mut results = Vec::new();
+    for (label, input) in inputs {
+        results.push(bench_step(
+            format!("day{}/parse/{}", entry.number, label),
+            || entry.parse(input),
+            samples,
+        ));
+        for part in parts {
+            results.push(bench_step(
+                format!("day{}/part{}/{}", entry.number, part, label),
+                || entry.solve(*part, input),
+                samples,
+            ));
+        }
+    }
+    results
+}
+
+#[derive(Debug)]
+pub enum BaselineError {
+    Read(String, io::Error),
+    Write(String, io::Error),
+    Syntax { line: usize, message: String },
+}
+
+impl fmt::Display for BaselineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BaselineError::Read(path, err) => write!(f, "failed to read {}: {}", path, err),
+            BaselineError::Write(path, err) => write!(f, "failed to write {}: {}", path, err),
+            BaselineError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for BaselineError {}
+
+/// Saved benchmark statistics keyed by benchmark name, stored one per line as
+/// `name mean_ns median_ns stddev_ns samples`. Blank lines and lines starting with `#` are
+/// ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Baseline(BTreeMap<String, Stats>);
+
+impl Baseline {
+    /// Keeps the statistics of every benchmark which succeeded.
+    pub fn of_results(results: &[BenchResult]) -> Self {
+        Baseline(
+            results
+                .iter()
+                .filter_map(|result| {
+                    let stats = result.outcome.as_ref().ok()?;
+                    Some((result.name.clone(), *stats))
+                })
+                .collect(),
+        )
+    }
+
+    pub fn parse(text: &str) -> Result<Self, BaselineError> {
+        let mut baseline = BTreeMap::new();
+        for (idx, line) in text.lines().enumerate() {
+            let syntax_error = |message: String| BaselineError::Syntax {
+                line: idx + 1,
+                message,
+            };
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [name, mean, median, stddev, samples] = fields[..] else {
+                return Err(syntax_error(
+                    "expected `name mean_ns median_ns stddev_ns samples`".to_owned(),
+                ));
+            };
+            let number = |field: &str| {
+                field
+                    .parse::<u64>()
+                    .map_err(|_| syntax_error(format!("`{}` is not a number", field)))
+            };
+            let stats = Stats {
+                mean: Duration::from_nanos(number(mean)?),
+                median: Duration::from_nanos(number(median)?),
+                stddev: Duration::from_nanos(number(stddev)?),
+                samples: number(samples)? as usize,
+            };
+            if baseline.insert(name.to_owned(), stats).is_some() {
+                return Err(syntax_error(format!("duplicate benchmark `{}`", name)));
+            }
+        }
+        Ok(Baseline(baseline))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BaselineError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|err| BaselineError::Read(path.display().to_string(), err))?;
+        Self::parse(&text)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), BaselineError> {
+        let path = path.as_ref();
+        fs::write(path, self.to_string())
+            .map_err(|err| BaselineError::Write(path.display().to_string(), err))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Stats> {
+        self.0.get(name)
+    }
+}
+
+impl fmt::Display for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# name mean_ns median_ns stddev_ns samples")?;
+        for (name, stats) in self.0.iter() {
+            writeln!(
+                f,
+                "{} {} {} {} {}",
+                name,
+                stats.mean.as_nanos(),
+                stats.median.as_nanos(),
+                stats.stddev.as_nanos(),
+                stats.samples
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The change in median time from the baseline, as a percentage.
+fn format_change(before: Duration, after: Duration) -> String {
+    if before.is_zero() {
+        return "-".to_owned();
+    }
+    let change = (after.as_secs_f64() / before.as_secs_f64() - 1.0) * 100.0;
+    format!("{:+.1}%", change)
+}
+
+/// Renders the results as an aligned table, comparing medians against `baseline` when one
+/// is given, followed by the errors of any benchmarks which failed.
+pub fn format_report(results: &[BenchResult], baseline: Option<&Baseline>) -> String {
+    let mut header: Vec<String> = ["benchmark", "mean", "median", "stddev", "samples"]
+        .map(str::to_owned)
+        .to_vec();
+    if baseline.is_some() {
+        header.push("change".to_owned());
+    }
+    let mut rows = vec![header];
+    for result in results {
+        let mut row = vec![result.name.clone()];
+        match &result.outcome {
+            Ok(stats) => row.extend([
+                format_duration(stats.mean),
+                format_duration(stats.median),
+                format_duration(stats.stddev),
+                stats.samples.to_string(),
+            ]),
+            Err(_) => row.extend(["(error)", "-", "-", "-"].map(str::to_owned)),
+        }
+        if let Some(baseline) = baseline {
+            row.push(match (&result.outcome, baseline.get(&result.name)) {
+                (Ok(stats), Some(before)) => format_change(before.median, stats.median),
+                (Ok(_), None) => "new".to_owned(),
+                (Err(_), _) => "-".to_owned(),
+            });
+        }
+        rows.push(row);
+    }
+    let mut report = format_rows(rows);
+    for result in results {
+        if let Err(err) = &result.outcome {
+            report.push_str(&format!("\n{}: error: {}\n", result.name, err));
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(mean: u64, median: u64, stddev: u64, samples: usize) -> Stats {
+        Stats {
+            mean: Duration::from_nanos(mean),
+            median: Duration::from_nanos(median),
+            stddev: Duration::from_nanos(stddev),
+            samples,
+        }
+    }
+
+    #[test]
+    fn sample_statistics() {
+        let samples = [2, 4, 4, 4, 5, 5, 7, 9].map(Duration::from_micros);
+        assert_eq!(Stats::of(&samples), Some(stats(5_000, 4_500, 2_138, 8)));
+        assert_eq!(
+            Stats::of(&[Duration::from_micros(3)]),
+            Some(stats(3_000, 3_000, 0, 1))
+        );
+        assert_eq!(Stats::of(&[]), None);
+    }
+
+    #[test]
+    fn baseline_round_trip() {
+        let baseline = Baseline::parse(
+            "# saved earlier
+day7/part1/input 1500 1400 30 20
+
+day10/parse/example 800 750 12 20
+",
+        )
+        .unwrap();
+        assert_eq!(
+            baseline.get("day7/part1/input"),
+            Some(&stats(1500, 1400, 30, 20))
+        );
+        assert_eq!(baseline.get("day7/part2/input"), None);
+        assert_eq!(Baseline::parse(&baseline.to_string()).unwrap(), baseline);
+        for (text, line) in [
+            ("day1/parse/input 1 2 3", 1),
+            ("\nday1/parse/input 1 2 x 4", 2),
+            ("day1/parse/input 1 2 3 4\nday1/parse/input 1 2 3 4", 2),
+        ] {
+            match Baseline::parse(text) {
+                Err(BaselineError::Syntax { line: l, .. }) => assert_eq!(l, line, "{}", text),
+                other => panic!("expected syntax error for {:?}, got {:?}", text, other),
+            }
+        }
+    }
+
+    #[test]
+    fn report_against_baseline() {
+        let results = vec![
+            BenchResult {
+                name: "day1/part1/input".to_owned(),
+                outcome: Ok(stats(1_100, 1_000, 50, 20)),
+            },
+            BenchResult {
+                name: "day1/part2/input".to_owned(),
+                outcome: Ok(stats(2_000, 2_000, 0, 20)),
+            },
+            BenchResult {
+                name: "day22/part2/example".to_owned(),
+                outcome: Err(SolveError::NoSolution("no exit".to_owned())),
+            },
+        ];
+        let baseline = Baseline::parse("day1/part1/input 900 800 10 20\n").unwrap();
+        assert_eq!(
+            format_report(&results, Some(&baseline)),
+            "benchmark            mean     median  stddev  samples  change
+day1/part1/input     1µs      1µs     50ns    20       +25.0%
+day1/part2/input     2µs      2µs     0ns     20       new
+day22/part2/example  (error)  -       -       -        -
+
+day22/part2/example: error: no solution: no exit
+"
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use crate::error::SolveError;
+use crate::solution::{self, Entry};
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// A file in the input cache, and the day it is for when its name ends in a day number
+/// (such as `day07.txt` or `7`). Inputs kept in a directory named for a year (such as
+/// `2021/day07.txt`) have that year.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedInput {
+    pub path: PathBuf,
+    pub year: Option<String>,
+    pub day: Option<u8>,
+}
+
+impl CachedInput {
+    /// Whether this could be the input for `year`'s puzzles.
+    pub fn is_for(&self, year: &str) -> bool {
+        self.year.as_deref().map_or(true, |y| y == year)
+    }
+}
+
+fn year_of(relative: &Path) -> Option<String> {
+    relative.parent()?.components().rev().find_map(|component| {
+        let name = component.as_os_str().to_str()?;
+        (name.len() == 4 && name.chars().all(|c| c.is_ascii_digit())).then(|| name.to_owned())
+    })
+}
+
+fn day_of(path: &Path) -> Option<u8> {
+    let stem = path.file_stem()?.to_str()?;
+    let digits: String = stem
+        .chars()
+        .rev()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<Vec<char>>()
+        .into_iter()
+        .rev()
+        .collect();
+    digits.parse().ok().filter(|day| (1..=25).contains(day))
+}
+
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            walk(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Every file under `dir`, sorted by path. A missing directory is an empty cache.
+pub fn discover(dir: &Path) -> io::Result<Vec<CachedInput>> {
+    let mut files = Vec::new();
+    if dir.exists() {
+        walk(dir, &mut files)?;
+    }
+    files.sort();
+    Ok(files
+        .into_iter()
+        .map(|path| CachedInput {
+            year: year_of(path.strip_prefix(dir).unwrap_or(&path)),
+            day: day_of(&path),
+            path,
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    Empty,
+    NotText,
+    /// An error page saved in place of the input, usually because the session expired.
+    Html,
+    Crlf,
+    NoTrailingNewline,
+    /// The day's parser rejects it, which is what a truncated download looks like.
+    Unparsable(SolveError),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Empty => write!(f, "empty file"),
+            Problem::NotText => write!(f, "not UTF-8 text"),
+            Problem::Html => write!(f, "an HTML page rather than a puzzle input"),
+            Problem::Crlf => write!(f, "CRLF line endings"),
+            Problem::NoTrailingNewline => write!(f, "no trailing newline"),
+            Problem::Unparsable(err) => write!(f, "truncated or corrupt: {}", err),
+        }
+    }
+}
+
+/// Everything wrong with a cached input, parsing it with `entry` when its day is known.
+pub fn check(contents: &[u8], entry: Option<&Entry>) -> Vec<Problem> {
+    let Ok(text) = std::str::from_utf8(contents) else {
+        return vec![Problem::NotText];
+    };
+    if text.trim().is_empty() {
+        return vec![Problem::Empty];
+    }
+    let start = text.trim_start().to_lowercase();
+    if start.starts_with("<!doctype html") || start.starts_with("<html") {
+        return vec![Problem::Html];
+    }
+    let mut problems = Vec::new();
+    if text.contains("\r\n") {
+        problems.push(Problem::Crlf);
+    }
+    if !text.ends_with('\n') {
+        problems.push(Problem::NoTrailingNewline);
+    }
+    // Only worth parsing when the line endings wouldn't already make it fail.
+    if let (Some(entry), true) = (entry, problems.is_empty()) {
+        if let Err(err) = entry.parse(text) {
+            problems.push(Problem::Unparsable(err));
+        }
+    }
+    problems
+}
+
+/// The solver to parse `input` with, which only makes sense for this year's puzzles.
+fn entry_for(input: &CachedInput, year: &str) -> Option<&'static Entry> {
+    input
+        .day
+        .filter(|_| input.is_for(year))
+        .and_then(solution::find)
+}
+
+pub fn check_file(input: &CachedInput, year: &str) -> io::Result<Vec<Problem>> {
+    Ok(check(&fs::read(&input.path)?, entry_for(input, year)))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Imported {
+    Copied,
+    Unchanged,
+    /// We already have a different file there, which is kept.
+    Conflict,
+    Skipped(Vec<Problem>),
+}
+
+impl fmt::Display for Imported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Imported::Copied => write!(f, "imported"),
+            Imported::Unchanged => write!(f, "already cached"),
+            Imported::Conflict => write!(f, "differs from the cached copy, kept ours"),
+            Imported::Skipped(problems) => {
+                let problems: Vec<String> = problems.iter().map(Problem::to_string).collect();
+                write!(f, "skipped, {}", problems.join(", "))
+            }
+        }
+    }
+}
+
+/// Copies the inputs from another cache directory into `cache`, keeping their paths
+/// relative to `from`. Files which fail `check` are skipped and existing files are never
+/// overwritten.
+pub fn import(from: &Path, cache: &Path, year: &str) -> io::Result<Vec<(PathBuf, Imported)>> {
+    let mut results = Vec::new();
+    for input in discover(from)? {
+        let contents = fs::read(&input.path)?;
+        let relative = input.path.strip_prefix(from).unwrap().to_owned();
+        let target = cache.join(&relative);
+        let problems = check(&contents, entry_for(&input, year));
+        let imported = if !problems.is_empty() {
+            Imported::Skipped(problems)
+        } else if target.exists() {
+            if fs::read(&target)? == contents {
+                Imported::Unchanged
+            } else {
+                Imported::Conflict
+            }
+        } else {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&target, contents)?;
+            Imported::Copied
+        };
+        results.push((relative, imported));
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_from_file_names() {
+        for (path, day) in [
+            ("inputs/2022/day07.txt", Some(7)),
+            ("inputs/25", Some(25)),
+            ("inputs/Day12", Some(12)),
+            ("inputs/day26.txt", None),
+            ("inputs/notes.txt", None),
+        ] {
+            assert_eq!(day_of(Path::new(path)), day, "{}", path);
+        }
+        assert_eq!(
+            year_of(Path::new("2021/day07.txt")),
+            Some("2021".to_owned())
+        );
+        assert_eq!(year_of(Path::new("day07.txt")), None);
+    }
+
+    #[test]
+    fn problems() {
+        let day6 = solution::find(6);
+        assert_eq!(check(b"mjqjpqmgbljsphdztnvjfqwrcgsmlb\n", day6), vec![]);
+        assert_eq!(check(b"\n", day6), vec![Problem::Empty]);
+        assert_eq!(check(&[0xff, 0xfe], day6), vec![Problem::NotText]);
+        assert_eq!(
+            check(
+                b"<!DOCTYPE html>\n<html><p>Please log in</p></html>\n",
+                day6
+            ),
+            vec![Problem::Html]
+        );
+        assert_eq!(
+            check(b"abc\r\ndef", day6),
+            vec![Problem::Crlf, Problem::NoTrailingNewline]
+        );
+        assert!(matches!(
+            &check(b"mjqjpqmgbljsp\xc3\xa9\n", day6)[..],
+            [Problem::Unparsable(SolveError::Parse { column: 14, .. })]
+        ));
+    }
+
+    #[test]
+    fn import_keeps_existing_files() {
+        let root = std::env::temp_dir().join(format!("aoc_cache_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let (from, cache) = (root.join("theirs"), root.join("ours"));
+        fs::create_dir_all(from.join("2021")).unwrap();
+        fs::create_dir_all(from.join("2022")).unwrap();
+        fs::create_dir_all(cache.join("2022")).unwrap();
+        fs::write(from.join("2021/day06.txt"), "abcd\n").unwrap();
+        fs::write(from.join("2022/day06.txt"), "wxyz\n").unwrap();
+        fs::write(from.join("2022/day07.txt"), "<html></html>").unwrap();
+        fs::write(cache.join("2022/day06.txt"), "abcd\n").unwrap();
+
+        let results = import(&from, &cache, "2022").unwrap();
+        assert_eq!(
+            results,
+            vec![
+                (PathBuf::from("2021/day06.txt"), Imported::Copied),
+                (PathBuf::from("2022/day06.txt"), Imported::Conflict),
+                (
+                    PathBuf::from("2022/day07.txt"),
+                    Imported::Skipped(vec![Problem::Html])
+                ),
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(cache.join("2022/day06.txt")).unwrap(),
+            "abcd\n"
+        );
+        assert_eq!(
+            import(&from, &cache, "2022").unwrap()[0],
+            (PathBuf::from("2021/day06.txt"), Imported::Unchanged)
+        );
+        assert_eq!(
+            discover(&cache)
+                .unwrap()
+                .iter()
+                .map(|input| (input.year.as_deref(), input.day))
+                .collect::<Vec<_>>(),
+            vec![(Some("2021"), Some(6)), (Some("2022"), Some(6))]
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
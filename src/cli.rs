@@ -0,0 +1,1306 @@
+use crate::fuzz;
+use crate::input::InputSource;
+use crate::render::ImageFormat;
+use crate::serve;
+use std::{error::Error, fmt, ops::RangeInclusive, path::PathBuf, str::FromStr, time::Duration};
+
+pub const USAGE: &str = "usage:
+    advent_of_code_2022 run --day <1-25> [--part <1|2>] [--input <path|->]
+                            [--format <text|json|csv>] [--timings] [--memory]
+                            [--jobs <n>] [--quiet] [--time-limit <secs>]
+    advent_of_code_2022 run --all [--part <1|2>] [--format <text|json|csv>] [--timings]
+                            [--memory] [--jobs <n>] [--quiet] [--time-limit <secs>]
+    advent_of_code_2022 run (--day <1-25> | --all) --frames <dir> [--steps <from>-<to>]
+                            [--image <ascii|ppm|pgm>]
+    advent_of_code_2022 run (--day <1-25> | --all) --explain [--part <1|2>]
+                            [--input <path|->] [--format <text|json>]
+    advent_of_code_2022 verify (--day <1-25> | --all) [--part <1|2>] [--input <path|->]
+                               [--answers <path>]
+    advent_of_code_2022 bench (--day <1-25> | --all) [--part <1|2>] [--input <path|->]
+                              [--examples] [--samples <n>] [--save <path>]
+                              [--baseline <path>]
+    advent_of_code_2022 submit --day <1-25> --part <1|2> [--input <path|->]
+                               [--base-url <url>]
+    advent_of_code_2022 regress <dir> [--day <1-25>] [--part <1|2>] [--jobs <n>]
+                                [--quiet] [--time-limit <secs>]
+    advent_of_code_2022 fuzz [--day <1-25>] [--runs <n>] [--seed <n>] [--quiet]
+    advent_of_code_2022 generate --day <1-25> [--size <n>] [--seed <n>]
+                                 [--out <path>]
+    advent_of_code_2022 repl [--day <1-25>] [--time-limit <secs>]
+    advent_of_code_2022 serve [--port <n>] [--time-limit <secs>]
+    advent_of_code_2022 cache (list | verify | import <dir>)
+    advent_of_code_2022 new-day <1-25>
+    advent_of_code_2022 help
+
+--input reads the puzzle input from a file (or stdin for `-`) instead of the
+cached download, so no session cookie is needed.
+
+--format json and csv print one record per part with the day, part, answer,
+elapsed time in nanoseconds, heap allocations, bytes allocated, peak heap in
+bytes and error, if any. --timings only applies to text.
+
+--memory adds a table of each part's heap allocations, bytes allocated and peak
+heap to the --timings report, with a day's totals under its parts, and implies
+--timings.
+
+--jobs runs up to n days at once. Results are still printed in day order, and
+the wall column of --timings shows how long each day took on its own thread.
+
+Slow days show their progress on stderr while they run, such as which blueprint
+day 19 is on and how many states it has explored. --quiet turns this off, and
+it's left off with --jobs above 1, where the days' lines would overwrite each
+other.
+
+--time-limit gives each part at most that many seconds (such as 0.5). The
+long searches of days 16, 19 and 24 stop and report a timeout once it's up;
+the other days are quick enough to always run to the end.
+
+--explain lists the intermediate results a day's answer was built from under
+each answer, or as a `trace` array in json: the directories day 7 added up, the
+packet pairs day 13 found in order, the valves day 16 opened and when, and the
+equations day 21 solved on the way down to humn.
+
+--frames writes a picture of each step of the simulation for days 14, 17, 22,
+23 and 24 to <dir>, one file per step, as text or PPM/PGM images. Only steps
+0-99 are written unless --steps picks others.
+
+bench times parsing and each part against the puzzle input and the day's
+example (only the example with --examples). --save writes the results as a
+baseline, and --baseline compares the medians against an earlier one.
+
+submit posts the answer using the session cookie and records the response in
+submissions.txt. An answer which was already judged isn't sent again.
+
+regress runs every day over several people's puzzle inputs and reports which
+inputs each solver fails on. <dir> has a directory per person holding their
+inputs, named like the cached ones (day07.txt), and an answers.txt.
+
+fuzz feeds each day's parser mutated copies of its example, and reports the
+first input it panics on or is still parsing after a couple of seconds. --runs
+sets how many inputs each day gets, and --seed picks a different set of them.
+
+generate writes a random puzzle input for the day to stdout, or to --out, for
+stress testing. --size scales it, such as how many numbers day 20 mixes or how
+many directories day 7 explores, and --seed picks a different one of that size.
+Pipe it into `run --day <n> --input -` to time a solver on it.
+
+repl starts an interactive session for trying a day on input of your own: load
+it from a file or paste it in, run either part and see how long it took, then
+change the day's parameters (such as day 15's row or day 11's rounds) or the
+time limit and run it again. Type `help` in the session for its commands.
+
+serve answers HTTP requests on localhost (port 2022 unless --port says
+otherwise), so other tools can use the solvers. POST the puzzle input to
+/day/<n>/part/<p> and the response is the part's record as in --format json,
+with status 200 when it was solved, 422 when the solver failed and 500 when it
+panicked.
+
+cache list shows the cached inputs, cache verify checks them for problems such
+as truncation, saved error pages and CRLF line endings, and cache import copies
+the inputs from another machine's cache without overwriting any of ours.
+
+new-day creates src/day<n>.rs from src/day_template.rs, registers it, and adds
+fixtures/day<n>/ for the example input and its expected answers.
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+pub const PARTS: [Part; 2] = [Part::One, Part::Two];
+
+pub const DEFAULT_SAMPLES: usize = 20;
+pub const DEFAULT_GENERATE_SIZE: usize = 100;
+pub const DEFAULT_GENERATE_SEED: u64 = 0x2022_1225;
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            _ => Err(CliError::InvalidPart(s.to_owned())),
+        }
+    }
+}
+
+/// How `run` prints its results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(CliError::InvalidFormat(s.to_owned())),
+        }
+    }
+}
+
+/// Which simulation steps `run --frames` draws, and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frames {
+    pub dir: PathBuf,
+    pub format: ImageFormat,
+    pub steps: RangeInclusive<usize>,
+}
+
+pub const DEFAULT_FRAME_STEPS: RangeInclusive<usize> = 0..=99;
+
+fn parse_steps(value: String) -> Result<RangeInclusive<usize>, CliError> {
+    let (from, to) = value.split_once('-').unwrap_or((&value, &value));
+    match (from.parse(), to.parse()) {
+        (Ok(from), Ok(to)) if from <= to => Ok(from..=to),
+        _ => Err(CliError::InvalidSteps(value)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheCommand {
+    List,
+    Verify,
+    Import(PathBuf),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    All,
+    Day(u8),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Run {
+        selection: Selection,
+        part: Option<Part>,
+        input: InputSource,
+        format: Format,
+        timings: bool,
+        jobs: usize,
+        quiet: bool,
+        memory: bool,
+        frames: Option<Frames>,
+        time_limit: Option<Duration>,
+        explain: bool,
+    },
+    Verify {
+        selection: Selection,
+        part: Option<Part>,
+        input: InputSource,
+        answers: Option<String>,
+    },
+    Bench {
+        selection: Selection,
+        part: Option<Part>,
+        input: InputSource,
+        examples: bool,
+        samples: usize,
+        save: Option<String>,
+        baseline: Option<String>,
+    },
+    Submit {
+        day: u8,
+        part: Part,
+        input: InputSource,
+        base_url: Option<String>,
+    },
+    Regress {
+        dir: PathBuf,
+        selection: Selection,
+        part: Option<Part>,
+        jobs: usize,
+        quiet: bool,
+        time_limit: Option<Duration>,
+    },
+    Fuzz {
+        selection: Selection,
+        runs: usize,
+        seed: u64,
+        quiet: bool,
+    },
+    Generate {
+        day: u8,
+        size: usize,
+        seed: u64,
+        out: Option<PathBuf>,
+    },
+    Repl {
+        day: Option<u8>,
+        time_limit: Option<Duration>,
+    },
+    Serve {
+        port: u16,
+        time_limit: Option<Duration>,
+    },
+    Cache(CacheCommand),
+    NewDay {
+        day: u8,
+    },
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    MissingCommand,
+    UnknownCommand(String),
+    UnknownFlag(String),
+    MissingValue(&'static str),
+    InvalidDay(String),
+    UnknownDay(u8),
+    InvalidPart(String),
+    MissingSelection,
+    ConflictingSelection,
+    InputNeedsDay,
+    InvalidSamples(String),
+    InvalidFormat(String),
+    InvalidJobs(String),
+    SubmitNeedsDayAndPart,
+    RegressTakesNoInput,
+    InvalidSteps(String),
+    InvalidImageFormat(String),
+    FrameOptionsNeedFrames,
+    FramesWithTimings,
+    ExplainWithTimings,
+    ExplainAsCsv,
+    InvalidTimeLimit(String),
+    InvalidRuns(String),
+    InvalidSeed(String),
+    GenerateNeedsDay,
+    InvalidSize(String),
+    ReplTakesOneDay,
+    InvalidPort(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::MissingCommand => write!(f, "no command given"),
+            CliError::UnknownCommand(command) => write!(f, "unknown command `{}`", command),
+            CliError::UnknownFlag(flag) => write!(f, "unknown option `{}`", flag),
+            CliError::MissingValue(flag) => write!(f, "option `{}` requires a value", flag),
+            CliError::InvalidDay(day) => write!(f, "`{}` is not a day number", day),
+            CliError::UnknownDay(day) => write!(f, "there is no solution for day {}", day),
+            CliError::InvalidPart(part) => write!(f, "`{}` is not a part, expected 1 or 2", part),
+            CliError::MissingSelection => write!(f, "expected either --day <n> or --all"),
+            CliError::ConflictingSelection => write!(f, "--day and --all cannot be combined"),
+            CliError::InputNeedsDay => write!(f, "--input can only be used with a single --day"),
+            CliError::InvalidSamples(samples) => {
+                write!(f, "`{}` is not a positive number of samples", samples)
+            }
+            CliError::InvalidFormat(format) => {
+                write!(
+                    f,
+                    "`{}` is not a format, expected text, json or csv",
+                    format
+                )
+            }
+            CliError::SubmitNeedsDayAndPart => {
+                write!(f, "submit needs a single --day and a --part")
+            }
+            CliError::InvalidJobs(jobs) => write!(f, "`{}` is not a positive number of jobs", jobs),
+            CliError::InvalidSteps(steps) => {
+                write!(f, "`{}` is not a step or range of steps like 10-20", steps)
+            }
+            CliError::InvalidImageFormat(format) => write!(
+                f,
+                "`{}` is not an image format, expected ascii, ppm or pgm",
+                format
+            ),
+            CliError::FrameOptionsNeedFrames => {
+                write!(f, "--steps and --image only apply with --frames <dir>")
+            }
+            CliError::FramesWithTimings => write!(
+                f,
+                "--frames can't be combined with --timings, drawing would skew the times"
+            ),
+            CliError::ExplainWithTimings => write!(
+                f,
+                "--explain can't be combined with --timings, tracing would skew the times"
+            ),
+            CliError::ExplainAsCsv => {
+                write!(
+                    f,
+                    "--explain needs --format text or json, csv has no room for a trace"
+                )
+            }
+            CliError::InvalidTimeLimit(limit) => {
+                write!(f, "`{}` is not a positive number of seconds", limit)
+            }
+            CliError::InvalidRuns(runs) => write!(f, "`{}` is not a positive number of runs", runs),
+            CliError::InvalidSeed(seed) => write!(f, "`{}` is not a seed, expected a number", seed),
+            CliError::GenerateNeedsDay => write!(f, "generate needs a single --day"),
+            CliError::InvalidSize(size) => write!(f, "`{}` is not a positive size", size),
+            CliError::ReplTakesOneDay => write!(f, "repl works on one --day at a time"),
+            CliError::InvalidPort(port) => write!(f, "`{}` is not a port number", port),
+            CliError::RegressTakesNoInput => {
+                write!(
+                    f,
+                    "regress reads its inputs from the directory, not --input"
+                )
+            }
+        }
+    }
+}
+
+impl Error for CliError {}
+
+fn next_value<I: Iterator<Item = String>>(
+    args: &mut I,
+    flag: &'static str,
+) -> Result<String, CliError> {
+    args.next().ok_or(CliError::MissingValue(flag))
+}
+
+/// The `--day`/`--all`/`--part`/`--input` options shared by the commands that run solvers.
+#[derive(Default)]
+struct SolverOptions {
+    selection: Option<Selection>,
+    part: Option<Part>,
+    input: Option<InputSource>,
+}
+
+impl SolverOptions {
+    /// Consumes `arg` (and its value) if it is one of the shared options.
+    fn accept<I: Iterator<Item = String>>(
+        &mut self,
+        arg: &str,
+        args: &mut I,
+    ) -> Result<bool, CliError> {
+        let next_selection = match arg {
+            "--day" | "-d" => {
+                let day = next_value(args, "--day")?;
+                Selection::Day(day.parse().map_err(|_| CliError::InvalidDay(day))?)
+            }
+            "--all" | "-a" => Selection::All,
+            "--part" | "-p" => {
+                self.part = Some(next_value(args, "--part")?.parse()?);
+                return Ok(true);
+            }
+            "--input" | "-i" => {
+                self.input = Some(InputSource::of_arg(&next_value(args, "--input")?));
+                return Ok(true);
+            }
+            _ => return Ok(false),
+        };
+        if self.selection.replace(next_selection).is_some() {
+            return Err(CliError::ConflictingSelection);
+        }
+        Ok(true)
+    }
+
+    fn selection(&self) -> Result<Selection, CliError> {
+        self.selection.ok_or(CliError::MissingSelection)
+    }
+
+    /// A supplied input belongs to one puzzle, so it can't be combined with `--all`.
+    fn input(&mut self) -> Result<InputSource, CliError> {
+        match (self.selection()?, self.input.take()) {
+            (_, None) => Ok(InputSource::Cache),
+            (Selection::Day(_), Some(input)) => Ok(input),
+            (Selection::All, Some(_)) => Err(CliError::InputNeedsDay),
+        }
+    }
+}
+
+fn parse_jobs<I: Iterator<Item = String>>(args: &mut I) -> Result<usize, CliError> {
+    let value = next_value(args, "--jobs")?;
+    match value.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(CliError::InvalidJobs(value)),
+    }
+}
+
+fn parse_time_limit<I: Iterator<Item = String>>(args: &mut I) -> Result<Duration, CliError> {
+    let value = next_value(args, "--time-limit")?;
+    match value.parse::<f64>() {
+        Ok(secs) if secs > 0.0 => {
+            Duration::try_from_secs_f64(secs).map_err(|_| CliError::InvalidTimeLimit(value))
+        }
+        _ => Err(CliError::InvalidTimeLimit(value)),
+    }
+}
+
+fn parse_run<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let mut options = SolverOptions::default();
+    let mut format = Format::Text;
+    let mut timings = false;
+    let mut jobs = 1;
+    let mut quiet = false;
+    let mut memory = false;
+    let mut frames_dir = None;
+    let mut steps = None;
+    let mut image = None;
+    let mut time_limit = None;
+    let mut explain = false;
+    while let Some(arg) = args.next() {
+        if options.accept(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--format" | "-f" => format = next_value(&mut args, "--format")?.parse()?,
+            "--timings" | "-t" => timings = true,
+            "--memory" | "-m" => memory = true,
+            "--quiet" | "-q" => quiet = true,
+            "--jobs" | "-j" => jobs = parse_jobs(&mut args)?,
+            "--frames" => frames_dir = Some(next_value(&mut args, "--frames")?.into()),
+            "--steps" => steps = Some(parse_steps(next_value(&mut args, "--steps")?)?),
+            "--image" => image = Some(next_value(&mut args, "--image")?.parse()?),
+            "--time-limit" => time_limit = Some(parse_time_limit(&mut args)?),
+            "--explain" => explain = true,
+            _ => return Err(CliError::UnknownFlag(arg)),
+        }
+    }
+    let frames = match frames_dir {
+        Some(dir) => Some(Frames {
+            dir,
+            format: image.unwrap_or(ImageFormat::Ascii),
+            steps: steps.unwrap_or(DEFAULT_FRAME_STEPS),
+        }),
+        None if steps.is_some() || image.is_some() => return Err(CliError::FrameOptionsNeedFrames),
+        None => None,
+    };
+    let timings = timings || memory;
+    if frames.is_some() && timings {
+        return Err(CliError::FramesWithTimings);
+    }
+    if explain && timings {
+        return Err(CliError::ExplainWithTimings);
+    }
+    if explain && format == Format::Csv {
+        return Err(CliError::ExplainAsCsv);
+    }
+    Ok(Command::Run {
+        selection: options.selection()?,
+        part: options.part,
+        input: options.input()?,
+        format,
+        timings,
+        jobs,
+        quiet,
+        memory,
+        frames,
+        time_limit,
+        explain,
+    })
+}
+
+fn parse_verify<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let mut options = SolverOptions::default();
+    let mut answers = None;
+    while let Some(arg) = args.next() {
+        if options.accept(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--answers" => answers = Some(next_value(&mut args, "--answers")?),
+            _ => return Err(CliError::UnknownFlag(arg)),
+        }
+    }
+    Ok(Command::Verify {
+        selection: options.selection()?,
+        part: options.part,
+        input: options.input()?,
+        answers,
+    })
+}
+
+fn parse_bench<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let mut options = SolverOptions::default();
+    let mut examples = false;
+    let mut samples = DEFAULT_SAMPLES;
+    let mut save = None;
+    let mut baseline = None;
+    while let Some(arg) = args.next() {
+        if options.accept(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--examples" | "-e" => examples = true,
+            "--samples" => {
+                let value = next_value(&mut args, "--samples")?;
+                samples = match value.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(CliError::InvalidSamples(value)),
+                };
+            }
+            "--save" => save = Some(next_value(&mut args, "--save")?),
+            "--baseline" => baseline = Some(next_value(&mut args, "--baseline")?),
+            _ => return Err(CliError::UnknownFlag(arg)),
+        }
+    }
+    Ok(Command::Bench {
+        selection: options.selection()?,
+        part: options.part,
+        input: options.input()?,
+        examples,
+        samples,
+        save,
+        baseline,
+    })
+}
+
+fn parse_submit<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let mut options = SolverOptions::default();
+    let mut base_url = None;
+    while let Some(arg) = args.next() {
+        if options.accept(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--base-url" => base_url = Some(next_value(&mut args, "--base-url")?),
+            _ => return Err(CliError::UnknownFlag(arg)),
+        }
+    }
+    let input = options.input()?;
+    match (options.selection()?, options.part) {
+        (Selection::Day(day), Some(part)) => Ok(Command::Submit {
+            day,
+            part,
+            input,
+            base_url,
+        }),
+        _ => Err(CliError::SubmitNeedsDayAndPart),
+    }
+}
+
+/// Like `run`, but every day is selected unless `--day` narrows it down. The directory may
+/// come before, after or between the flags.
+fn parse_regress<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let mut dir = None;
+    let mut options = SolverOptions::default();
+    let mut jobs = 1;
+    let mut quiet = false;
+    let mut time_limit = None;
+    while let Some(arg) = args.next() {
+        if options.accept(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--quiet" | "-q" => quiet = true,
+            "--jobs" | "-j" => jobs = parse_jobs(&mut args)?,
+            "--time-limit" => time_limit = Some(parse_time_limit(&mut args)?),
+            _ if dir.is_none() && !arg.starts_with('-') => dir = Some(arg.into()),
+            _ => return Err(CliError::UnknownFlag(arg)),
+        }
+    }
+    if options.input.is_some() {
+        return Err(CliError::RegressTakesNoInput);
+    }
+    Ok(Command::Regress {
+        dir: dir.ok_or(CliError::MissingValue("regress"))?,
+        selection: options.selection.unwrap_or(Selection::All),
+        part: options.part,
+        jobs,
+        quiet,
+        time_limit,
+    })
+}
+
+/// Every day is fuzzed unless `--day` picks one. The input comes from mutating the examples,
+/// so `--input` and `--part` don't apply.
+fn parse_fuzz<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let mut options = SolverOptions::default();
+    let mut runs = fuzz::DEFAULT_RUNS;
+    let mut seed = fuzz::DEFAULT_SEED;
+    let mut quiet = false;
+    while let Some(arg) = args.next() {
+        if matches!(arg.as_str(), "--part" | "-p" | "--input" | "-i") {
+            return Err(CliError::UnknownFlag(arg));
+        }
+        if options.accept(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--runs" => {
+                let value = next_value(&mut args, "--runs")?;
+                runs = match value.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(CliError::InvalidRuns(value)),
+                };
+            }
+            "--seed" => {
+                let value = next_value(&mut args, "--seed")?;
+                seed = value.parse().map_err(|_| CliError::InvalidSeed(value))?;
+            }
+            "--quiet" | "-q" => quiet = true,
+            _ => return Err(CliError::UnknownFlag(arg)),
+        }
+    }
+    Ok(Command::Fuzz {
+        selection: options.selection.unwrap_or(Selection::All),
+        runs,
+        seed,
+        quiet,
+    })
+}
+
+/// A generated input is for one day, and there's nothing to run or read, so only `--day` of
+/// the shared options applies.
+fn parse_generate<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let mut options = SolverOptions::default();
+    let mut size = DEFAULT_GENERATE_SIZE;
+    let mut seed = DEFAULT_GENERATE_SEED;
+    let mut out = None;
+    while let Some(arg) = args.next() {
+        if matches!(arg.as_str(), "--part" | "-p" | "--input" | "-i") {
+            return Err(CliError::UnknownFlag(arg));
+        }
+        if options.accept(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--size" => {
+                let value = next_value(&mut args, "--size")?;
+                size = match value.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(CliError::InvalidSize(value)),
+                };
+            }
+            "--seed" => {
+                let value = next_value(&mut args, "--seed")?;
+                seed = value.parse().map_err(|_| CliError::InvalidSeed(value))?;
+            }
+            "--out" | "-o" => out = Some(next_value(&mut args, "--out")?.into()),
+            _ => return Err(CliError::UnknownFlag(arg)),
+        }
+    }
+    match options.selection {
+        Some(Selection::Day(day)) => Ok(Command::Generate {
+            day,
+            size,
+            seed,
+            out,
+        }),
+        _ => Err(CliError::GenerateNeedsDay),
+    }
+}
+
+/// The session picks its input and parts as it goes, so only `--day` of the shared options
+/// applies, and only to say which day to start on.
+fn parse_repl<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let mut options = SolverOptions::default();
+    let mut time_limit = None;
+    while let Some(arg) = args.next() {
+        if matches!(arg.as_str(), "--part" | "-p" | "--input" | "-i") {
+            return Err(CliError::UnknownFlag(arg));
+        }
+        if options.accept(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--time-limit" => time_limit = Some(parse_time_limit(&mut args)?),
+            _ => return Err(CliError::UnknownFlag(arg)),
+        }
+    }
+    let day = match options.selection {
+        None => None,
+        Some(Selection::Day(day)) => Some(day),
+        Some(Selection::All) => return Err(CliError::ReplTakesOneDay),
+    };
+    Ok(Command::Repl { day, time_limit })
+}
+
+fn parse_serve<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let mut port = serve::DEFAULT_PORT;
+    let mut time_limit = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => {
+                let value = next_value(&mut args, "--port")?;
+                port = value.parse().map_err(|_| CliError::InvalidPort(value))?;
+            }
+            "--time-limit" => time_limit = Some(parse_time_limit(&mut args)?),
+            _ => return Err(CliError::UnknownFlag(arg)),
+        }
+    }
+    Ok(Command::Serve { port, time_limit })
+}
+
+fn parse_cache<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let command = match next_value(&mut args, "cache")?.as_str() {
+        "list" => CacheCommand::List,
+        "verify" => CacheCommand::Verify,
+        "import" => CacheCommand::Import(next_value(&mut args, "cache import")?.into()),
+        other => return Err(CliError::UnknownCommand(format!("cache {}", other))),
+    };
+    match args.next() {
+        Some(arg) => Err(CliError::UnknownFlag(arg)),
+        None => Ok(Command::Cache(command)),
+    }
+}
+
+fn parse_new_day<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let day = next_value(&mut args, "new-day")?;
+    let day = day.parse().map_err(|_| CliError::InvalidDay(day))?;
+    match args.next() {
+        Some(arg) => Err(CliError::UnknownFlag(arg)),
+        None => Ok(Command::NewDay { day }),
+    }
+}
+
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Command, CliError> {
+    let mut args = args.into_iter();
+    match args.next().as_deref() {
+        None => Err(CliError::MissingCommand),
+        Some("run") => parse_run(args),
+        Some("verify") => parse_verify(args),
+        Some("bench") => parse_bench(args),
+        Some("submit") => parse_submit(args),
+        Some("regress") => parse_regress(args),
+        Some("fuzz") => parse_fuzz(args),
+        Some("generate") => parse_generate(args),
+        Some("repl") => parse_repl(args),
+        Some("serve") => parse_serve(args),
+        Some("cache") => parse_cache(args),
+        Some("new-day") => parse_new_day(args),
+        Some("help" | "--help" | "-h") => Ok(Command::Help),
+        Some(command) => Err(CliError::UnknownCommand(command.to_owned())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Command, CliError> {
+        parse_args(args.split_whitespace().map(str::to_owned))
+    }
+
+    #[test]
+    fn run_day_and_part() {
+        assert_eq!(
+            parse("run --day 16 --part 2"),
+            Ok(Command::Run {
+                selection: Selection::Day(16),
+                part: Some(Part::Two),
+                input: InputSource::Cache,
+                format: Format::Text,
+                timings: false,
+                jobs: 1,
+                quiet: false,
+                memory: false,
+                frames: None,
+                time_limit: None,
+                explain: false,
+            })
+        );
+    }
+
+    #[test]
+    fn run_all() {
+        assert_eq!(
+            parse("run --all --timings"),
+            Ok(Command::Run {
+                selection: Selection::All,
+                part: None,
+                input: InputSource::Cache,
+                format: Format::Text,
+                timings: true,
+                jobs: 1,
+                quiet: false,
+                memory: false,
+                frames: None,
+                time_limit: None,
+                explain: false,
+            })
+        );
+    }
+
+    #[test]
+    fn verify_with_answers() {
+        assert_eq!(
+            parse("verify --all --answers mine.txt --part 1"),
+            Ok(Command::Verify {
+                selection: Selection::All,
+                part: Some(Part::One),
+                input: InputSource::Cache,
+                answers: Some("mine.txt".to_owned()),
+            })
+        );
+    }
+
+    #[test]
+    fn input_sources() {
+        assert_eq!(
+            parse("run --day 7 --input day7.txt"),
+            Ok(Command::Run {
+                selection: Selection::Day(7),
+                part: None,
+                input: InputSource::File("day7.txt".into()),
+                format: Format::Text,
+                timings: false,
+                jobs: 1,
+                quiet: false,
+                memory: false,
+                frames: None,
+                time_limit: None,
+                explain: false,
+            })
+        );
+        assert_eq!(
+            parse("verify -i - -d 7"),
+            Ok(Command::Verify {
+                selection: Selection::Day(7),
+                part: None,
+                input: InputSource::Stdin,
+                answers: None,
+            })
+        );
+        assert_eq!(
+            parse("run --all --input day7.txt"),
+            Err(CliError::InputNeedsDay)
+        );
+        assert_eq!(
+            parse("run --day 7 --input"),
+            Err(CliError::MissingValue("--input"))
+        );
+    }
+
+    #[test]
+    fn output_formats() {
+        assert_eq!(
+            parse("run --all --format json"),
+            Ok(Command::Run {
+                selection: Selection::All,
+                part: None,
+                input: InputSource::Cache,
+                format: Format::Json,
+                timings: false,
+                jobs: 1,
+                quiet: false,
+                memory: false,
+                frames: None,
+                time_limit: None,
+                explain: false,
+            })
+        );
+        assert_eq!(
+            parse("run -d 10 -f csv").map(|command| match command {
+                Command::Run { format, .. } => Some(format),
+                _ => None,
+            }),
+            Ok(Some(Format::Csv))
+        );
+        assert_eq!(
+            parse("run --all --jobs 4 --timings --quiet"),
+            Ok(Command::Run {
+                selection: Selection::All,
+                part: None,
+                input: InputSource::Cache,
+                format: Format::Text,
+                timings: true,
+                jobs: 4,
+                quiet: true,
+                memory: false,
+                frames: None,
+                time_limit: None,
+                explain: false,
+            })
+        );
+        assert_eq!(
+            parse("run --all -j 0"),
+            Err(CliError::InvalidJobs("0".to_owned()))
+        );
+        assert_eq!(
+            parse("run --all --format yaml"),
+            Err(CliError::InvalidFormat("yaml".to_owned()))
+        );
+    }
+
+    #[test]
+    fn memory() {
+        assert_eq!(
+            parse("run --all --memory").map(|command| match command {
+                Command::Run {
+                    timings, memory, ..
+                } => Some((timings, memory)),
+                _ => None,
+            }),
+            Ok(Some((true, true)))
+        );
+    }
+
+    #[test]
+    fn frames() {
+        assert_eq!(
+            parse("run --day 14 --frames out --steps 10-20 --image ppm").map(
+                |command| match command {
+                    Command::Run { frames, .. } => frames,
+                    _ => None,
+                }
+            ),
+            Ok(Some(Frames {
+                dir: "out".into(),
+                format: ImageFormat::Ppm,
+                steps: 10..=20,
+            }))
+        );
+        assert_eq!(
+            parse("run --day 23 --frames out --steps 7").map(|command| match command {
+                Command::Run { frames, .. } => frames.map(|frames| frames.steps),
+                _ => None,
+            }),
+            Ok(Some(7..=7))
+        );
+        assert_eq!(
+            parse("run --day 14 --steps 1-5"),
+            Err(CliError::FrameOptionsNeedFrames)
+        );
+        assert_eq!(
+            parse("run --day 14 --frames out --steps 5-1"),
+            Err(CliError::InvalidSteps("5-1".to_owned()))
+        );
+        assert_eq!(
+            parse("run --day 14 --frames out --image gif"),
+            Err(CliError::InvalidImageFormat("gif".to_owned()))
+        );
+        assert_eq!(
+            parse("run --day 14 --frames out --timings"),
+            Err(CliError::FramesWithTimings)
+        );
+    }
+
+    #[test]
+    fn explain() {
+        assert_eq!(
+            parse("run --day 21 --part 2 --explain --format json").map(|command| match command {
+                Command::Run { explain, .. } => explain,
+                _ => false,
+            }),
+            Ok(true)
+        );
+        assert_eq!(
+            parse("run --day 7 --explain --timings"),
+            Err(CliError::ExplainWithTimings)
+        );
+        assert_eq!(
+            parse("run --day 7 --explain --format csv"),
+            Err(CliError::ExplainAsCsv)
+        );
+        // -e is bench's --examples.
+        assert_eq!(
+            parse("run --day 7 -e"),
+            Err(CliError::UnknownFlag("-e".to_owned()))
+        );
+    }
+
+    #[test]
+    fn time_limit() {
+        assert_eq!(
+            parse("run --all --time-limit 0.5").map(|command| match command {
+                Command::Run { time_limit, .. } => time_limit,
+                _ => None,
+            }),
+            Ok(Some(Duration::from_millis(500)))
+        );
+        assert_eq!(
+            parse("regress team --time-limit 10").map(|command| match command {
+                Command::Regress { time_limit, .. } => time_limit,
+                _ => None,
+            }),
+            Ok(Some(Duration::from_secs(10)))
+        );
+        assert_eq!(
+            parse("run --all --time-limit 0"),
+            Err(CliError::InvalidTimeLimit("0".to_owned()))
+        );
+        assert_eq!(
+            parse("run --all --time-limit soon"),
+            Err(CliError::InvalidTimeLimit("soon".to_owned()))
+        );
+    }
+
+    #[test]
+    fn bench_options() {
+        assert_eq!(
+            parse("bench --all --examples --samples 5 --save base.txt"),
+            Ok(Command::Bench {
+                selection: Selection::All,
+                part: None,
+                input: InputSource::Cache,
+                examples: true,
+                samples: 5,
+                save: Some("base.txt".to_owned()),
+                baseline: None,
+            })
+        );
+        assert_eq!(
+            parse("bench --day 16 --part 2 --baseline base.txt"),
+            Ok(Command::Bench {
+                selection: Selection::Day(16),
+                part: Some(Part::Two),
+                input: InputSource::Cache,
+                examples: false,
+                samples: DEFAULT_SAMPLES,
+                save: None,
+                baseline: Some("base.txt".to_owned()),
+            })
+        );
+        assert_eq!(
+            parse("bench --day 1 --samples 0"),
+            Err(CliError::InvalidSamples("0".to_owned()))
+        );
+    }
+
+    #[test]
+    fn submit() {
+        assert_eq!(
+            parse("submit --day 3 --part 2 --base-url http://localhost:8000"),
+            Ok(Command::Submit {
+                day: 3,
+                part: Part::Two,
+                input: InputSource::Cache,
+                base_url: Some("http://localhost:8000".to_owned()),
+            })
+        );
+        assert_eq!(
+            parse("submit --day 3"),
+            Err(CliError::SubmitNeedsDayAndPart)
+        );
+        assert_eq!(
+            parse("submit --all --part 1"),
+            Err(CliError::SubmitNeedsDayAndPart)
+        );
+    }
+
+    #[test]
+    fn regress() {
+        assert_eq!(
+            parse("regress team --part 2 -j 4"),
+            Ok(Command::Regress {
+                dir: "team".into(),
+                selection: Selection::All,
+                part: Some(Part::Two),
+                jobs: 4,
+                quiet: false,
+                time_limit: None,
+            })
+        );
+        assert_eq!(
+            parse("regress team --day 22 --quiet").map(|command| match command {
+                Command::Regress { selection, .. } => Some(selection),
+                _ => None,
+            }),
+            Ok(Some(Selection::Day(22)))
+        );
+        assert_eq!(
+            parse("regress team --day 22 --input day22.txt"),
+            Err(CliError::RegressTakesNoInput)
+        );
+        assert_eq!(
+            parse("regress --day 3 -q team"),
+            Ok(Command::Regress {
+                dir: "team".into(),
+                selection: Selection::Day(3),
+                part: None,
+                jobs: 1,
+                quiet: true,
+                time_limit: None,
+            })
+        );
+        assert_eq!(parse("regress"), Err(CliError::MissingValue("regress")));
+        assert_eq!(
+            parse("regress --quiet"),
+            Err(CliError::MissingValue("regress"))
+        );
+        assert_eq!(
+            parse("regress --days 1-5 team"),
+            Err(CliError::UnknownFlag("--days".to_owned()))
+        );
+        assert_eq!(
+            parse("regress team other"),
+            Err(CliError::UnknownFlag("other".to_owned()))
+        );
+    }
+
+    #[test]
+    fn fuzz() {
+        assert_eq!(
+            parse("fuzz"),
+            Ok(Command::Fuzz {
+                selection: Selection::All,
+                runs: fuzz::DEFAULT_RUNS,
+                seed: fuzz::DEFAULT_SEED,
+                quiet: false,
+            })
+        );
+        assert_eq!(
+            parse("fuzz --day 6 --runs 50 --seed 7 -q"),
+            Ok(Command::Fuzz {
+                selection: Selection::Day(6),
+                runs: 50,
+                seed: 7,
+                quiet: true,
+            })
+        );
+        assert_eq!(
+            parse("fuzz --runs 0"),
+            Err(CliError::InvalidRuns("0".to_owned()))
+        );
+        assert_eq!(
+            parse("fuzz --seed x"),
+            Err(CliError::InvalidSeed("x".to_owned()))
+        );
+        assert_eq!(
+            parse("fuzz --day 6 --input day6.txt"),
+            Err(CliError::UnknownFlag("--input".to_owned()))
+        );
+    }
+
+    #[test]
+    fn generate() {
+        assert_eq!(
+            parse("generate --day 20"),
+            Ok(Command::Generate {
+                day: 20,
+                size: DEFAULT_GENERATE_SIZE,
+                seed: DEFAULT_GENERATE_SEED,
+                out: None,
+            })
+        );
+        assert_eq!(
+            parse("generate -d 7 --size 100000 --seed 3 --out day07.txt"),
+            Ok(Command::Generate {
+                day: 7,
+                size: 100000,
+                seed: 3,
+                out: Some("day07.txt".into()),
+            })
+        );
+        assert_eq!(parse("generate"), Err(CliError::GenerateNeedsDay));
+        assert_eq!(parse("generate --all"), Err(CliError::GenerateNeedsDay));
+        assert_eq!(
+            parse("generate --day 7 --size 0"),
+            Err(CliError::InvalidSize("0".to_owned()))
+        );
+        assert_eq!(
+            parse("generate --day 7 --part 1"),
+            Err(CliError::UnknownFlag("--part".to_owned()))
+        );
+    }
+
+    #[test]
+    fn repl() {
+        assert_eq!(
+            parse("repl"),
+            Ok(Command::Repl {
+                day: None,
+                time_limit: None,
+            })
+        );
+        assert_eq!(
+            parse("repl -d 15 --time-limit 2"),
+            Ok(Command::Repl {
+                day: Some(15),
+                time_limit: Some(Duration::from_secs(2)),
+            })
+        );
+        assert_eq!(parse("repl --all"), Err(CliError::ReplTakesOneDay));
+        assert_eq!(
+            parse("repl --day 15 --input day15.txt"),
+            Err(CliError::UnknownFlag("--input".to_owned()))
+        );
+    }
+
+    #[test]
+    fn serve() {
+        assert_eq!(
+            parse("serve"),
+            Ok(Command::Serve {
+                port: serve::DEFAULT_PORT,
+                time_limit: None,
+            })
+        );
+        assert_eq!(
+            parse("serve --port 8080 --time-limit 5"),
+            Ok(Command::Serve {
+                port: 8080,
+                time_limit: Some(Duration::from_secs(5)),
+            })
+        );
+        assert_eq!(
+            parse("serve --port 65536"),
+            Err(CliError::InvalidPort("65536".to_owned()))
+        );
+        assert_eq!(
+            parse("serve --day 1"),
+            Err(CliError::UnknownFlag("--day".to_owned()))
+        );
+    }
+
+    #[test]
+    fn cache() {
+        assert_eq!(parse("cache list"), Ok(Command::Cache(CacheCommand::List)));
+        assert_eq!(
+            parse("cache import ../other/inputs"),
+            Ok(Command::Cache(CacheCommand::Import(
+                "../other/inputs".into()
+            )))
+        );
+        assert_eq!(
+            parse("cache import"),
+            Err(CliError::MissingValue("cache import"))
+        );
+        assert_eq!(
+            parse("cache clear"),
+            Err(CliError::UnknownCommand("cache clear".to_owned()))
+        );
+    }
+
+    #[test]
+    fn new_day() {
+        assert_eq!(parse("new-day 12"), Ok(Command::NewDay { day: 12 }));
+        assert_eq!(parse("new-day"), Err(CliError::MissingValue("new-day")));
+        assert_eq!(
+            parse("new-day twelve"),
+            Err(CliError::InvalidDay("twelve".to_owned()))
+        );
+        assert_eq!(
+            parse("new-day 12 --force"),
+            Err(CliError::UnknownFlag("--force".to_owned()))
+        );
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        assert_eq!(parse(""), Err(CliError::MissingCommand));
+        assert_eq!(parse("run"), Err(CliError::MissingSelection));
+        assert_eq!(
+            parse("run --day 3 --all"),
+            Err(CliError::ConflictingSelection)
+        );
+        assert_eq!(
+            parse("run --day three"),
+            Err(CliError::InvalidDay("three".to_owned()))
+        );
+        assert_eq!(
+            parse("run --day 3 --part 3"),
+            Err(CliError::InvalidPart("3".to_owned()))
+        );
+        assert_eq!(parse("run --day"), Err(CliError::MissingValue("--day")));
+        assert_eq!(
+            parse("verify --day 1 --timings"),
+            Err(CliError::UnknownFlag("--timings".to_owned()))
+        );
+    }
+}
@@ -1,28 +1,62 @@
+use crate::error::{parse_input, SolveError};
+use crate::rng::Rng;
 use advent_of_code::parse::{parsers, Parser};
 
+fn too_many_calories() -> SolveError {
+    SolveError::Invalid("the calories add up to more than fit in a u32".to_owned())
+}
+
+pub fn parse(input: &str) -> Result<Vec<u32>, SolveError> {
+    parse_input(parsers::number().many_lines("\n").list("\n"), input)?
+        .map(|mut l| {
+            l.try_fold(0u32, |sum, calories| sum.checked_add(calories))
+                .ok_or_else(too_many_calories)
+        })
+        .collect()
+}
+
+/// `size` elves' worth of snacks.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            (0..rng.range(1, 15))
+                .map(|_| format!("{}\n", rng.range(1000, 60000)))
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
 #[allow(dead_code)]
-pub fn part1(input: &str) -> u32 {
-    parsers::number()
-        .many_lines("\n")
-        .list("\n")
-        .parse(input)
-        .finish()
-        .unwrap()
-        .map(|l| l.sum())
+pub fn part1(input: &str) -> Result<u32, SolveError> {
+    parse(input)?
+        .into_iter()
         .max()
-        .unwrap()
+        .ok_or(SolveError::EmptyInput)
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> u32 {
-    let mut calories: Vec<u32> = parsers::number()
-        .many_lines("\n")
-        .list("\n")
-        .parse(input)
-        .finish()
-        .unwrap()
-        .map(|l| l.sum())
-        .collect();
+pub fn part2(input: &str) -> Result<u32, SolveError> {
+    let mut calories: Vec<u32> = parse(input)?;
+    if calories.len() < 3 {
+        return Err(SolveError::Invalid(format!(
+            "need at least 3 elves, found {}",
+            calories.len()
+        )));
+    }
     calories.sort();
-    calories.pop().unwrap() + calories.pop().unwrap() + calories.pop().unwrap()
+    calories
+        .iter()
+        .rev()
+        .take(3)
+        .try_fold(0u32, |sum, calories| sum.checked_add(*calories))
+        .ok_or_else(too_many_calories)
+}
+
+#[test]
+fn large_totals_fail_instead_of_overflowing() {
+    let invalid = Err(too_many_calories());
+    assert_eq!(part1("4000000000\n4000000000\n"), invalid);
+    assert_eq!(part2("4000000000\n\n4000000000\n\n1\n"), invalid);
+    assert_eq!(part2("4000000000\n\n1\n\n1\n"), Ok(4000000002));
 }
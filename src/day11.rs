@@ -1,7 +1,28 @@
+use crate::error::{parse_input, SolveError};
+use crate::rng::Rng;
+use crate::tuning::Tunable;
 use advent_of_code::parse::{parsers, Parser};
 
+pub const ROUNDS: Tunable = Tunable {
+    day: 11,
+    name: "rounds",
+    about: "rounds of keep away played in part 1",
+    default: 20,
+    min: 0,
+    max: 100_000,
+};
+
+pub const WORRIED_ROUNDS: Tunable = Tunable {
+    day: 11,
+    name: "worried-rounds",
+    about: "rounds played in part 2, where worry levels never drop",
+    default: 10_000,
+    min: 0,
+    max: 1_000_000,
+};
+
 #[derive(Debug, Clone, Copy)]
-enum Operation {
+pub enum Operation {
     Add(u64),
     Multiply(u64),
     Square,
@@ -18,59 +39,116 @@ impl Operation {
 }
 
 #[derive(Debug, Clone)]
-struct Monkey {
-    items: Vec<u64>,
-    operation: Operation,
-    divisor: u64,
-    true_target_id: usize,
-    false_target_id: usize,
+pub struct Monkey {
+    pub items: Vec<u64>,
+    pub operation: Operation,
+    pub divisor: u64,
+    pub true_target_id: usize,
+    pub false_target_id: usize,
 }
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::tag("Monkey ")
-            .ignore(parsers::number())
-            .skip_tag(":\n  Starting items: ")
-            .and_then(parsers::number().map(|n| n as u64).list(", ").line("\n"))
-            .skip_tag("  Operation: new = old ")
-            .and_then(
-                parsers::tag("+ ")
+pub fn parse(input: &str) -> Result<Vec<Monkey>, SolveError> {
+    let parser = parsers::tag("Monkey ")
+        .ignore(parsers::number())
+        .skip_tag(":\n  Starting items: ")
+        .and_then(parsers::number().map(|n| n as u64).list(", ").line("\n"))
+        .skip_tag("  Operation: new = old ")
+        .and_then(
+            parsers::tag("+ ")
+                .ignore(parsers::number())
+                .map(|n| Operation::Add(n as u64))
+                .or(parsers::tag("* ")
                     .ignore(parsers::number())
-                    .map(|n| Operation::Add(n as u64))
-                    .or(parsers::tag("* ")
-                        .ignore(parsers::number())
-                        .map(|n| Operation::Multiply(n as u64)))
-                    .or(parsers::tag("* old").map(|_| Operation::Square))
-                    .line("\n"),
-            )
-            .skip_tag("  Test: divisible by ")
-            .and_then(parsers::number().line("\n").map(|n| n as u64))
-            .skip_tag("    If true: throw to monkey ")
-            .and_then(parsers::number().line("\n").map(|n| n as usize))
-            .skip_tag("    If false: throw to monkey ")
-            .and_then(parsers::number().line("\n").map(|n| n as usize))
-            .map(
-                |(((((_id, list), operation), divisor), true_target_id), false_target_id)| Monkey {
-                    items: list.collect(),
-                    operation,
-                    divisor,
-                    true_target_id,
-                    false_target_id,
-                },
+                    .map(|n| Operation::Multiply(n as u64)))
+                .or(parsers::tag("* old").map(|_| Operation::Square))
+                .line("\n"),
+        )
+        .skip_tag("  Test: divisible by ")
+        .and_then(parsers::number().line("\n").map(|n| n as u64))
+        .skip_tag("    If true: throw to monkey ")
+        .and_then(parsers::number().line("\n").map(|n| n as usize))
+        .skip_tag("    If false: throw to monkey ")
+        .and_then(parsers::number().line("\n").map(|n| n as usize))
+        .map(
+            |(((((_id, list), operation), divisor), true_target_id), false_target_id)| Monkey {
+                items: list.collect(),
+                operation,
+                divisor,
+                true_target_id,
+                false_target_id,
+            },
+        )
+        .list("\n");
+    let monkeys: Vec<Monkey> = parse_input(parser, input)?.collect();
+    if monkeys.len() < 2 {
+        return Err(SolveError::Invalid(format!(
+            "need at least 2 monkeys, found {}",
+            monkeys.len()
+        )));
+    }
+    for (idx, monkey) in monkeys.iter().enumerate() {
+        if monkey.divisor == 0 {
+            return Err(SolveError::Invalid(format!(
+                "monkey {} tests divisibility by 0",
+                idx
+            )));
+        }
+        if let Some(target) = [monkey.true_target_id, monkey.false_target_id]
+            .into_iter()
+            .find(|target| *target >= monkeys.len())
+        {
+            return Err(SolveError::Invalid(format!(
+                "monkey {} throws to monkey {}, which doesn't exist",
+                idx, target
+            )));
+        }
+    }
+    Ok(monkeys)
+}
+
+/// Two to eight monkeys passing `size` items around. Each tests a different prime, and none
+/// squares the worry level, so part 2's running modulus keeps every level well inside a `u64`.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut divisors = [2, 3, 5, 7, 11, 13, 17, 19];
+    rng.shuffle(&mut divisors);
+    let monkeys = size.clamp(2, divisors.len());
+    let mut items: Vec<Vec<i64>> = (0..monkeys).map(|_| vec![rng.range(50, 99)]).collect();
+    for _ in monkeys..size {
+        items[rng.range(0, monkeys as i64 - 1) as usize].push(rng.range(50, 99));
+    }
+    let notes: Vec<String> = items
+        .iter()
+        .enumerate()
+        .map(|(id, items)| {
+            let operation = match rng.range(0, 1) {
+                0 => format!("+ {}", rng.range(1, 8)),
+                _ => format!("* {}", rng.range(2, 3)),
+            };
+            // Anyone but themselves.
+            let target = |rng: &mut Rng| (id + rng.range(1, monkeys as i64 - 1) as usize) % monkeys;
+            let (if_true, if_false) = (target(rng), target(rng));
+            let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+            let note = format!(
+                "Monkey {}:\n  Starting items: {}\n  Operation: new = old {}\n",
+                id,
+                items.join(", "),
+                operation
+            );
+            note + &format!(
+                "  Test: divisible by {}\n    If true: throw to monkey {}\n    If false: throw to monkey {}\n",
+                divisors[id], if_true, if_false
             )
-            .list("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-    };
+        })
+        .collect();
+    notes.join("\n")
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> u64 {
-    let mut monkeys: Vec<Monkey> = parse!(input).collect();
+pub fn part1(input: &str) -> Result<u64, SolveError> {
+    let mut monkeys: Vec<Monkey> = parse(input)?;
     let monkey_count = monkeys.len();
     let mut inspections: Vec<u64> = vec![0; monkey_count];
-    for _ in 0..20 {
+    for _ in 0..ROUNDS.get() {
         for monkey_idx in 0..monkey_count {
             let items = monkeys[monkey_idx].items.clone();
             monkeys[monkey_idx].items = vec![];
@@ -87,16 +165,16 @@ pub fn part1(input: &str) -> u64 {
         }
     }
     inspections.sort();
-    inspections[monkey_count - 1] * inspections[monkey_count - 2]
+    Ok(inspections[monkey_count - 1] * inspections[monkey_count - 2])
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> u64 {
-    let mut monkeys: Vec<Monkey> = parse!(input).collect();
+pub fn part2(input: &str) -> Result<u64, SolveError> {
+    let mut monkeys: Vec<Monkey> = parse(input)?;
     let modulus: u64 = monkeys.iter().map(|monkey| monkey.divisor).product();
     let monkey_count = monkeys.len();
     let mut inspections: Vec<u64> = vec![0; monkey_count];
-    for _ in 0..10000 {
+    for _ in 0..WORRIED_ROUNDS.get() {
         for monkey_idx in 0..monkey_count {
             let items = monkeys[monkey_idx].items.clone();
             monkeys[monkey_idx].items = vec![];
@@ -113,71 +191,5 @@ pub fn part2(input: &str) -> u64 {
         }
     }
     inspections.sort();
-    inspections[monkey_count - 1] * inspections[monkey_count - 2]
-}
-
-#[test]
-fn part1_test() {
-    let input = "Monkey 0:
-  Starting items: 79, 98
-  Operation: new = old * 19
-  Test: divisible by 23
-    If true: throw to monkey 2
-    If false: throw to monkey 3
-
-Monkey 1:
-  Starting items: 54, 65, 75, 74
-  Operation: new = old + 6
-  Test: divisible by 19
-    If true: throw to monkey 2
-    If false: throw to monkey 0
-
-Monkey 2:
-  Starting items: 79, 60, 97
-  Operation: new = old * old
-  Test: divisible by 13
-    If true: throw to monkey 1
-    If false: throw to monkey 3
-
-Monkey 3:
-  Starting items: 74
-  Operation: new = old + 3
-  Test: divisible by 17
-    If true: throw to monkey 0
-    If false: throw to monkey 1
-";
-    assert_eq!(part1(input), 10605);
-}
-
-#[test]
-fn part2_test() {
-    let input = "Monkey 0:
-  Starting items: 79, 98
-  Operation: new = old * 19
-  Test: divisible by 23
-    If true: throw to monkey 2
-    If false: throw to monkey 3
-
-Monkey 1:
-  Starting items: 54, 65, 75, 74
-  Operation: new = old + 6
-  Test: divisible by 19
-    If true: throw to monkey 2
-    If false: throw to monkey 0
-
-Monkey 2:
-  Starting items: 79, 60, 97
-  Operation: new = old * old
-  Test: divisible by 13
-    If true: throw to monkey 1
-    If false: throw to monkey 3
-
-Monkey 3:
-  Starting items: 74
-  Operation: new = old + 3
-  Test: divisible by 17
-    If true: throw to monkey 0
-    If false: throw to monkey 1
-";
-    assert_eq!(part2(input), 2713310158);
+    Ok(inspections[monkey_count - 1] * inspections[monkey_count - 2])
 }
@@ -1,22 +1,48 @@
+use crate::error::{check_rectangular, parse_input, SolveError};
+use crate::rng::Rng;
 use advent_of_code::{
     grid::{Grid, GridPoint, PLUS_ADJACENT},
     parse::{parsers, Parser},
     search::WeightedGraph,
 };
 
-macro_rules! parser {
-    ($input: ident) => {{
-        let vec_of_vecs = parsers::chars(|c| c.is_alphabetic())
-            .map(|c| u8::try_from(c).unwrap())
-            .many()
-            .many_lines("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-            .map(|v| v.collect::<Vec<u8>>())
-            .collect::<Vec<Vec<u8>>>();
-        Grid::of_vec_of_vecs(vec_of_vecs).unwrap()
-    }};
+pub fn parse(input: &str) -> Result<Grid<u8>, SolveError> {
+    let parser = parsers::chars(|c| c.is_ascii_alphabetic())
+        .map(|c| u8::try_from(c).unwrap())
+        .many()
+        .many_lines("\n");
+    let vec_of_vecs = parse_input(parser, input)?
+        .map(|v| v.collect::<Vec<u8>>())
+        .collect::<Vec<Vec<u8>>>();
+    check_rectangular(&vec_of_vecs)?;
+    Ok(Grid::of_vec_of_vecs(vec_of_vecs).unwrap())
+}
+
+/// A heightmap `size` rows high, rising steadily from `S` on the left to `E` on the right
+/// along one row, with everything off that row no higher than the path in the same column.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let (rows, cols) = (size.max(1), size + 26);
+    let path = rng.range(0, rows as i64 - 1) as usize;
+    let mut map = String::new();
+    for row in 0..rows {
+        for col in 0..cols {
+            // Never more than one higher than the column before.
+            let height = (col * 26 / cols) as u8;
+            map.push(match (row == path, col) {
+                (true, 0) => 'S',
+                (true, col) if col == cols - 1 => 'E',
+                (true, _) => (b'a' + height) as char,
+                _ => (b'a' + rng.range(0, height as i64) as u8) as char,
+            });
+        }
+        map.push('\n');
+    }
+    map
+}
+
+fn find(map: &Grid<u8>, square: u8) -> Result<GridPoint<usize>, SolveError> {
+    map.find(&square)
+        .ok_or_else(|| SolveError::Invalid(format!("the map has no `{}` square", square as char)))
 }
 
 struct Mountain<F: Fn(u8, u8) -> bool> {
@@ -47,8 +73,8 @@ impl<F: Fn(u8, u8) -> bool> WeightedGraph<GridPoint<usize>, u32> for Mountain<F>
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> u32 {
-    let map: Grid<u8> = parser!(input);
+pub fn part1(input: &str) -> Result<u32, SolveError> {
+    let map: Grid<u8> = parse(input)?;
     let height_map = Grid::from(
         map.clone().into_iter().map(|c| {
             if c == b'S' {
@@ -63,7 +89,8 @@ pub fn part1(input: &str) -> u32 {
         map.cols(),
     )
     .unwrap();
-    let start_idx = map.find(&b'S').unwrap();
+    let start_idx = find(&map, b'S')?;
+    find(&map, b'E')?;
     let graph = Mountain {
         map,
         height_map,
@@ -71,13 +98,13 @@ pub fn part1(input: &str) -> u32 {
     };
     graph
         .shortest_distance(start_idx, |end| graph.map[*end] == b'E', 0)
-        .unwrap()
-        .1
+        .map(|(_, distance)| distance)
+        .ok_or_else(|| SolveError::NoSolution("no path from S to E".to_owned()))
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> u32 {
-    let map: Grid<u8> = parser!(input);
+pub fn part2(input: &str) -> Result<u32, SolveError> {
+    let map: Grid<u8> = parse(input)?;
     let height_map = Grid::from(
         map.clone().into_iter().map(|c| {
             if c == b'S' {
@@ -92,7 +119,7 @@ pub fn part2(input: &str) -> u32 {
         map.cols(),
     )
     .unwrap();
-    let start_idx = map.find(&b'E').unwrap();
+    let start_idx = find(&map, b'E')?;
     let graph = Mountain {
         map,
         height_map,
@@ -100,28 +127,6 @@ pub fn part2(input: &str) -> u32 {
     };
     graph
         .shortest_distance(start_idx, |end| graph.map[*end] == b'a', 0)
-        .unwrap()
-        .1
-}
-
-#[test]
-fn part1_test() {
-    let input = "Sabqponm
-abcryxxl
-accszExk
-acctuvwj
-abdefghi
-";
-    assert_eq!(31, part1(input));
-}
-
-#[test]
-fn part2_test() {
-    let input = "Sabqponm
-abcryxxl
-accszExk
-acctuvwj
-abdefghi
-";
-    assert_eq!(29, part2(input));
+        .map(|(_, distance)| distance)
+        .ok_or_else(|| SolveError::NoSolution("no path from E down to an `a` square".to_owned()))
 }
@@ -1,39 +1,85 @@
+use crate::error::{parse_input, SolveError};
+use crate::explain;
+use crate::rng::Rng;
 use advent_of_code::parse::{parsers, Parser};
 use itertools::Itertools;
 use std::cmp::Ordering;
 use std::collections::VecDeque;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Input {
+pub enum Input {
     LeftParen,
     RightParen,
     Number(u32),
 }
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::char('[')
-            .map(|_| Input::LeftParen)
-            .or(parsers::char(']').map(|_| Input::RightParen))
-            .or(parsers::number().map(|n| Input::Number(n)))
-            .skip(parsers::char(',').maybe())
-            .many()
-            .line("\n")
-            .repeat(2)
-            .map(|mut v| {
-                (
-                    v.next().unwrap().collect::<VecDeque<Input>>(),
-                    v.next().unwrap().collect::<VecDeque<Input>>(),
-                )
-            })
-            .list("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-    };
+fn balanced(packet: &VecDeque<Input>) -> bool {
+    let mut depth = 0_usize;
+    for token in packet {
+        match token {
+            Input::LeftParen => depth += 1,
+            Input::RightParen => match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return false,
+            },
+            Input::Number(_) => {}
+        }
+    }
+    depth == 0
+}
+
+pub fn parse(input: &str) -> Result<Vec<(VecDeque<Input>, VecDeque<Input>)>, SolveError> {
+    let parser = parsers::char('[')
+        .map(|_| Input::LeftParen)
+        .or(parsers::char(']').map(|_| Input::RightParen))
+        .or(parsers::number().map(|n| Input::Number(n)))
+        .skip(parsers::char(',').maybe())
+        .many()
+        .line("\n")
+        .repeat(2)
+        .map(|mut v| {
+            (
+                v.next().unwrap().collect::<VecDeque<Input>>(),
+                v.next().unwrap().collect::<VecDeque<Input>>(),
+            )
+        })
+        .list("\n");
+    let pairs: Vec<(VecDeque<Input>, VecDeque<Input>)> = parse_input(parser, input)?.collect();
+    for (idx, (a, b)) in pairs.iter().enumerate() {
+        if let Some(offset) = [a, b].into_iter().position(|packet| !balanced(packet)) {
+            return Err(SolveError::Invalid(format!(
+                "packet on line {} has unbalanced brackets",
+                idx * 3 + offset + 1
+            )));
+        }
+    }
+    Ok(pairs)
 }
 
-fn compare_packets(left: &VecDeque<Input>, right: &VecDeque<Input>) -> Ordering {
+/// `size` pairs of packets, nested up to four lists deep.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let left = random_packet(rng, 4);
+            format!("{}\n{}\n", left, random_packet(rng, 4))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn random_packet(rng: &mut Rng, depth: u32) -> String {
+    let items: Vec<String> = (0..rng.range(0, 4))
+        .map(|_| match rng.range(0, 2) {
+            0 if depth > 0 => random_packet(rng, depth - 1),
+            _ => rng.range(0, 10).to_string(),
+        })
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+/// Orders two packets by the puzzle's rules: lists compare element by element, and a number
+/// compared with a list is treated as a list holding just that number.
+pub fn compare_packets(left: &VecDeque<Input>, right: &VecDeque<Input>) -> Ordering {
     let mut a = left.clone();
     let mut b = right.clone();
     loop {
@@ -75,21 +121,24 @@ fn compare_packets(left: &VecDeque<Input>, right: &VecDeque<Input>) -> Ordering
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> usize {
-    parse!(input)
+pub fn part1(input: &str) -> Result<usize, SolveError> {
+    Ok(parse(input)?
+        .into_iter()
         .enumerate()
         .filter_map(|(idx, (a, b))| {
             if compare_packets(&a, &b) == Ordering::Less {
+                explain::step("in order", format_args!("pair {}", idx + 1));
                 Some(idx + 1)
             } else {
                 None
             }
         })
-        .sum()
+        .sum())
 }
 #[allow(dead_code)]
-pub fn part2(input: &str) -> usize {
-    let mut packets: Vec<VecDeque<Input>> = parse!(input)
+pub fn part2(input: &str) -> Result<usize, SolveError> {
+    let mut packets: Vec<VecDeque<Input>> = parse(input)?
+        .into_iter()
         .flat_map(|(a, b)| vec![a, b].into_iter())
         .collect();
     let div_2 = VecDeque::from([
@@ -109,64 +158,13 @@ pub fn part2(input: &str) -> usize {
     packets.push(div_2.clone());
     packets.push(div_6.clone());
     packets.sort_by(compare_packets);
-    (1 + packets.iter().find_position(|p| p == &&div_2).unwrap().0)
-        * (1 + packets.iter().find_position(|p| p == &&div_6).unwrap().0)
-}
-
-#[test]
-fn part1_test() {
-    let input = "[1,1,3,1,1]
-[1,1,5,1,1]
-
-[[1],[2,3,4]]
-[[1],4]
-
-[9]
-[[8,7,6]]
-
-[[4,4],4,4]
-[[4,4],4,4,4]
-
-[7,7,7,7]
-[7,7,7]
-
-[]
-[3]
-
-[[[]]]
-[[]]
-
-[1,[2,[3,[4,[5,6,7]]]],8,9]
-[1,[2,[3,[4,[5,6,0]]]],8,9]
-";
-    assert_eq!(part1(input), 13);
-}
-
-#[test]
-fn part2_test() {
-    let input = "[1,1,3,1,1]
-[1,1,5,1,1]
-
-[[1],[2,3,4]]
-[[1],4]
-
-[9]
-[[8,7,6]]
-
-[[4,4],4,4]
-[[4,4],4,4,4]
-
-[7,7,7,7]
-[7,7,7]
-
-[]
-[3]
-
-[[[]]]
-[[]]
-
-[1,[2,[3,[4,[5,6,7]]]],8,9]
-[1,[2,[3,[4,[5,6,0]]]],8,9]
-";
-    assert_eq!(part2(input), 140);
+    let position = |divider: &VecDeque<Input>, written: &str| {
+        let position = 1 + packets.iter().find_position(|p| p == &divider).unwrap().0;
+        explain::step(
+            "divider",
+            format_args!("{} is packet {}", written, position),
+        );
+        position
+    };
+    Ok(position(&div_2, "[[2]]") * position(&div_6, "[[6]]"))
 }
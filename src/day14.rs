@@ -1,40 +1,94 @@
+use crate::error::{parse_input, SolveError};
+use crate::render::{self, Bounds, Frame, Style, EMPTY};
+use crate::rng::Rng;
 use advent_of_code::{
     grid::{GridPoint, GridPointDelta, Lattice, SOUTH, SOUTHEAST, SOUTHWEST},
     parse::{parsers, Parser},
 };
 use std::cmp::max;
 const DOWN: [GridPointDelta<isize>; 3] = [SOUTH, SOUTHWEST, SOUTHEAST];
+const ROCK: Style = Style::new('#', [140, 120, 100]);
+const SAND: Style = Style::new('o', [230, 200, 120]);
+const SOURCE: Style = Style::new('+', [255, 80, 80]);
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::signed_number()
-            .pair(",", parsers::signed_number())
-            .map(|(a, b)| GridPoint::new(a as isize, b as isize))
-            .list(" -> ")
-            .map(|v| v.collect::<Vec<GridPoint<isize>>>())
-            .many_lines("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-    };
+pub fn parse(input: &str) -> Result<Vec<Vec<GridPoint<isize>>>, SolveError> {
+    let parser = parsers::signed_number()
+        .pair(",", parsers::signed_number())
+        .map(|(a, b)| GridPoint::new(a as isize, b as isize))
+        .list(" -> ")
+        .map(|v| v.collect::<Vec<GridPoint<isize>>>())
+        .many_lines("\n");
+    Ok(parse_input(parser, input)?.collect())
 }
 
-#[allow(dead_code)]
-pub fn part1(input: &str) -> u32 {
+/// `size` rock paths below the source, spread wider and deeper as there are more of them.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let spread = 10 + size as i64;
+    (0..size.max(1))
+        .map(|_| {
+            let (mut x, mut y) = (500 + rng.range(-spread, spread), rng.range(1, spread));
+            let mut points = vec![format!("{},{}", x, y)];
+            for segment in 0..rng.range(1, 4) {
+                // Alternately across and down, so no segment runs diagonally.
+                if segment % 2 == 0 {
+                    x += rng.range(1, 5) * *rng.choose(&[-1, 1]);
+                } else {
+                    y += rng.range(1, 5);
+                }
+                points.push(format!("{},{}", x, y));
+            }
+            points.join(" -> ") + "\n"
+        })
+        .collect()
+}
+
+/// Draws the rock paths, returning them along with the lowest rock's depth.
+fn scan(input: &str) -> Result<(Lattice<char>, isize), SolveError> {
     let mut grid: Lattice<char> = Lattice::empty();
     let mut max_y: isize = 0;
-    parse!(input).for_each(|line| {
+    for (line_idx, line) in parse(input)?.into_iter().enumerate() {
         for idx in 1..line.len() {
             let start = line[idx - 1];
             let finish = line[idx];
+            if start.row() != finish.row() && start.col() != finish.col() {
+                return Err(SolveError::Invalid(format!(
+                    "path on line {} runs diagonally from {},{} to {},{}",
+                    line_idx + 1,
+                    start.row(),
+                    start.col(),
+                    finish.row(),
+                    finish.col()
+                )));
+            }
             start.traverse_to(finish).unwrap().for_each(|idx| {
                 max_y = max(*idx.col(), max_y);
                 grid.set(idx, '#');
             })
         }
+    }
+    Ok((grid, max_y))
+}
+
+/// The cave after a grain of sand comes to rest, wide enough for the pile in part 2. Points
+/// are stored as `(x, y)`, so they are flipped to draw `y` going down.
+fn cave_frame(grid: &Lattice<char>, max_y: isize) -> Frame {
+    let bounds = Bounds::new(0, max_y + 3, 500 - max_y - 3, 500 + max_y + 4);
+    let mut frame = Frame::draw(bounds, |point| {
+        match grid.get(GridPoint::new(*point.col(), *point.row())) {
+            Some('#') => ROCK,
+            Some(_) => SAND,
+            None => EMPTY,
+        }
     });
-    (0..)
-        .find(|_| {
+    frame.set(GridPoint::new(0, 500), SOURCE);
+    frame
+}
+
+#[allow(dead_code)]
+pub fn part1(input: &str) -> Result<u32, SolveError> {
+    let (mut grid, max_y) = scan(input)?;
+    Ok((0..)
+        .find(|grain| {
             let mut sand = GridPoint::new(500, 0);
             while let Some(next_sand) = DOWN.into_iter().find_map(|delta| {
                 let next_sand = (sand + delta)?;
@@ -47,27 +101,17 @@ pub fn part1(input: &str) -> u32 {
                 sand = next_sand;
             }
             grid.set(sand, 'o');
+            render::frame(*grain as usize, || cave_frame(&grid, max_y));
             *sand.col() >= max_y
         })
-        .unwrap()
+        .unwrap())
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> u32 {
-    let mut grid: Lattice<char> = Lattice::empty();
-    let mut max_y: isize = 0;
-    parse!(input).for_each(|line| {
-        for idx in 1..line.len() {
-            let start = line[idx - 1];
-            let finish = line[idx];
-            start.traverse_to(finish).unwrap().for_each(|idx| {
-                max_y = max(*idx.col(), max_y);
-                grid.set(idx, '#');
-            })
-        }
-    });
-    (1..)
-        .find(|_| {
+pub fn part2(input: &str) -> Result<u32, SolveError> {
+    let (mut grid, max_y) = scan(input)?;
+    Ok((1..)
+        .find(|grain| {
             let mut sand = GridPoint::new(500, 0);
             while let Some(next_sand) = DOWN.into_iter().find_map(|delta| {
                 let next_sand = (sand + delta)?;
@@ -80,27 +124,12 @@ pub fn part2(input: &str) -> u32 {
                 sand = next_sand;
             }
             grid.set(sand, 'o');
+            render::frame(*grain as usize, || cave_frame(&grid, max_y));
             if *sand.col() == 0 {
                 return true;
             } else {
                 return false;
             }
         })
-        .unwrap()
-}
-
-#[test]
-fn part1_test() {
-    let input = "498,4 -> 498,6 -> 496,6
-503,4 -> 502,4 -> 502,9 -> 494,9
-";
-    assert_eq!(part1(input), 24);
-}
-
-#[test]
-fn part2_test() {
-    let input = "498,4 -> 498,6 -> 496,6
-503,4 -> 502,4 -> 502,9 -> 494,9
-";
-    assert_eq!(part2(input), 93);
+        .unwrap())
 }
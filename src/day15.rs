@@ -1,3 +1,10 @@
+#[cfg(test)]
+use crate::differential;
+use crate::error::{parse_input, SolveError};
+use crate::rng::Rng;
+use crate::tuning::Tunable;
+#[cfg(test)]
+use crate::tuning::{self, Settings};
 use advent_of_code::{
     grid::GridPoint,
     parse::{parsers, Parser},
@@ -7,35 +14,94 @@ use std::{
     ops::AddAssign,
 };
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::tag("Sensor at x=")
-            .ignore(
-                parsers::signed_number()
-                    .pair(", y=", parsers::signed_number())
-                    .map(|(x, y)| GridPoint::new(y as i64, x as i64)),
-            )
-            .skip_tag(": closest beacon is at x=")
-            .and_then(
-                parsers::signed_number()
-                    .pair(", y=", parsers::signed_number())
-                    .map(|(x, y)| GridPoint::new(y as i64, x as i64)),
+pub const ROW: Tunable = Tunable {
+    day: 15,
+    name: "row",
+    about: "row part 1 counts the positions a beacon can't be in",
+    default: 2_000_000,
+    min: i32::MIN as i64,
+    max: i32::MAX as i64,
+};
+
+pub const MAX: Tunable = Tunable {
+    day: 15,
+    name: "max",
+    about: "largest x and y part 2 searches for the distress beacon",
+    default: 4_000_000,
+    min: 0,
+    max: 4_000_000,
+};
+
+pub fn parse(input: &str) -> Result<HashMap<GridPoint<i64>, GridPoint<i64>>, SolveError> {
+    let parser = parsers::tag("Sensor at x=")
+        .ignore(
+            parsers::signed_number()
+                .pair(", y=", parsers::signed_number())
+                .map(|(x, y)| GridPoint::new(y as i64, x as i64)),
+        )
+        .skip_tag(": closest beacon is at x=")
+        .and_then(
+            parsers::signed_number()
+                .pair(", y=", parsers::signed_number())
+                .map(|(x, y)| GridPoint::new(y as i64, x as i64)),
+        )
+        .many_lines("\n");
+    Ok(parse_input(parser, input)?.collect::<HashMap<GridPoint<i64>, GridPoint<i64>>>())
+}
+
+/// How far the search area of a generated input reaches for each sensor in it, so that size
+/// 100 gets the puzzle's area.
+const AREA_PER_SENSOR: i64 = 40_000;
+
+/// `size` sensors scattered over a search area from 0 to `AREA_PER_SENSOR` times `size` (at
+/// most the `max` tunable), plus four just outside its corners which between them cover every
+/// position in it but one. Part 2 finds that position with `max` set to the same bound.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let max = (size as i64).saturating_mul(AREA_PER_SENSOR).min(MAX.get());
+    sensors_around(rng, size, max)
+}
+
+/// `generate` for a search area from 0 to `max`. Sensors inside it are kept short of the
+/// hidden position; each corner sensor reaches right up to it, which covers all of the area
+/// on that side of it.
+fn sensors_around(rng: &mut Rng, size: usize, max: i64) -> String {
+    let hidden = (rng.range(0, max), rng.range(0, max));
+    let distance = |(x, y): (i64, i64)| (x - hidden.0).abs() + (y - hidden.1).abs();
+    let mut sensors: Vec<((i64, i64), (i64, i64))> = [-1, max + 1]
+        .into_iter()
+        .flat_map(|x| [(x, -1), (x, max + 1)])
+        .map(|corner| (corner, (corner.0, corner.1 - (distance(corner) - 1))))
+        .collect();
+    for _ in 0..size {
+        let sensor = (rng.range(0, max), rng.range(0, max));
+        if sensor == hidden {
+            continue;
+        }
+        // Part 2 goes through every row a sensor reaches, so keep the rest of them small.
+        let reach = rng.range(0, (distance(sensor) - 1).min(max / 10));
+        let across = rng.range(-reach, reach);
+        let down = (reach - across.abs()) * *rng.choose(&[-1, 1]);
+        sensors.push((sensor, (sensor.0 + across, sensor.1 + down)));
+    }
+    rng.shuffle(&mut sensors);
+    sensors
+        .into_iter()
+        .map(|((x, y), (beacon_x, beacon_y))| {
+            format!(
+                "Sensor at x={}, y={}: closest beacon is at x={}, y={}\n",
+                x, y, beacon_x, beacon_y
             )
-            .many_lines("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-            .collect::<HashMap<GridPoint<i64>, GridPoint<i64>>>()
-    };
+        })
+        .collect()
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> usize {
-    part1_inner(input, 2_000_000)
+pub fn part1(input: &str) -> Result<usize, SolveError> {
+    part1_inner(input, ROW.get())
 }
 
-fn part1_inner(input: &str, row: i64) -> usize {
-    let sensors = parse!(input);
+fn part1_inner(input: &str, row: i64) -> Result<usize, SolveError> {
+    let sensors = parse(input)?;
     let mut row_data: BTreeMap<i64, i64> = BTreeMap::new();
     sensors.iter().for_each(|(sensor, beacon)| {
         let dist = beacon.sub::<i64>(*sensor).unwrap().l1_norm();
@@ -48,7 +114,7 @@ fn part1_inner(input: &str, row: i64) -> usize {
         row_data.entry(left_edge).or_insert(0).add_assign(1);
         row_data.entry(right_edge).or_insert(0).add_assign(-1);
     });
-    row_data
+    Ok(row_data
         .iter()
         .fold((i64::MIN, 0, 0), |(last_col, acc, sum), (col, adj)| {
             (
@@ -68,16 +134,16 @@ fn part1_inner(input: &str, row: i64) -> usize {
                 }
             })
             .collect::<BTreeSet<i64>>()
-            .len()
+            .len())
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> i64 {
-    part2_inner(input, 0, 4_000_000)
+pub fn part2(input: &str) -> Result<i64, SolveError> {
+    part2_inner(input, 0, MAX.get())
 }
 
-fn part2_inner(input: &str, min: i64, max: i64) -> i64 {
-    let sensors = parse!(input);
+fn part2_inner(input: &str, min: i64, max: i64) -> Result<i64, SolveError> {
+    let sensors = parse(input)?;
     let mut all_row_data: BTreeMap<i64, BTreeMap<i64, i64>> = BTreeMap::new();
     sensors.iter().for_each(|(sensor, beacon)| {
         let dist = beacon.sub::<i64>(*sensor).unwrap().l1_norm();
@@ -108,45 +174,81 @@ fn part2_inner(input: &str, min: i64, max: i64) -> i64 {
                 })
                 .1
         })
-        .unwrap()
+        .ok_or_else(|| SolveError::NoSolution("every position is covered by a sensor".to_owned()))
 }
 
 #[test]
 fn part1_test() {
-    let input = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15
-Sensor at x=9, y=16: closest beacon is at x=10, y=16
-Sensor at x=13, y=2: closest beacon is at x=15, y=3
-Sensor at x=12, y=14: closest beacon is at x=10, y=16
-Sensor at x=10, y=20: closest beacon is at x=10, y=16
-Sensor at x=14, y=17: closest beacon is at x=10, y=16
-Sensor at x=8, y=7: closest beacon is at x=2, y=10
-Sensor at x=2, y=0: closest beacon is at x=2, y=10
-Sensor at x=0, y=11: closest beacon is at x=2, y=10
-Sensor at x=20, y=14: closest beacon is at x=25, y=17
-Sensor at x=17, y=20: closest beacon is at x=21, y=22
-Sensor at x=16, y=7: closest beacon is at x=15, y=3
-Sensor at x=14, y=3: closest beacon is at x=15, y=3
-Sensor at x=20, y=1: closest beacon is at x=15, y=3
-";
-    assert_eq!(part1_inner(input, 10), 26);
+    let input = include_str!("../fixtures/day15/example.txt");
+    assert_eq!(part1_inner(input, 10), Ok(26));
 }
 
 #[test]
 fn part2_test() {
-    let input = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15
-Sensor at x=9, y=16: closest beacon is at x=10, y=16
-Sensor at x=13, y=2: closest beacon is at x=15, y=3
-Sensor at x=12, y=14: closest beacon is at x=10, y=16
-Sensor at x=10, y=20: closest beacon is at x=10, y=16
-Sensor at x=14, y=17: closest beacon is at x=10, y=16
-Sensor at x=8, y=7: closest beacon is at x=2, y=10
-Sensor at x=2, y=0: closest beacon is at x=2, y=10
-Sensor at x=0, y=11: closest beacon is at x=2, y=10
-Sensor at x=20, y=14: closest beacon is at x=25, y=17
-Sensor at x=17, y=20: closest beacon is at x=21, y=22
-Sensor at x=16, y=7: closest beacon is at x=15, y=3
-Sensor at x=14, y=3: closest beacon is at x=15, y=3
-Sensor at x=20, y=1: closest beacon is at x=15, y=3
-";
-    assert_eq!(part2_inner(input, 0, 20), 56000011);
+    let input = include_str!("../fixtures/day15/example.txt");
+    assert_eq!(part2_inner(input, 0, 20), Ok(56000011));
+}
+
+#[test]
+fn generated_area_grows_with_size() {
+    let mut settings = Settings::new();
+    tuning::set(&mut settings, 15, "max", &AREA_PER_SENSOR.to_string()).unwrap();
+    let mut rng = Rng::new(15);
+    let input = generate(&mut rng, 1);
+    assert!(tuning::with(&settings, || part2(&input)).is_ok());
+    // The corner sensors sit just outside the area; their beacons can be anywhere.
+    for size in [1, 3] {
+        let bound = size as i64 * AREA_PER_SENSOR;
+        let sensors = parse(&generate(&mut rng, size)).unwrap();
+        assert!(sensors.keys().all(|sensor| [sensor.row(), sensor.col()]
+            .iter()
+            .all(|coord| (-1..=bound + 1).contains(*coord))));
+    }
+}
+
+#[test]
+fn generated_sensors_leave_one_position() {
+    let mut rng = Rng::new(15);
+    for size in [0, 5, 20] {
+        let input = sensors_around(&mut rng, size, 20);
+        assert!(part2_inner(&input, 0, 20).is_ok(), "{}", input);
+    }
+}
+
+/// `part1_inner` by checking each position along the row against every sensor.
+#[cfg(test)]
+fn naive_part1(input: &str, row: i64) -> Result<usize, SolveError> {
+    let sensors = parse(input)?;
+    let reach = |sensor: &GridPoint<i64>, beacon: &GridPoint<i64>| {
+        beacon.sub::<i64>(*sensor).unwrap().l1_norm()
+    };
+    let widest = sensors
+        .iter()
+        .map(|(sensor, beacon)| sensor.col().abs() + reach(sensor, beacon))
+        .max()
+        .unwrap_or(0);
+    Ok((-widest..=widest)
+        .map(|col| GridPoint::new(row, col))
+        .filter(|point| {
+            !sensors.values().any(|beacon| beacon == point)
+                && sensors
+                    .iter()
+                    .any(|(sensor, beacon)| reach(sensor, point) <= reach(sensor, beacon))
+        })
+        .count())
+}
+
+#[test]
+fn part1_matches_naive() {
+    // A small area keeps the naive version's walk along row 10 short.
+    let mut settings = Settings::new();
+    tuning::set(&mut settings, 15, "max", "20").unwrap();
+    tuning::with(&settings, || {
+        differential::compare(
+            300,
+            generate,
+            |input| naive_part1(input, 10),
+            |input| part1_inner(input, 10),
+        )
+    });
 }
@@ -1,33 +1,98 @@
+use crate::deadline;
+use crate::error::{parse_input, SolveError};
+use crate::explain;
+use crate::progress;
+use crate::rng::Rng;
 use advent_of_code::{
     parse::{parsers, Parser},
     search::WeightedGraph,
 };
 use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
+use std::{
+    cmp::max,
+    collections::{HashMap, HashSet},
+};
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::tag("Valve ")
-            .ignore(parsers::char_any().repeat(2).map(|s| s.collect::<String>()))
-            .skip_tag(" has flow rate=")
-            .and_then(parsers::number())
-            .skip_tag("; ")
-            .skip(
-                parsers::tag("tunnels lead to valves ").or(parsers::tag("tunnel leads to valve ")),
-            )
-            .and_then(
-                parsers::char_any()
-                    .repeat(2)
-                    .map(|s| s.collect::<String>())
-                    .list(", ")
-                    .map(|v| v.collect::<Vec<String>>()),
-            )
-            .many_lines("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-            .collect::<Vec<((String, u32), Vec<String>)>>()
+pub fn parse(input: &str) -> Result<Vec<((String, u32), Vec<String>)>, SolveError> {
+    let parser = parsers::tag("Valve ")
+        .ignore(parsers::char_any().repeat(2).map(|s| s.collect::<String>()))
+        .skip_tag(" has flow rate=")
+        .and_then(parsers::number())
+        .skip_tag("; ")
+        .skip(parsers::tag("tunnels lead to valves ").or(parsers::tag("tunnel leads to valve ")))
+        .and_then(
+            parsers::char_any()
+                .repeat(2)
+                .map(|s| s.collect::<String>())
+                .list(", ")
+                .map(|v| v.collect::<Vec<String>>()),
+        )
+        .many_lines("\n");
+    let data = parse_input(parser, input)?.collect::<Vec<((String, u32), Vec<String>)>>();
+    let names: HashSet<&String> = data.iter().map(|((name, _), _)| name).collect();
+    if !names.contains(&"AA".to_owned()) {
+        return Err(SolveError::Invalid(
+            "there is no valve AA to start from".to_owned(),
+        ));
+    }
+    for ((name, _), tunnels) in data.iter() {
+        if let Some(missing) = tunnels.iter().find(|tunnel| !names.contains(tunnel)) {
+            return Err(SolveError::Invalid(format!(
+                "valve {} has a tunnel to unknown valve {}",
+                name, missing
+            )));
+        }
+    }
+    Ok(data)
+}
+
+/// `size` valves, about a quarter of them working, joined by tunnels into a single network.
+/// There are only 676 two-letter names, so that's as many as there can be.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let valves = size.clamp(2, 26 * 26);
+    let name = |idx: usize| {
+        let letter = |n: usize| (b'A' + n as u8) as char;
+        format!("{}{}", letter(idx / 26), letter(idx % 26))
     };
+    let mut tunnels: Vec<Vec<usize>> = vec![Vec::new(); valves];
+    // A tree through every valve, so they're all connected, and then some shortcuts.
+    for valve in 1..valves {
+        let other = rng.range(0, valve as i64 - 1) as usize;
+        tunnels[valve].push(other);
+        tunnels[other].push(valve);
+    }
+    for _ in 0..valves / 2 {
+        let a = rng.range(0, valves as i64 - 1) as usize;
+        let b = rng.range(0, valves as i64 - 1) as usize;
+        if a != b && !tunnels[a].contains(&b) {
+            tunnels[a].push(b);
+            tunnels[b].push(a);
+        }
+    }
+    let mut scan: Vec<String> = tunnels
+        .iter()
+        .enumerate()
+        .map(|(valve, tunnels)| {
+            let rate = match rng.range(0, 3) {
+                0 if valve > 0 => rng.range(1, 25),
+                _ => 0,
+            };
+            let names: Vec<String> = tunnels.iter().map(|other| name(*other)).collect();
+            let leads = match names.len() {
+                1 => "tunnel leads to valve",
+                _ => "tunnels lead to valves",
+            };
+            format!(
+                "Valve {} has flow rate={}; {} {}\n",
+                name(valve),
+                rate,
+                leads,
+                names.join(", ")
+            )
+        })
+        .collect();
+    rng.shuffle(&mut scan);
+    scan.concat()
 }
 
 fn simplify_graph(graph: &HashMap<String, Vec<String>>) -> HashMap<String, HashMap<String, u32>> {
@@ -72,15 +137,33 @@ fn filter_graph(
         .collect()
 }
 
+/// The most pressure that can be released from `node` in `time_left` minutes. The states it
+/// explored are reported once at the end, as reporting each one would slow the search down.
 fn explore(
     graph: &HashMap<String, HashMap<String, u32>>,
     valves: &HashMap<String, u32>,
     on: HashSet<String>,
     node: String,
     time_left: u32,
-) -> u32 {
+) -> Result<u32, SolveError> {
+    let mut explored = 0;
+    let best = search(graph, valves, on, node, time_left, &mut explored);
+    progress::count("states explored", explored);
+    best
+}
+
+fn search(
+    graph: &HashMap<String, HashMap<String, u32>>,
+    valves: &HashMap<String, u32>,
+    on: HashSet<String>,
+    node: String,
+    time_left: u32,
+    explored: &mut u64,
+) -> Result<u32, SolveError> {
+    *explored += 1;
+    deadline::check()?;
     match graph.get(&node) {
-        None => 0,
+        None => Ok(0),
         Some(adjacent) => adjacent
             .iter()
             .filter_map(|(next, distance)| {
@@ -89,27 +172,120 @@ fn explore(
                     new_on.insert(next.clone());
                     let additional_weight = (time_left - distance - 1) * valves.get(next)?;
                     Some(
-                        additional_weight
-                            + explore(
-                                graph,
-                                valves,
-                                new_on,
-                                next.clone(),
-                                time_left - distance - 1,
-                            ),
+                        search(
+                            graph,
+                            valves,
+                            new_on,
+                            next.clone(),
+                            time_left - distance - 1,
+                            explored,
+                        )
+                        .map(|weight| additional_weight + weight),
                     )
                 } else {
                     None
                 }
             })
-            .max()
-            .unwrap_or(0),
+            .try_fold(0, |best, weight| -> Result<u32, SolveError> {
+                Ok(max(best, weight?))
+            }),
+    }
+}
+
+/// Reports which valves `who` opens and when. The search only keeps the best pressure, so the
+/// plan is found again by following, from AA, a valve which still leads to that pressure. The
+/// states this revisits were already counted by the search, so they aren't reported again.
+fn explain_plan(
+    graph: &HashMap<String, HashMap<String, u32>>,
+    valves: &HashMap<String, u32>,
+    who: &str,
+    minutes: u32,
+) -> Result<(), SolveError> {
+    if !explain::tracing() {
+        return Ok(());
+    }
+    let (mut on, mut node, mut time_left) = (HashSet::new(), "AA".to_owned(), minutes);
+    let mut replayed = 0;
+    let mut best = search(
+        graph,
+        valves,
+        on.clone(),
+        node.clone(),
+        time_left,
+        &mut replayed,
+    )?;
+    while best > 0 {
+        let mut opened = None;
+        for (next, distance) in graph.get(&node).into_iter().flatten() {
+            let Some(rate) = valves.get(next) else {
+                continue;
+            };
+            if distance + 1 >= time_left || on.contains(next) {
+                continue;
+            }
+            let remaining = time_left - distance - 1;
+            let mut next_on = on.clone();
+            next_on.insert(next.clone());
+            let released = remaining * rate;
+            let rest = search(
+                graph,
+                valves,
+                next_on.clone(),
+                next.clone(),
+                remaining,
+                &mut replayed,
+            )?;
+            if released + rest == best {
+                opened = Some((next.clone(), next_on, remaining, released));
+                break;
+            }
+        }
+        let Some((next, next_on, remaining, released)) = opened else {
+            break;
+        };
+        explain::step(
+            "opened",
+            format_args!(
+                "{} opens {} at minute {}, releasing {}",
+                who,
+                next,
+                minutes - remaining,
+                released
+            ),
+        );
+        (on, node, time_left, best) = (next_on, next, remaining, best - released);
     }
+    Ok(())
+}
+
+/// The part of `graph` with AA and either the valves picked out by `subset`, or the rest.
+fn share_graph(
+    graph: &HashMap<String, HashMap<String, u32>>,
+    all_valves: &[String],
+    subset: &[usize],
+    picked: bool,
+) -> HashMap<String, HashMap<String, u32>> {
+    filter_graph(
+        graph,
+        all_valves
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, v)| {
+                if subset.contains(&idx) == picked {
+                    Some(v)
+                } else {
+                    None
+                }
+            })
+            .cloned()
+            .chain(vec!["AA".to_owned()].into_iter())
+            .collect(),
+    )
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> u32 {
-    let data = parse!(input);
+pub fn part1(input: &str) -> Result<u32, SolveError> {
+    let data = parse(input)?;
     let valves = data
         .iter()
         .filter_map(|((k, v), _)| if *v > 0 { Some((k.clone(), *v)) } else { None })
@@ -127,12 +303,23 @@ pub fn part1(input: &str) -> u32 {
             .chain(vec!["AA".to_owned()].into_iter())
             .collect(),
     );
-    explore(&graph, &valves, HashSet::new(), "AA".to_owned(), 30)
+    let best = explore(&graph, &valves, HashSet::new(), "AA".to_owned(), 30)?;
+    explain_plan(&graph, &valves, "I", 30)?;
+    Ok(best)
+}
+
+/// How much pressure a split of the valves between me and the elephant releases, and which
+/// valves are mine.
+type Split = (u32, Vec<usize>);
+
+fn better_split(best: Split, split: Result<Split, SolveError>) -> Result<Split, SolveError> {
+    let split = split?;
+    Ok(if split.0 > best.0 { split } else { best })
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> u32 {
-    let data = parse!(input);
+pub fn part2(input: &str) -> Result<u32, SolveError> {
+    let data = parse(input)?;
     let valves = data
         .iter()
         .filter_map(|((k, v), _)| if *v > 0 { Some((k.clone(), *v)) } else { None })
@@ -151,87 +338,76 @@ pub fn part2(input: &str) -> u32 {
             .collect(),
     );
     let all_valves = valves.keys().cloned().collect::<Vec<String>>();
-    (0..=(valves.len() + 1) / 2)
-        .filter_map(|comb| {
-            println!("{:?}", comb);
+    let largest_share = (valves.len() + 1) / 2;
+    let (best, subset) = (0..=largest_share)
+        .map(|comb| -> Result<Split, SolveError> {
+            progress::stage(format_args!(
+                "my share of {} valves, up to {}",
+                comb, largest_share
+            ));
             (0..valves.len())
                 .combinations(comb)
-                .map(|subset_idx| {
-                    let my_graph = filter_graph(
-                        &graph,
-                        all_valves
-                            .iter()
-                            .enumerate()
-                            .filter_map(|(idx, v)| {
-                                if subset_idx.contains(&idx) {
-                                    Some(v)
-                                } else {
-                                    None
-                                }
-                            })
-                            .cloned()
-                            .chain(vec!["AA".to_owned()].into_iter())
-                            .collect(),
-                    );
-                    let elephant_graph = filter_graph(
-                        &graph,
-                        all_valves
-                            .iter()
-                            .enumerate()
-                            .filter_map(|(idx, v)| {
-                                if subset_idx.contains(&idx) {
-                                    None
-                                } else {
-                                    Some(v)
-                                }
-                            })
-                            .cloned()
-                            .chain(vec!["AA".to_owned()].into_iter())
-                            .collect(),
-                    );
-                    explore(&my_graph, &valves, HashSet::new(), "AA".to_owned(), 26)
+                .map(|subset| -> Result<Split, SolveError> {
+                    progress::count("splits", 1);
+                    let my_graph = share_graph(&graph, &all_valves, &subset, true);
+                    let elephant_graph = share_graph(&graph, &all_valves, &subset, false);
+                    let total = explore(&my_graph, &valves, HashSet::new(), "AA".to_owned(), 26)?
                         + explore(
                             &elephant_graph,
                             &valves,
                             HashSet::new(),
                             "AA".to_owned(),
                             26,
-                        )
+                        )?;
+                    Ok((total, subset))
                 })
-                .max()
+                .try_fold((0, Vec::new()), better_split)
         })
-        .max()
-        .unwrap()
+        .try_fold((0, Vec::new()), better_split)?;
+    explain_plan(
+        &share_graph(&graph, &all_valves, &subset, true),
+        &valves,
+        "I",
+        26,
+    )?;
+    explain_plan(
+        &share_graph(&graph, &all_valves, &subset, false),
+        &valves,
+        "the elephant",
+        26,
+    )?;
+    Ok(best)
 }
 
 #[test]
-fn part1_test() {
-    let input = "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
-Valve BB has flow rate=13; tunnels lead to valves CC, AA
-Valve CC has flow rate=2; tunnels lead to valves DD, BB
-Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
-Valve EE has flow rate=3; tunnels lead to valves FF, DD
-Valve FF has flow rate=0; tunnels lead to valves EE, GG
-Valve GG has flow rate=0; tunnels lead to valves FF, HH
-Valve HH has flow rate=22; tunnel leads to valve GG
-Valve II has flow rate=0; tunnels lead to valves AA, JJ
-Valve JJ has flow rate=21; tunnel leads to valve II
-";
-    assert_eq!(part1(input), 1651);
+fn explained_valves_add_up_to_the_answer() {
+    let input = include_str!("../fixtures/day16/example.txt");
+    let parts: [fn(&str) -> Result<u32, SolveError>; 2] = [part1, part2];
+    for part in parts {
+        let (answer, steps) = explain::trace(|| part(input));
+        let released: u32 = steps
+            .iter()
+            .map(|step| {
+                step.detail
+                    .rsplit(' ')
+                    .next()
+                    .unwrap()
+                    .parse::<u32>()
+                    .unwrap()
+            })
+            .sum();
+        assert_eq!(answer, Ok(released));
+    }
 }
 
 #[test]
-fn part2_test() {
-    let input = "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
-Valve BB has flow rate=13; tunnels lead to valves CC, AA
-Valve CC has flow rate=2; tunnels lead to valves DD, BB
-Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
-Valve EE has flow rate=3; tunnels lead to valves FF, DD
-Valve FF has flow rate=0; tunnels lead to valves EE, GG
-Valve GG has flow rate=0; tunnels lead to valves FF, HH
-Valve HH has flow rate=22; tunnel leads to valve GG
-Valve II has flow rate=0; tunnels lead to valves AA, JJ
-Valve JJ has flow rate=21; tunnel leads to valve II
-";
-    assert_eq!(part2(input), 1707);
+fn explaining_does_not_count_states_twice() {
+    let input = include_str!("../fixtures/day16/example.txt");
+    let (_, plain) = progress::counted(|| part1(input));
+    let ((_, explained), _) = explain::trace(|| progress::counted(|| part1(input)));
+    assert!(plain.get("states explored") > 0);
+    assert_eq!(
+        explained.get("states explored"),
+        plain.get("states explored")
+    );
 }
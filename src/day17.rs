@@ -1,3 +1,9 @@
+#[cfg(test)]
+use crate::differential;
+use crate::error::{parse_input, SolveError};
+use crate::render::{self, Bounds, Frame, Style, EMPTY};
+use crate::rng::Rng;
+use crate::tuning::Tunable;
 use advent_of_code::{
     grid::{Block, GridPoint, GridPointDelta, Lattice, EAST, SOUTH, WEST},
     parse::{parsers, Parser},
@@ -5,24 +11,43 @@ use advent_of_code::{
 
 use std::{cmp::max, collections::HashMap};
 
+const ROCK: Style = Style::new('#', [170, 170, 190]);
+const FLOOR: Style = Style::new('-', [90, 70, 50]);
+/// How much of the tower a frame shows, counting down from just above its top.
+const TOWER_ROWS: isize = 40;
+
+pub const ROCKS: Tunable = Tunable {
+    day: 17,
+    name: "rocks",
+    about: "rocks dropped before part 1 measures the tower",
+    default: 2022,
+    min: 0,
+    max: 1_000_000,
+};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Move {
+pub enum Move {
     Left,
     Right,
 }
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::char('>')
-            .map(|_| Move::Right)
-            .or(parsers::char('<').map(|_| Move::Left))
-            .many()
-            .line("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-            .collect::<Vec<Move>>()
-    };
+pub fn parse(input: &str) -> Result<Vec<Move>, SolveError> {
+    let parser = parsers::char('>')
+        .map(|_| Move::Right)
+        .or(parsers::char('<').map(|_| Move::Left))
+        .many()
+        .line("\n");
+    let moves = parse_input(parser, input)?.collect::<Vec<Move>>();
+    if moves.is_empty() {
+        return Err(SolveError::EmptyInput);
+    }
+    Ok(moves)
+}
+
+/// A jet pattern `size` jets long.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let jets: String = (0..size.max(1)).map(|_| *rng.choose(&['<', '>'])).collect();
+    jets + "\n"
 }
 
 fn slice_hash(
@@ -44,8 +69,20 @@ fn slice_hash(
     hash
 }
 
-#[allow(dead_code)]
-pub fn part1(input: &str) -> isize {
+/// The top of the tower, with the floor under it while that's still in view. The tower grows
+/// towards negative rows from the floor at row 1.
+fn tower_frame(game: &Lattice<char>, height: isize) -> Frame {
+    let top = -(height + 3);
+    let bounds = Bounds::new(top, (top + TOWER_ROWS).min(2), 0, 7);
+    Frame::draw(bounds, |point| match game.get(point) {
+        _ if *point.row() == 1 => FLOOR,
+        Some(_) => ROCK,
+        None => EMPTY,
+    })
+}
+
+/// The height of the tower after dropping `count` rocks one by one.
+fn drop_rocks(moves: &[Move], count: usize) -> isize {
     let mut blocks = vec![
         Block::from(vec![
             GridPoint::new(0, 0),
@@ -82,10 +119,10 @@ pub fn part1(input: &str) -> isize {
     ]
     .into_iter()
     .cycle();
-    let mut moves = parse!(input).into_iter().cycle();
+    let mut moves = moves.iter().copied().cycle();
     let mut game: Lattice<char> = Lattice::empty();
     let mut height = 0;
-    for _ in 0..2022 {
+    for landed in 1..=count {
         let mut rock = (blocks.next().unwrap() + GridPointDelta::new(-(height + 3), 2)).unwrap();
         loop {
             let moved_rock = match moves.next() {
@@ -112,6 +149,7 @@ pub fn part1(input: &str) -> isize {
             if game.intersects_block(&lowered_rock) || *lowered_rock.max_row().unwrap() > 0 {
                 height = max(height, 1 - *rock.min_row().unwrap());
                 game.set_block(rock, '#');
+                render::frame(landed, || tower_frame(&game, height));
                 break;
             } else {
                 rock = lowered_rock;
@@ -122,7 +160,13 @@ pub fn part1(input: &str) -> isize {
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> isize {
+pub fn part1(input: &str) -> Result<isize, SolveError> {
+    Ok(drop_rocks(&parse(input)?, ROCKS.get() as usize))
+}
+
+/// The height of the tower after `count` rocks, found by dropping rocks until the top of the
+/// tower repeats and extrapolating from there. `count` must be a multiple of 5.
+fn extrapolate_height(moves: &[Move], count: isize) -> isize {
     let rocks = vec![
         Block::from(vec![
             GridPoint::new(0, 0),
@@ -157,7 +201,7 @@ pub fn part2(input: &str) -> isize {
             GridPoint::new(-1, 1),
         ]),
     ];
-    let mut moves = parse!(input).into_iter().enumerate().cycle();
+    let mut moves = moves.iter().copied().enumerate().cycle();
     let mut seen: HashMap<(usize, u64), (isize, isize)> = HashMap::new();
     let mut round_idx = 0;
     let mut game: Lattice<char> = Lattice::empty();
@@ -195,6 +239,7 @@ pub fn part2(input: &str) -> isize {
                 if game.intersects_block(&lowered_rock) || *lowered_rock.max_row().unwrap() > 0 {
                     height = max(height, 1 - *rock.min_row().unwrap());
                     game.set_block(rock, '#');
+                    render::frame(round_idx as usize, || tower_frame(&game, height));
                     break;
                 } else {
                     rock = lowered_rock;
@@ -207,10 +252,10 @@ pub fn part2(input: &str) -> isize {
         );
         let value = (round_idx, height);
         if let Some((last_round_idx, last_height)) = seen.get(&key) {
-            if (1_000_000_000_000 - last_round_idx) % (round_idx - last_round_idx) != 0 {
+            if (count - last_round_idx) % (round_idx - last_round_idx) != 0 {
                 continue;
             }
-            return (1_000_000_000_000 - last_round_idx) / (round_idx - last_round_idx)
+            return (count - last_round_idx) / (round_idx - last_round_idx)
                 * (height - last_height)
                 + last_height;
         } else {
@@ -219,16 +264,19 @@ pub fn part2(input: &str) -> isize {
     }
 }
 
-#[test]
-fn part1_test() {
-    let input = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>
-";
-    assert_eq!(part1(input), 3068);
+#[allow(dead_code)]
+pub fn part2(input: &str) -> Result<isize, SolveError> {
+    Ok(extrapolate_height(&parse(input)?, 1_000_000_000_000))
 }
 
+// The differential sizes keep the jet patterns short, so they repeat quickly enough to
+// extrapolate well within the count.
 #[test]
-fn part2_test() {
-    let input = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>
-";
-    assert_eq!(part2(input), 1_514_285_714_288);
+fn extrapolation_matches_simulation() {
+    differential::compare(
+        20,
+        generate,
+        |input| Ok(drop_rocks(&parse(input)?, 5_000)),
+        |input| Ok(extrapolate_height(&parse(input)?, 5_000)),
+    );
 }
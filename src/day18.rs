@@ -1,3 +1,5 @@
+use crate::error::{parse_input, SolveError};
+use crate::rng::Rng;
 use advent_of_code::{
     parse::{parsers, Parser},
     vector::Vector,
@@ -5,20 +7,42 @@ use advent_of_code::{
 
 use std::collections::{HashSet, VecDeque};
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::signed_number()
-            .skip_tag(",")
-            .and_then(parsers::signed_number())
-            .skip_tag(",")
-            .and_then(parsers::signed_number())
-            .map(|((a, b), c)| Vector::of_raw([a, b, c]))
-            .many_lines("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-            .collect::<HashSet<Vector<3, i32>>>()
-    };
+pub fn parse(input: &str) -> Result<HashSet<Vector<3, i32>>, SolveError> {
+    let parser = parsers::signed_number()
+        .skip_tag(",")
+        .and_then(parsers::signed_number())
+        .skip_tag(",")
+        .and_then(parsers::signed_number())
+        .map(|((a, b), c)| Vector::of_raw([a, b, c]))
+        .many_lines("\n");
+    Ok(parse_input(parser, input)?.collect::<HashSet<Vector<3, i32>>>())
+}
+
+/// A droplet of `size` cubes grown one at a time onto the cubes already there, so it's all one
+/// piece, and big ones close off pockets of air inside.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    const SIDES: [[i32; 3]; 6] = [
+        [1, 0, 0],
+        [-1, 0, 0],
+        [0, 1, 0],
+        [0, -1, 0],
+        [0, 0, 1],
+        [0, 0, -1],
+    ];
+    let mut cubes = vec![[0, 0, 0]];
+    let mut taken = HashSet::from([[0, 0, 0]]);
+    while cubes.len() < size {
+        let [x, y, z] = *rng.choose(&cubes);
+        let [dx, dy, dz] = *rng.choose(&SIDES);
+        let cube = [x + dx, y + dy, z + dz];
+        if taken.insert(cube) {
+            cubes.push(cube);
+        }
+    }
+    cubes
+        .iter()
+        .map(|[x, y, z]| format!("{},{},{}\n", x, y, z))
+        .collect()
 }
 
 fn is_external(
@@ -54,7 +78,7 @@ fn is_external(
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> usize {
+pub fn part1(input: &str) -> Result<usize, SolveError> {
     let adjacent: Vec<Vector<3, i32>> = vec![
         Vector::of_raw([1, 0, 0]),
         Vector::of_raw([-1, 0, 0]),
@@ -63,8 +87,8 @@ pub fn part1(input: &str) -> usize {
         Vector::of_raw([0, 0, 1]),
         Vector::of_raw([0, 0, -1]),
     ];
-    let pixels = parse!(input);
-    pixels
+    let pixels = parse(input)?;
+    Ok(pixels
         .iter()
         .map(|pixel| {
             adjacent
@@ -72,11 +96,11 @@ pub fn part1(input: &str) -> usize {
                 .filter(|adj| !pixels.contains(&(*pixel + **adj)))
                 .count()
         })
-        .sum()
+        .sum())
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> usize {
+pub fn part2(input: &str) -> Result<usize, SolveError> {
     let adjacent: Vec<Vector<3, i32>> = vec![
         Vector::of_raw([1, 0, 0]),
         Vector::of_raw([-1, 0, 0]),
@@ -85,9 +109,9 @@ pub fn part2(input: &str) -> usize {
         Vector::of_raw([0, 0, 1]),
         Vector::of_raw([0, 0, -1]),
     ];
-    let pixels = parse!(input);
+    let pixels = parse(input)?;
     let mut external: HashSet<Vector<3, i32>> = HashSet::from([Vector::of_raw([0, 0, 0])]);
-    pixels
+    Ok(pixels
         .iter()
         .map(|pixel| {
             adjacent
@@ -95,43 +119,5 @@ pub fn part2(input: &str) -> usize {
                 .filter(|adj| is_external(&mut external, &pixels, *pixel + **adj))
                 .count()
         })
-        .sum()
-}
-
-#[test]
-fn part1_test() {
-    let input = "2,2,2
-1,2,2
-3,2,2
-2,1,2
-2,3,2
-2,2,1
-2,2,3
-2,2,4
-2,2,6
-1,2,5
-3,2,5
-2,1,5
-2,3,5
-";
-    assert_eq!(part1(input), 64);
-}
-
-#[test]
-fn part2_test() {
-    let input = "2,2,2
-1,2,2
-3,2,2
-2,1,2
-2,3,2
-2,2,1
-2,2,3
-2,2,4
-2,2,6
-1,2,5
-3,2,5
-2,1,5
-2,3,5
-";
-    assert_eq!(part2(input), 58);
+        .sum())
 }
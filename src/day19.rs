@@ -1,16 +1,39 @@
+use crate::deadline;
+use crate::error::{parse_input, SolveError};
+use crate::progress;
+use crate::rng::Rng;
+use crate::tuning::Tunable;
 use advent_of_code::parse::{parsers, Parser};
 use std::cmp::max;
 use std::collections::HashMap;
 
+pub const MINUTES: Tunable = Tunable {
+    day: 19,
+    name: "minutes",
+    about: "minutes each blueprint has to crack geodes in part 1",
+    default: 24,
+    min: 1,
+    max: 40,
+};
+
+pub const LONG_MINUTES: Tunable = Tunable {
+    day: 19,
+    name: "long-minutes",
+    about: "minutes the first three blueprints have in part 2",
+    default: 32,
+    min: 1,
+    max: 40,
+};
+
 #[derive(Debug, Clone, Copy)]
-struct Blueprint {
-    id: u32,
-    ore_robot_ore_cost: u32,
-    clay_robot_ore_cost: u32,
-    obsidian_robot_ore_cost: u32,
-    obsidian_robot_clay_cost: u32,
-    geode_robot_ore_cost: u32,
-    geode_robot_obsidian_cost: u32,
+pub struct Blueprint {
+    pub id: u32,
+    pub ore_robot_ore_cost: u32,
+    pub clay_robot_ore_cost: u32,
+    pub obsidian_robot_ore_cost: u32,
+    pub obsidian_robot_clay_cost: u32,
+    pub geode_robot_ore_cost: u32,
+    pub geode_robot_obsidian_cost: u32,
 }
 
 impl Blueprint {
@@ -161,102 +184,130 @@ impl ProblemState {
     }
 }
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::tag("Blueprint ")
-            .ignore(parsers::number())
-            .skip_tag(": Each ore robot costs ")
-            .and_then(parsers::number())
-            .skip_tag(" ore. Each clay robot costs ")
-            .and_then(parsers::number())
-            .skip_tag(" ore. Each obsidian robot costs ")
-            .and_then(parsers::number())
-            .skip_tag(" ore and ")
-            .and_then(parsers::number())
-            .skip_tag(" clay. Each geode robot costs ")
-            .and_then(parsers::number())
-            .skip_tag(" ore and ")
-            .and_then(parsers::number())
-            .skip_tag(" obsidian.")
-            .map(|((((((a, b), c), d), e), f), g)| Blueprint {
-                id: a,
-                ore_robot_ore_cost: b,
-                clay_robot_ore_cost: c,
-                obsidian_robot_ore_cost: d,
-                obsidian_robot_clay_cost: e,
-                geode_robot_ore_cost: f,
-                geode_robot_obsidian_cost: g,
-            })
-            .many_lines("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-            .collect::<Vec<Blueprint>>()
-    };
+pub fn parse(input: &str) -> Result<Vec<Blueprint>, SolveError> {
+    let parser = parsers::tag("Blueprint ")
+        .ignore(parsers::number())
+        .skip_tag(": Each ore robot costs ")
+        .and_then(parsers::number())
+        .skip_tag(" ore. Each clay robot costs ")
+        .and_then(parsers::number())
+        .skip_tag(" ore. Each obsidian robot costs ")
+        .and_then(parsers::number())
+        .skip_tag(" ore and ")
+        .and_then(parsers::number())
+        .skip_tag(" clay. Each geode robot costs ")
+        .and_then(parsers::number())
+        .skip_tag(" ore and ")
+        .and_then(parsers::number())
+        .skip_tag(" obsidian.")
+        .map(|((((((a, b), c), d), e), f), g)| Blueprint {
+            id: a,
+            ore_robot_ore_cost: b,
+            clay_robot_ore_cost: c,
+            obsidian_robot_ore_cost: d,
+            obsidian_robot_clay_cost: e,
+            geode_robot_ore_cost: f,
+            geode_robot_obsidian_cost: g,
+        })
+        .many_lines("\n");
+    Ok(parse_input(parser, input)?.collect::<Vec<Blueprint>>())
 }
 
-fn optimize_blueprint(
+/// `size` blueprints, with costs in the same ranges as the puzzle's.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (1..=size.max(1))
+        .map(|id| {
+            format!(
+                "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. \
+                 Each obsidian robot costs {} ore and {} clay. \
+                 Each geode robot costs {} ore and {} obsidian.\n",
+                id,
+                rng.range(2, 4),
+                rng.range(2, 4),
+                rng.range(2, 4),
+                rng.range(5, 20),
+                rng.range(2, 4),
+                rng.range(5, 20)
+            )
+        })
+        .collect()
+}
+
+/// The most geodes `blueprint` can crack from `state`, with the counters for the search
+/// reported once it's done rather than for every state.
+fn optimize_blueprint(state: ProblemState, blueprint: Blueprint) -> Result<u32, SolveError> {
+    let mut known_states: HashMap<ProblemState, u32> = HashMap::new();
+    let mut memo_hits = 0;
+    let best = search(&mut known_states, &mut memo_hits, state, blueprint);
+    progress::count("states explored", known_states.len() as u64);
+    progress::count("memo hits", memo_hits);
+    best
+}
+
+fn search(
     known_states: &mut HashMap<ProblemState, u32>,
+    memo_hits: &mut u64,
     state: ProblemState,
     blueprint: Blueprint,
-) -> u32 {
+) -> Result<u32, SolveError> {
     if state.time_left == 1 {
-        return state.clone().mine();
+        return Ok(state.clone().mine());
     }
     if let Some(ret) = known_states.get(&state) {
-        return *ret;
+        *memo_hits += 1;
+        return Ok(*ret);
     }
-    let ret = state
-        .next_states(blueprint)
-        .into_iter()
-        .map(|(next_state, geodes)| {
-            optimize_blueprint(known_states, next_state, blueprint) + geodes
-        })
-        .max()
-        .unwrap_or(0);
+    deadline::check()?;
+    let ret = state.next_states(blueprint).into_iter().try_fold(
+        0,
+        |best, (next_state, geodes)| -> Result<u32, SolveError> {
+            Ok(max(
+                best,
+                search(known_states, memo_hits, next_state, blueprint)? + geodes,
+            ))
+        },
+    )?;
     known_states.insert(state, ret);
-    ret
+    Ok(ret)
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> u32 {
-    let init_state = ProblemState::init(24);
-    parse!(input)
+pub fn part1(input: &str) -> Result<u32, SolveError> {
+    let init_state = ProblemState::init(MINUTES.get() as u32);
+    parse(input)?
         .into_iter()
-        .map(|blueprint| {
-            println!("blueprint {}", blueprint.id);
-            let mut known_states: HashMap<ProblemState, u32> = HashMap::new();
-            optimize_blueprint(&mut known_states, init_state, blueprint) * blueprint.id
+        .map(|blueprint| -> Result<u32, SolveError> {
+            progress::stage(format_args!("blueprint {}", blueprint.id));
+            Ok(optimize_blueprint(init_state, blueprint)? * blueprint.id)
         })
         .sum()
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> u32 {
-    let init_state = ProblemState::init(32);
-    parse!(input)
+pub fn part2(input: &str) -> Result<u32, SolveError> {
+    let init_state = ProblemState::init(LONG_MINUTES.get() as u32);
+    parse(input)?
         .into_iter()
         .take(3)
         .map(|blueprint| {
-            println!("blueprint {}", blueprint.id);
-            let mut known_states: HashMap<ProblemState, u32> = HashMap::new();
-            optimize_blueprint(&mut known_states, init_state, blueprint)
+            progress::stage(format_args!("blueprint {}", blueprint.id));
+            optimize_blueprint(init_state, blueprint)
         })
         .product()
 }
 
 #[test]
-fn part1_test() {
-    let input = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
-Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.
-";
-    assert_eq!(part1(input), 33);
-}
+fn reports_each_blueprint() {
+    use std::{cell::RefCell, rc::Rc};
 
-#[test]
-fn part2_test() {
-    let input = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
-Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.
-";
-    assert_eq!(part2(input), 62 * 56);
+    let stages = Rc::new(RefCell::new(Vec::new()));
+    let seen = stages.clone();
+    let (answer, counters) = progress::observe(
+        move |message, _| seen.borrow_mut().push(message.to_owned()),
+        || part1(include_str!("../fixtures/day19/example.txt")),
+    );
+    assert_eq!(answer, Ok(33));
+    assert_eq!(*stages.borrow(), vec!["blueprint 1", "blueprint 2"]);
+    assert!(counters.get("states explored") > 0);
+    assert!(counters.get("memo hits") > 0);
 }
@@ -1,47 +1,62 @@
+use crate::error::{parse_input, SolveError};
+use crate::rng::Rng;
 use advent_of_code::parse::{parsers, Parser};
 
+pub fn parse(input: &str) -> Result<Vec<String>, SolveError> {
+    Ok(parse_input(
+        parsers::char_any()
+            .repeat(3)
+            .map(|s| s.collect::<String>())
+            .many_lines("\n"),
+        input,
+    )?
+    .collect())
+}
+
+/// A strategy guide `size` rounds long.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let opponent = *rng.choose(&['A', 'B', 'C']);
+            format!("{} {}\n", opponent, rng.choose(&['X', 'Y', 'Z']))
+        })
+        .collect()
+}
+
 #[allow(dead_code)]
-pub fn part1(input: &str) -> u32 {
-    parsers::char_any()
-        .repeat(3)
-        .map(|s| match s.collect::<String>().as_str() {
-            "A X" => 1 + 3,
-            "A Y" => 2 + 6,
-            "A Z" => 3 + 0,
-            "B X" => 1 + 0,
-            "B Y" => 2 + 3,
-            "B Z" => 3 + 6,
-            "C X" => 1 + 6,
-            "C Y" => 2 + 0,
-            "C Z" => 3 + 3,
-            _ => panic!("Failed to parse"),
+pub fn part1(input: &str) -> Result<u32, SolveError> {
+    parse(input)?
+        .iter()
+        .map(|s| match s.as_str() {
+            "A X" => Ok(1 + 3),
+            "A Y" => Ok(2 + 6),
+            "A Z" => Ok(3 + 0),
+            "B X" => Ok(1 + 0),
+            "B Y" => Ok(2 + 3),
+            "B Z" => Ok(3 + 6),
+            "C X" => Ok(1 + 6),
+            "C Y" => Ok(2 + 0),
+            "C Z" => Ok(3 + 3),
+            _ => Err(SolveError::Invalid(format!("unknown round `{}`", s))),
         })
-        .many_lines("\n")
-        .parse(input)
-        .finish()
-        .unwrap()
         .sum()
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> u32 {
-    parsers::char_any()
-        .repeat(3)
-        .map(|s| match s.collect::<String>().as_str() {
-            "A X" => 3 + 0,
-            "A Y" => 1 + 3,
-            "A Z" => 2 + 6,
-            "B X" => 1 + 0,
-            "B Y" => 2 + 3,
-            "B Z" => 3 + 6,
-            "C X" => 2 + 0,
-            "C Y" => 3 + 3,
-            "C Z" => 1 + 6,
-            _ => panic!("Failed to parse"),
+pub fn part2(input: &str) -> Result<u32, SolveError> {
+    parse(input)?
+        .iter()
+        .map(|s| match s.as_str() {
+            "A X" => Ok(3 + 0),
+            "A Y" => Ok(1 + 3),
+            "A Z" => Ok(2 + 6),
+            "B X" => Ok(1 + 0),
+            "B Y" => Ok(2 + 3),
+            "B Z" => Ok(3 + 6),
+            "C X" => Ok(2 + 0),
+            "C Y" => Ok(3 + 3),
+            "C Z" => Ok(1 + 6),
+            _ => Err(SolveError::Invalid(format!("unknown round `{}`", s))),
         })
-        .many_lines("\n")
-        .parse(input)
-        .finish()
-        .unwrap()
         .sum()
 }
@@ -1,20 +1,63 @@
+use crate::error::{parse_input, SolveError};
+use crate::rng::Rng;
+use crate::tuning::Tunable;
+#[cfg(test)]
+use crate::tuning::{self, Settings};
 use advent_of_code::parse::{parsers, Parser};
 
-macro_rules! parse {
-    ($input: ident) => {
+pub const KEY: Tunable = Tunable {
+    day: 20,
+    name: "key",
+    about: "decryption key part 2 multiplies every number by",
+    default: 811_589_153,
+    min: -1_000_000_000_000,
+    max: 1_000_000_000_000,
+};
+
+pub const MIXES: Tunable = Tunable {
+    day: 20,
+    name: "mixes",
+    about: "times part 2 mixes the file",
+    default: 10,
+    min: 0,
+    max: 1000,
+};
+
+pub fn parse(input: &str) -> Result<Vec<isize>, SolveError> {
+    let numbers = parse_input(
         parsers::signed_number()
             .map(|n| n as isize)
-            .many_lines("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-            .collect::<Vec<isize>>()
-    };
+            .many_lines("\n"),
+        input,
+    )?
+    .collect::<Vec<isize>>();
+    if numbers.len() < 2 {
+        return Err(SolveError::Invalid(format!(
+            "need at least 2 numbers to mix, found {}",
+            numbers.len()
+        )));
+    }
+    if !numbers.contains(&0) {
+        return Err(SolveError::Invalid(
+            "the file has no 0 to count from".to_owned(),
+        ));
+    }
+    Ok(numbers)
+}
+
+/// `size` numbers to mix, exactly one of them 0.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut numbers: Vec<i64> = (1..size.max(2))
+        .map(|_| rng.range(1, 10000) * *rng.choose(&[-1, 1]))
+        .collect();
+    let at = rng.range(0, numbers.len() as i64) as usize;
+    numbers.insert(at, 0);
+    numbers.iter().map(|n| format!("{}\n", n)).collect()
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> isize {
-    let offsets = parse!(input);
+pub fn part1(input: &str) -> Result<isize, SolveError> {
+    let offsets = parse(input)?;
     let mut array = (0..offsets.len()).collect::<Vec<usize>>();
     let mut next_to_move = 0;
     let mut idx = 0;
@@ -30,21 +73,23 @@ pub fn part1(input: &str) -> isize {
     let zero_idx = (0..array.len())
         .find(|idx| offsets[array[*idx]] == 0)
         .unwrap();
-    (1000..=3000)
+    Ok((1000..=3000)
         .step_by(1000)
         .map(|idx| offsets[array[(idx + zero_idx) % array.len()]])
-        .sum()
+        .sum())
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> isize {
-    let decryption_key = 811589153;
-    let offsets = parse!(input)
+pub fn part2(input: &str) -> Result<isize, SolveError> {
+    let decryption_key = KEY.get() as isize;
+    let too_large =
+        || SolveError::Invalid(format!("decrypting with key {} overflows", decryption_key));
+    let offsets = parse(input)?
         .into_iter()
-        .map(|v| v * decryption_key)
-        .collect::<Vec<isize>>();
+        .map(|v| v.checked_mul(decryption_key).ok_or_else(too_large))
+        .collect::<Result<Vec<isize>, SolveError>>()?;
     let mut array = (0..offsets.len()).collect::<Vec<usize>>();
-    for _ in 0..10 {
+    for _ in 0..MIXES.get() {
         let mut next_to_move = 0;
         let mut idx = 0;
         while next_to_move < array.len() {
@@ -52,8 +97,9 @@ pub fn part2(input: &str) -> isize {
                 idx = (idx + 1) % array.len();
             }
             let item = array.remove(idx);
-            let new_idx =
-                (((idx as isize) + offsets[item]).rem_euclid(array.len() as isize)) as usize;
+            // Reduced first, as the decrypted offsets can be too large to add to.
+            let len = array.len() as isize;
+            let new_idx = ((idx as isize) + offsets[item].rem_euclid(len)).rem_euclid(len) as usize;
             array.insert(new_idx, item);
             next_to_move += 1;
         }
@@ -64,31 +110,20 @@ pub fn part2(input: &str) -> isize {
     (1000..=3000)
         .step_by(1000)
         .map(|idx| offsets[array[(idx + zero_idx) % array.len()]])
-        .sum()
-}
-
-#[test]
-fn part1_test() {
-    let input = "1
-2
--3
-3
--2
-0
-4
-";
-    assert_eq!(part1(input), 3);
+        .try_fold(0isize, |sum, offset| sum.checked_add(offset))
+        .ok_or_else(too_large)
 }
 
 #[test]
-fn part2_test() {
-    let input = "1
-2
--3
-3
--2
-0
-4
-";
-    assert_eq!(part2(input), 1623178306);
+fn large_keys_fail_instead_of_overflowing() {
+    let mut settings = Settings::new();
+    tuning::set(&mut settings, 20, "key", &KEY.max.to_string()).unwrap();
+    assert!(matches!(
+        tuning::with(&settings, || part2("9999999\n0\n-3\n")),
+        Err(SolveError::Invalid(_))
+    ));
+    assert_eq!(
+        tuning::with(&settings, || part2("1\n0\n-3\n")),
+        Ok(-2 * KEY.max as isize)
+    );
 }
@@ -1,8 +1,11 @@
+use crate::error::{parse_input, SolveError};
+use crate::explain;
+use crate::rng::Rng;
 use advent_of_code::parse::{parsers, Parser};
 use std::collections::HashMap;
 
 #[derive(Clone, Copy, Debug)]
-enum Operation {
+pub enum Operation {
     Add,
     Sub,
     Mul,
@@ -10,36 +13,49 @@ enum Operation {
 }
 
 impl Operation {
-    fn apply(self, a: i64, b: i64) -> i64 {
+    fn symbol(self) -> char {
         match self {
-            Self::Add => a + b,
-            Self::Sub => a - b,
-            Self::Mul => a * b,
-            Self::Div => a / b,
+            Self::Add => '+',
+            Self::Sub => '-',
+            Self::Mul => '*',
+            Self::Div => '/',
         }
     }
 
-    fn solve_left(self, b: i64, eq: i64) -> i64 {
+    fn apply(self, a: i64, b: i64) -> Option<i64> {
         match self {
-            Self::Add => eq - b,
-            Self::Sub => eq + b,
-            Self::Mul => eq / b,
-            Self::Div => eq * b,
+            Self::Add => a.checked_add(b),
+            Self::Sub => a.checked_sub(b),
+            Self::Mul => a.checked_mul(b),
+            Self::Div => a.checked_div(b),
         }
     }
 
-    fn solve_right(self, a: i64, eq: i64) -> i64 {
+    fn solve_left(self, b: i64, eq: i64) -> Option<i64> {
         match self {
-            Self::Add => eq - a,
-            Self::Sub => a - eq,
-            Self::Mul => eq / a,
-            Self::Div => a / eq,
+            Self::Add => eq.checked_sub(b),
+            Self::Sub => eq.checked_add(b),
+            Self::Mul => eq.checked_div(b),
+            Self::Div => eq.checked_mul(b),
+        }
+    }
+
+    fn solve_right(self, a: i64, eq: i64) -> Option<i64> {
+        match self {
+            Self::Add => eq.checked_sub(a),
+            Self::Sub => a.checked_sub(eq),
+            Self::Mul => eq.checked_div(a),
+            Self::Div => a.checked_div(eq),
         }
     }
 }
 
+fn arithmetic_error(monkey_id: &str) -> SolveError {
+    SolveError::Invalid(format!("monkey {} overflows or divides by zero", monkey_id))
+}
+
 #[derive(Clone, Debug)]
-enum Monkey {
+pub enum Monkey {
     Target,
     Resolved(i64),
     LeftResolved(Operation, i64, String),
@@ -47,64 +63,173 @@ enum Monkey {
     Unresolved(Operation, String, String),
 }
 
-macro_rules! parse {
-    ($input: ident) => {{
-        let parse_monkey_id = parsers::char_any().repeat(4).map(|n| n.collect::<String>());
-        parse_monkey_id
-            .skip_tag(": ")
-            .and_then(
-                parsers::signed_number()
-                    .map(|n| Monkey::Resolved(n as i64))
-                    .or(parse_monkey_id
-                        .skip_tag(" + ")
-                        .and_then(parse_monkey_id)
-                        .map(|(a, b)| Monkey::Unresolved(Operation::Add, a, b)))
-                    .or(parse_monkey_id
-                        .skip_tag(" - ")
-                        .and_then(parse_monkey_id)
-                        .map(|(a, b)| Monkey::Unresolved(Operation::Sub, a, b)))
-                    .or(parse_monkey_id
-                        .skip_tag(" * ")
-                        .and_then(parse_monkey_id)
-                        .map(|(a, b)| Monkey::Unresolved(Operation::Mul, a, b)))
-                    .or(parse_monkey_id
-                        .skip_tag(" / ")
-                        .and_then(parse_monkey_id)
-                        .map(|(a, b)| Monkey::Unresolved(Operation::Div, a, b))),
-            )
-            .many_lines("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-            .collect::<HashMap<String, Monkey>>()
-    }};
+pub fn parse(input: &str) -> Result<HashMap<String, Monkey>, SolveError> {
+    let parse_monkey_id = parsers::char_any().repeat(4).map(|n| n.collect::<String>());
+    let parser = parse_monkey_id
+        .skip_tag(": ")
+        .and_then(
+            parsers::signed_number()
+                .map(|n| Monkey::Resolved(n as i64))
+                .or(parse_monkey_id
+                    .skip_tag(" + ")
+                    .and_then(parse_monkey_id)
+                    .map(|(a, b)| Monkey::Unresolved(Operation::Add, a, b)))
+                .or(parse_monkey_id
+                    .skip_tag(" - ")
+                    .and_then(parse_monkey_id)
+                    .map(|(a, b)| Monkey::Unresolved(Operation::Sub, a, b)))
+                .or(parse_monkey_id
+                    .skip_tag(" * ")
+                    .and_then(parse_monkey_id)
+                    .map(|(a, b)| Monkey::Unresolved(Operation::Mul, a, b)))
+                .or(parse_monkey_id
+                    .skip_tag(" / ")
+                    .and_then(parse_monkey_id)
+                    .map(|(a, b)| Monkey::Unresolved(Operation::Div, a, b))),
+        )
+        .many_lines("\n");
+    Ok(parse_input(parser, input)?.collect::<HashMap<String, Monkey>>())
+}
+
+/// A troop of monkeys where `humn` is `size / 2` operations below `root`, and about as many
+/// monkeys on the other side of `root` yell the number it needs. Every operation is exact,
+/// so part 2's answer is the number `humn` starts out yelling.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut troop = Troop::default();
+    let human = rng.range(1, 5000);
+    troop.jobs.push(format!("humn: {}\n", human));
+    let (mut name, mut value) = ("humn".to_owned(), human);
+    for _ in 0..size.max(2) / 2 {
+        let other = rng.range(1, 20);
+        let operand = troop.yelling(rng, other, 1);
+        let (job, next) = match rng.range(0, 4) {
+            0 => (format!("{} + {}", name, operand), value + other),
+            1 => (format!("{} - {}", operand, name), other - value),
+            2 if value.abs() < 1 << 40 => (format!("{} * {}", name, operand), value * other),
+            3 if value % other == 0 => (format!("{} / {}", name, operand), value / other),
+            _ => (format!("{} - {}", name, operand), value - other),
+        };
+        let parent = troop.name();
+        troop.jobs.push(format!("{}: {}\n", parent, job));
+        (name, value) = (parent, next);
+    }
+    let other = troop.yelling(rng, value, size / 2);
+    troop.jobs.push(format!("root: {} + {}\n", name, other));
+    rng.shuffle(&mut troop.jobs);
+    troop.jobs.concat()
+}
+
+#[derive(Default)]
+struct Troop {
+    named: usize,
+    jobs: Vec<String>,
+}
+
+impl Troop {
+    /// The next unused name, skipping the two the puzzle gives a meaning to.
+    fn name(&mut self) -> String {
+        loop {
+            let mut idx = self.named;
+            self.named += 1;
+            let name: String = (0..4)
+                .map(|_| {
+                    let letter = (b'a' + (idx % 26) as u8) as char;
+                    idx /= 26;
+                    letter
+                })
+                .collect();
+            if name != "root" && name != "humn" {
+                return name;
+            }
+        }
+    }
+
+    /// Adds monkeys which between them yell `value`, about `budget` of them, and returns the
+    /// name of the one at the top. Only small positive numbers are yelled outright, like in
+    /// the puzzle.
+    fn yelling(&mut self, rng: &mut Rng, value: i64, budget: usize) -> String {
+        let name = self.name();
+        let job = if budget <= 1 && (1..1000).contains(&value) {
+            value.to_string()
+        } else {
+            let divisor = rng.range(2, 9);
+            let (operation, a, b) = if value <= 0 {
+                let b = value.abs() + rng.range(1, 100);
+                ('-', value + b, b)
+            } else if budget <= 1 {
+                // Out of monkeys to spend, so take the shortest way down to small numbers.
+                match value % divisor {
+                    0 => ('*', value / divisor, divisor),
+                    rest => ('+', value - rest, rest),
+                }
+            } else {
+                match rng.range(0, 3) {
+                    0 if value > 1 => {
+                        let a = rng.range(1, value - 1);
+                        ('+', a, value - a)
+                    }
+                    1 if value % divisor == 0 => ('*', value / divisor, divisor),
+                    2 if value < 1 << 40 => ('/', value * divisor, divisor),
+                    _ => {
+                        let b = rng.range(1, 100);
+                        ('-', value + b, b)
+                    }
+                }
+            };
+            let budget = budget.saturating_sub(1) / 2;
+            let a = self.yelling(rng, a, budget);
+            format!("{} {} {}", a, operation, self.yelling(rng, b, budget))
+        };
+        self.jobs.push(format!("{}: {}\n", name, job));
+        name
+    }
 }
 
-fn resolve_monkey(monkeys: &mut HashMap<String, Monkey>, monkey_id: &str) -> Monkey {
-    let res = match monkeys.get(monkey_id).cloned().expect("missing monkey!") {
+/// Works out what `monkey_id` yells, recording every monkey it depends on in `monkeys` as
+/// it goes. Monkeys waiting on the `Target` are left partly resolved, with the side that is
+/// known filled in.
+pub fn resolve_monkey(
+    monkeys: &mut HashMap<String, Monkey>,
+    monkey_id: &str,
+) -> Result<Monkey, SolveError> {
+    let monkey = monkeys
+        .get(monkey_id)
+        .cloned()
+        .ok_or_else(|| SolveError::MissingMonkey(monkey_id.to_owned()))?;
+    let res = match monkey {
         s @ Monkey::Target => s,
         s @ Monkey::Resolved(_) => s,
         Monkey::LeftResolved(operation, n, id) => {
-            if let Monkey::Resolved(m) = resolve_monkey(monkeys, &id) {
-                Monkey::Resolved(operation.apply(n, m))
+            if let Monkey::Resolved(m) = resolve_monkey(monkeys, &id)? {
+                Monkey::Resolved(
+                    operation
+                        .apply(n, m)
+                        .ok_or_else(|| arithmetic_error(monkey_id))?,
+                )
             } else {
                 Monkey::LeftResolved(operation, n, id)
             }
         }
         Monkey::RightResolved(operation, id, m) => {
-            if let Monkey::Resolved(n) = resolve_monkey(monkeys, &id) {
-                Monkey::Resolved(operation.apply(n, m))
+            if let Monkey::Resolved(n) = resolve_monkey(monkeys, &id)? {
+                Monkey::Resolved(
+                    operation
+                        .apply(n, m)
+                        .ok_or_else(|| arithmetic_error(monkey_id))?,
+                )
             } else {
                 Monkey::RightResolved(operation, id, m)
             }
         }
         Monkey::Unresolved(operation, key_a, key_b) => {
-            let a = resolve_monkey(monkeys, &key_a);
-            let b = resolve_monkey(monkeys, &key_b);
+            let a = resolve_monkey(monkeys, &key_a)?;
+            let b = resolve_monkey(monkeys, &key_b)?;
             match (a, b) {
-                (Monkey::Resolved(n), Monkey::Resolved(m)) => {
-                    Monkey::Resolved(operation.apply(n, m))
-                }
+                (Monkey::Resolved(n), Monkey::Resolved(m)) => Monkey::Resolved(
+                    operation
+                        .apply(n, m)
+                        .ok_or_else(|| arithmetic_error(monkey_id))?,
+                ),
                 (Monkey::Resolved(n), _) => Monkey::LeftResolved(operation, n, key_b),
                 (_, Monkey::Resolved(m)) => Monkey::RightResolved(operation, key_a, m),
                 (_, _) => Monkey::Unresolved(operation, key_a, key_b),
@@ -112,82 +237,132 @@ fn resolve_monkey(monkeys: &mut HashMap<String, Monkey>, monkey_id: &str) -> Mon
         }
     };
     monkeys.insert(monkey_id.to_string(), res.clone());
-    res
+    Ok(res)
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> i64 {
-    let mut monkeys = parse!(input);
-    if let Monkey::Resolved(n) = resolve_monkey(&mut monkeys, "root") {
-        n
+pub fn part1(input: &str) -> Result<i64, SolveError> {
+    let mut monkeys = parse(input)?;
+    if let Monkey::Resolved(n) = resolve_monkey(&mut monkeys, "root")? {
+        Ok(n)
     } else {
-        panic!("failed to resolve root");
+        Err(SolveError::NoSolution("failed to resolve root".to_owned()))
     }
 }
 
-fn attempt_solve(monkeys: &HashMap<String, Monkey>, monkey_id: &str, eq: i64) -> i64 {
+/// Solves for the `Target` below `monkey_id`, given that `monkey_id` must yell `eq`, by
+/// undoing each partly resolved monkey's operation in turn. `monkeys` must already have been
+/// through `resolve_monkey`.
+pub fn attempt_solve(
+    monkeys: &HashMap<String, Monkey>,
+    monkey_id: &str,
+    eq: i64,
+) -> Result<i64, SolveError> {
     match monkeys[monkey_id].clone() {
-        Monkey::Target => eq,
+        Monkey::Target => Ok(eq),
         Monkey::LeftResolved(operation, a, id) => {
-            attempt_solve(monkeys, &id, operation.solve_right(a, eq))
+            let next = operation
+                .solve_right(a, eq)
+                .ok_or_else(|| arithmetic_error(monkey_id))?;
+            explain::step(
+                "solved",
+                format_args!(
+                    "{} = {} {} {} = {}, so {} = {}",
+                    monkey_id,
+                    a,
+                    operation.symbol(),
+                    id,
+                    eq,
+                    id,
+                    next
+                ),
+            );
+            attempt_solve(monkeys, &id, next)
         }
         Monkey::RightResolved(operation, id, b) => {
-            attempt_solve(monkeys, &id, operation.solve_left(b, eq))
+            let next = operation
+                .solve_left(b, eq)
+                .ok_or_else(|| arithmetic_error(monkey_id))?;
+            explain::step(
+                "solved",
+                format_args!(
+                    "{} = {} {} {} = {}, so {} = {}",
+                    monkey_id,
+                    id,
+                    operation.symbol(),
+                    b,
+                    eq,
+                    id,
+                    next
+                ),
+            );
+            attempt_solve(monkeys, &id, next)
         }
-        _ => panic!("unsolvable, failed at {}", monkey_id),
+        _ => Err(SolveError::NoSolution(format!(
+            "unsolvable, failed at {}",
+            monkey_id
+        ))),
     }
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> i64 {
-    let mut monkeys = parse!(input);
+pub fn part2(input: &str) -> Result<i64, SolveError> {
+    let mut monkeys = parse(input)?;
     monkeys.insert("humn".to_string(), Monkey::Target);
-    match resolve_monkey(&mut monkeys, "root") {
+    match resolve_monkey(&mut monkeys, "root")? {
         Monkey::LeftResolved(_, n, id) | Monkey::RightResolved(_, id, n) => {
+            explain::step(
+                "solved",
+                format_args!("root's sides are equal, so {} = {}", id, n),
+            );
             attempt_solve(&monkeys, &id, n)
         }
-        v => panic!("root was unexpected value {:?}", v),
+        v => Err(SolveError::NoSolution(format!(
+            "root was unexpected value {:?}",
+            v
+        ))),
     }
 }
 
 #[test]
-fn part1_test() {
+fn missing_monkey() {
     let input = "root: pppw + sjmn
-dbpl: 5
-cczh: sllz + lgvd
-zczc: 2
-ptdq: humn - dvpt
-dvpt: 3
-lfqf: 4
-humn: 5
-ljgn: 2
-sjmn: drzm * dbpl
-sllz: 4
-pppw: cczh / lfqf
-lgvd: ljgn * ptdq
-drzm: hmdt - zczc
-hmdt: 32
+pppw: 4
 ";
-    assert_eq!(part1(input), 152);
+    assert_eq!(
+        part1(input),
+        Err(SolveError::MissingMonkey("sjmn".to_owned()))
+    );
 }
 
 #[test]
-fn part2_test() {
-    let input = "root: pppw + sjmn
-dbpl: 5
-cczh: sllz + lgvd
-zczc: 2
-ptdq: humn - dvpt
-dvpt: 3
-lfqf: 4
-humn: 5
-ljgn: 2
-sjmn: drzm * dbpl
-sllz: 4
-pppw: cczh / lfqf
-lgvd: ljgn * ptdq
-drzm: hmdt - zczc
-hmdt: 32
-";
-    assert_eq!(part2(input), 301);
+fn explains_the_humn_equation() {
+    let (answer, steps) = explain::trace(|| part2(include_str!("../fixtures/day21/example.txt")));
+    assert_eq!(answer, Ok(301));
+    let steps: Vec<String> = steps.iter().map(|step| step.detail.clone()).collect();
+    assert_eq!(
+        steps,
+        vec![
+            "root's sides are equal, so pppw = 150",
+            "pppw = cczh / 4 = 150, so cczh = 600",
+            "cczh = 4 + lgvd = 600, so lgvd = 596",
+            "lgvd = 2 * ptdq = 596, so ptdq = 298",
+            "ptdq = humn - 3 = 298, so humn = 301",
+        ]
+    );
+}
+
+#[test]
+fn generated_troop_solves_for_humn() {
+    let mut rng = Rng::new(21);
+    for size in [1, 10, 100] {
+        let input = generate(&mut rng, size);
+        let human: i64 = input
+            .lines()
+            .find_map(|line| line.strip_prefix("humn: "))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(part2(&input), Ok(human));
+    }
 }
@@ -1,3 +1,6 @@
+use crate::error::{check_rectangular, parse_input, SolveError};
+use crate::render::{self, Frame, Style};
+use crate::rng::Rng;
 use advent_of_code::{
     grid::{
         Direction::{self, East, North, South, West},
@@ -6,12 +9,47 @@ use advent_of_code::{
     parse::{parsers, Parser},
 };
 
-enum Instruction {
+pub enum Instruction {
     Left,
     Right,
     Move(u32),
 }
 
+/// The width of each face of the cube.
+const FACE: usize = 50;
+/// Which faces are in each band of `FACE` rows of the map. Every real puzzle's map is this
+/// net, and it's the only one part 2 knows how to fold.
+const NET: [[bool; 3]; 4] = [
+    [false, true, true],
+    [false, true, false],
+    [true, true, false],
+    [true, false, false],
+];
+
+const VOID: Style = Style::new(' ', [0, 0, 0]);
+const OPEN: Style = Style::new('.', [60, 60, 70]);
+const WALL: Style = Style::new('#', [200, 200, 200]);
+
+/// The map after an instruction, with the walker drawn facing the way it's heading.
+fn map_frame(grid: &Grid<char>, walker: GridPoint<usize>, orientation: Direction) -> Frame {
+    let mut frame = Frame::of_grid(grid, |tile| match tile {
+        '.' => OPEN,
+        '#' => WALL,
+        _ => VOID,
+    });
+    let glyph = match orientation {
+        East => '>',
+        South => 'v',
+        West => '<',
+        North => '^',
+    };
+    frame.set(
+        GridPoint::new(*walker.row() as isize, *walker.col() as isize),
+        Style::new(glyph, [255, 60, 60]),
+    );
+    frame
+}
+
 fn score_orientation(orientation: Direction) -> u32 {
     match orientation {
         Direction::East => 0,
@@ -21,35 +59,57 @@ fn score_orientation(orientation: Direction) -> u32 {
     }
 }
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::char(' ')
-            .or(parsers::char('.'))
-            .or(parsers::char('#'))
-            .many_at_least_one()
-            .map(|v| v.collect::<Vec<char>>())
-            .many_lines("\n")
-            .map(|v| {
-                let mut vec_of_vecs = v.collect::<Vec<Vec<char>>>();
-                let width = vec_of_vecs.iter().map(|l| l.len()).max().unwrap_or(0);
-                vec_of_vecs
-                    .iter_mut()
-                    .for_each(|l| l.extend((0..(width - l.len())).map(|_| ' ')));
-                Grid::of_vec_of_vecs(vec_of_vecs).unwrap()
-            })
-            .skip_tag("\n")
-            .and_then(
-                parsers::number()
-                    .map(|n| Instruction::Move(n))
-                    .or(parsers::char('R').map(|_| Instruction::Right))
-                    .or(parsers::char('L').map(|_| Instruction::Left))
-                    .many(),
-            )
-            .line("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-    };
+pub fn parse(input: &str) -> Result<(Grid<char>, Vec<Instruction>), SolveError> {
+    let parser = parsers::char(' ')
+        .or(parsers::char('.'))
+        .or(parsers::char('#'))
+        .many_at_least_one()
+        .map(|v| v.collect::<Vec<char>>())
+        .many_lines("\n")
+        .map(|v| v.collect::<Vec<Vec<char>>>())
+        .skip_tag("\n")
+        .and_then(
+            parsers::number()
+                .map(|n| Instruction::Move(n))
+                .or(parsers::char('R').map(|_| Instruction::Right))
+                .or(parsers::char('L').map(|_| Instruction::Left))
+                .many()
+                .map(|v| v.collect::<Vec<Instruction>>()),
+        )
+        .line("\n");
+    let (mut vec_of_vecs, instructions) = parse_input(parser, input)?;
+    let width = vec_of_vecs.iter().map(|l| l.len()).max().unwrap_or(0);
+    vec_of_vecs
+        .iter_mut()
+        .for_each(|l| l.extend((0..(width - l.len())).map(|_| ' ')));
+    check_rectangular(&vec_of_vecs)?;
+    Ok((Grid::of_vec_of_vecs(vec_of_vecs).unwrap(), instructions))
+}
+
+/// A path of `size` moves over the map of the real puzzle's shape, whose cube net of 50 by 50
+/// faces is the one part 2 folds. Walls are scattered anywhere but the starting tile.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut notes = String::new();
+    for row in 0..NET.len() * FACE {
+        let faces = NET[row / FACE];
+        let width = FACE * (faces.iter().rposition(|face| *face).unwrap() + 1);
+        for col in 0..width {
+            notes.push(match faces[col / FACE] {
+                false => ' ',
+                true if (row, col) != (0, FACE) && rng.range(0, 9) == 0 => '#',
+                true => '.',
+            });
+        }
+        notes.push('\n');
+    }
+    notes.push('\n');
+    for step in 0..size.max(1) {
+        if step > 0 {
+            notes.push(*rng.choose(&['L', 'R']));
+        }
+        notes.push_str(&rng.range(1, 50).to_string());
+    }
+    notes + "\n"
 }
 
 fn wrap(
@@ -69,8 +129,38 @@ fn wrap(
     )
 }
 
-fn wrap_cube(point: GridPoint<usize>, orientation: Direction) -> (GridPoint<usize>, Direction) {
-    match (point, orientation) {
+/// Checks that the map is laid out as `NET`, since `wrap_cube` would otherwise send the
+/// walker off the map.
+fn check_net(grid: &Grid<char>) -> Result<(), SolveError> {
+    let (rows, cols) = (NET.len() * FACE, NET[0].len() * FACE);
+    if (grid.rows(), grid.cols()) != (rows, cols) {
+        return Err(SolveError::Invalid(format!(
+            "part 2 folds a {} by {} map, found {} by {}",
+            rows,
+            cols,
+            grid.rows(),
+            grid.cols()
+        )));
+    }
+    for row in 0..rows {
+        for col in 0..cols {
+            let point = GridPoint::new(row, col);
+            if NET[row / FACE][col / FACE] != (grid[point] != ' ') {
+                return Err(SolveError::Invalid(format!(
+                    "the map doesn't fold into the expected cube at {}",
+                    point
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn wrap_cube(
+    point: GridPoint<usize>,
+    orientation: Direction,
+) -> Result<(GridPoint<usize>, Direction), SolveError> {
+    Ok(match (point, orientation) {
         (GridPoint { row: 0, col }, North) if col < 100 => (GridPoint::new(col + 100, 0), East), // a
         (GridPoint { row, col: 0 }, West) if row >= 150 => (GridPoint::new(0, row - 100), South), // a
         (GridPoint { row, col: 0 }, West) if row < 150 => (GridPoint::new(149 - row, 50), East), // b
@@ -85,37 +175,42 @@ fn wrap_cube(point: GridPoint<usize>, orientation: Direction) -> (GridPoint<usiz
         (GridPoint { row: 49, col }, South) => (GridPoint::new(col - 50, 99), West), // f
         (GridPoint { row: 149, col }, South) => (GridPoint::new(col + 100, 49), West), // g
         (GridPoint { row, col: 49 }, East) => (GridPoint::new(149, row - 100), North), // g
-        _ => panic!(
-            "unrecognized point: {} orientation: {:?}",
-            point, orientation
-        ),
-    }
+        _ => {
+            return Err(SolveError::Invalid(format!(
+                "the map doesn't fold into the expected cube at {} facing {:?}",
+                point, orientation
+            )))
+        }
+    })
 }
 
 fn simluate<
     I: Iterator<Item = Instruction>,
-    F: Fn(GridPoint<usize>, Direction) -> (GridPoint<usize>, Direction),
+    F: Fn(GridPoint<usize>, Direction) -> Result<(GridPoint<usize>, Direction), SolveError>,
 >(
     grid: &Grid<char>,
     instructions: I,
     wrap: F,
-) -> u32 {
+) -> Result<u32, SolveError> {
     let mut curr: GridPoint<usize> = GridPoint::new(0, 0)
         .traverse_by(EAST, 0, grid.rows(), 0, grid.cols())
         .find(|p| grid[*p] != ' ')
-        .unwrap();
+        .ok_or_else(|| SolveError::Invalid("the top row of the map has no tiles".to_owned()))?;
     let mut orientation: Direction = Direction::East;
-    for instr in instructions {
+    render::frame(0, || map_frame(grid, curr, orientation));
+    for (step, instr) in instructions.enumerate() {
         match instr {
             Instruction::Left => orientation = orientation.rotate_left(),
             Instruction::Right => orientation = orientation.rotate_right(),
             Instruction::Move(n) => {
                 for _ in 0..n {
-                    let (next, next_orientation) = curr
+                    let (next, next_orientation) = match curr
                         .add_checked(orientation.into(), &0, &grid.rows(), &0, &grid.cols())
                         .filter(|p| grid[*p] != ' ')
-                        .map(|p| (p, orientation))
-                        .unwrap_or_else(|| wrap(curr, orientation));
+                    {
+                        Some(p) => (p, orientation),
+                        None => wrap(curr, orientation)?,
+                    };
                     if grid[next] == '#' {
                         break;
                     } else {
@@ -125,30 +220,49 @@ fn simluate<
                 }
             }
         }
+        render::frame(step + 1, || map_frame(grid, curr, orientation));
     }
-    (1 + curr.row()) as u32 * 1000 + (1 + curr.col()) as u32 * 4 + score_orientation(orientation)
+    Ok((1 + curr.row()) as u32 * 1000
+        + (1 + curr.col()) as u32 * 4
+        + score_orientation(orientation))
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> u32 {
-    let (grid, instructions) = parse!(input);
-    simluate(&grid, instructions, |point, orientation| {
-        wrap(&grid, point, orientation.into(), grid.rows(), grid.cols())
+pub fn part1(input: &str) -> Result<u32, SolveError> {
+    let (grid, instructions) = parse(input)?;
+    simluate(&grid, instructions.into_iter(), |point, orientation| {
+        Ok(wrap(&grid, point, orientation.into(), grid.rows(), grid.cols()))
     })
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> u32 {
-    let (grid, instructions) = parse!(input);
-    simluate(&grid, instructions, |point, orientation| {
+pub fn part2(input: &str) -> Result<u32, SolveError> {
+    let (grid, instructions) = parse(input)?;
+    check_net(&grid)?;
+    simluate(&grid, instructions.into_iter(), |point, orientation| {
         wrap_cube( point, orientation.into())
     })
 }
 
+#[test]
+fn maps_of_other_shapes_fail_to_fold() {
+    assert_eq!(
+        part2(include_str!("../fixtures/day22/example.txt")),
+        Err(SolveError::Invalid(
+            "part 2 folds a 200 by 150 map, found 12 by 16".to_owned()
+        ))
+    );
+    let notes = generate(&mut Rng::new(22), 20);
+    assert!(part2(&notes).is_ok());
+    // A tile left of the first face, where the net has none.
+    let notes = format!(".{}", &notes[1..]);
+    assert!(matches!(part2(&notes), Err(SolveError::Invalid(_))));
+}
+
 /*
 #[allow(dead_code)]
 pub fn part1(input: &str) -> u32 {
-    let (grid, instructions) = parse!(input);
+    let (grid, instructions) = parse(input);
     let mut curr: GridPoint<usize> = GridPoint::new(0, 0)
         .traverse_by(EAST, 0, grid.rows(), 0, grid.cols())
         .find(|p| grid[*p] != ' ')
@@ -182,7 +296,7 @@ pub fn part1(input: &str) -> u32 {
 /*
 #[allow(dead_code)]
 pub fn part2(input: &str) -> u32 {
-    let (grid, instructions) = parse!(input);
+    let (grid, instructions) = parse(input);
     let mut curr: GridPoint<usize> = GridPoint::new(0, 0)
         .traverse_by(EAST, 0, grid.rows(), 0, grid.cols())
         .find(|p| grid[*p] != ' ')
@@ -212,22 +326,3 @@ pub fn part2(input: &str) -> u32 {
     (1 + curr.row()) as u32 * 1000 + (1 + curr.col()) as u32 * 4 + score_orientation(orientation)
 }
 */
-#[test]
-fn part1_test() {
-    let input = "        ...#
-        .#..
-        #...
-        ....
-...#.......#
-........#...
-..#....#....
-..........#.
-        ...#....
-        .....#..
-        .#......
-        ......#.
-
-10R5L5R10L4R5L5
-";
-    assert_eq!(part1(input), 6032);
-}
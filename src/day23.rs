@@ -1,3 +1,8 @@
+use crate::error::{parse_input, SolveError};
+use crate::progress;
+use crate::render::{self, Frame, Style};
+use crate::rng::Rng;
+use crate::tuning::Tunable;
 use advent_of_code::{
     grid::{
         GridPoint, GridPointDelta, ADJACENT, EAST, NORTH, NORTHEAST, NORTHWEST, SOUTH, SOUTHEAST,
@@ -7,26 +12,49 @@ use advent_of_code::{
 };
 use std::collections::{HashMap, HashSet};
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::char('.')
-            .or(parsers::char('#'))
-            .many()
-            .map(|r| r.enumerate().filter(|(_, c)| *c == '#'))
-            .many_lines("\n")
-            .map(|g| {
-                g.enumerate()
-                    .flat_map(|(row_idx, r)| {
-                        r.map(move |(col_idx, _)| {
-                            GridPoint::new(row_idx as isize, col_idx as isize)
-                        })
-                    })
-                    .collect::<HashSet<GridPoint<isize>>>()
-            })
-            .parse($input)
-            .finish()
-            .unwrap()
-    };
+const ELF: Style = Style::new('#', [80, 200, 90]);
+
+pub const ROUNDS: Tunable = Tunable {
+    day: 23,
+    name: "rounds",
+    about: "rounds the elves spread out for before part 1 measures them",
+    default: 10,
+    min: 0,
+    max: 100_000,
+};
+
+pub fn parse(input: &str) -> Result<HashSet<GridPoint<isize>>, SolveError> {
+    let parser = parsers::char('.')
+        .or(parsers::char('#'))
+        .many()
+        .map(|r| r.enumerate().filter(|(_, c)| *c == '#'))
+        .many_lines("\n")
+        .map(|g| {
+            g.enumerate()
+                .flat_map(|(row_idx, r)| {
+                    r.map(move |(col_idx, _)| GridPoint::new(row_idx as isize, col_idx as isize))
+                })
+                .collect::<HashSet<GridPoint<isize>>>()
+        });
+    let elves = parse_input(parser, input)?;
+    if elves.is_empty() {
+        return Err(SolveError::EmptyInput);
+    }
+    Ok(elves)
+}
+
+/// Elves standing on about half of a `size` by `size` patch of ground.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut ground: String = (0..size.max(1))
+        .map(|_| {
+            let row: String = (0..size.max(1)).map(|_| *rng.choose(&['.', '#'])).collect();
+            row + "\n"
+        })
+        .collect();
+    if !ground.contains('#') {
+        ground.replace_range(0..1, "#");
+    }
+    ground
 }
 
 fn desired_spot(
@@ -53,6 +81,7 @@ fn round(
     elves: &HashSet<GridPoint<isize>>,
     search_pattern: &Vec<(Vec<GridPointDelta<isize>>, GridPointDelta<isize>)>,
 ) -> (HashSet<GridPoint<isize>>, bool) {
+    progress::count("rounds simulated", 1);
     let mut next_elves: HashMap<GridPoint<isize>, GridPoint<isize>> = HashMap::new();
     let mut finished = true;
     for elf in elves.iter() {
@@ -78,8 +107,8 @@ fn score(elves: &HashSet<GridPoint<isize>>) -> isize {
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> isize {
-    let mut elves: HashSet<GridPoint<isize>> = parse!(input);
+pub fn part1(input: &str) -> Result<isize, SolveError> {
+    let mut elves: HashSet<GridPoint<isize>> = parse(input)?;
     let mut search_pattern = vec![
         (Vec::from(ADJACENT), ZERO),
         (vec![NORTHWEST, NORTH, NORTHEAST], NORTH),
@@ -87,18 +116,20 @@ pub fn part1(input: &str) -> isize {
         (vec![NORTHWEST, WEST, SOUTHWEST], WEST),
         (vec![NORTHEAST, EAST, SOUTHEAST], EAST),
     ];
-    for _ in 0..10 {
+    render::frame(0, || Frame::of_points(elves.iter(), ELF));
+    for round_idx in 1..=ROUNDS.get() as usize {
         score(&elves);
         (elves, _) = round(&elves, &search_pattern);
+        render::frame(round_idx, || Frame::of_points(elves.iter(), ELF));
         let front = search_pattern.remove(1);
         search_pattern.push(front);
     }
-    score(&elves)
+    Ok(score(&elves))
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> u32 {
-    let mut elves: HashSet<GridPoint<isize>> = parse!(input);
+pub fn part2(input: &str) -> Result<u32, SolveError> {
+    let mut elves: HashSet<GridPoint<isize>> = parse(input)?;
     let mut search_pattern = vec![
         (Vec::from(ADJACENT), ZERO),
         (vec![NORTHWEST, NORTH, NORTHEAST], NORTH),
@@ -108,38 +139,27 @@ pub fn part2(input: &str) -> u32 {
     ];
     let mut finished = false;
     let mut idx = 0;
+    render::frame(0, || Frame::of_points(elves.iter(), ELF));
     while !finished {
         idx += 1;
         score(&elves);
         (elves, finished) = round(&elves, &search_pattern);
+        render::frame(idx as usize, || Frame::of_points(elves.iter(), ELF));
         let front = search_pattern.remove(1);
         search_pattern.push(front);
     }
-    idx
-}
-
-#[test]
-fn part1_test() {
-    let input = "....#..
-..###.#
-#...#.#
-.#...##
-#.###..
-##.#.##
-.#..#..
-";
-    assert_eq!(part1(input), 110);
+    Ok(idx)
 }
 
 #[test]
-fn part2_test() {
-    let input = "....#..
-..###.#
-#...#.#
-.#...##
-#.###..
-##.#.##
-.#..#..
-";
-    assert_eq!(part2(input), 20);
+fn counts_rounds() {
+    let input = include_str!("../fixtures/day23/example.txt");
+    assert_eq!(
+        progress::counted(|| part1(input)).1.get("rounds simulated"),
+        10
+    );
+    assert_eq!(
+        progress::counted(|| part2(input)).1.get("rounds simulated"),
+        20
+    );
 }
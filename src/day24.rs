@@ -1,3 +1,7 @@
+use crate::deadline;
+use crate::error::{parse_input, SolveError};
+use crate::render::{self, Bounds, Frame, Style, EMPTY};
+use crate::rng::Rng;
 use advent_of_code::{
     grid::{Direction, GridPoint, PLUS_ADJACENT, ZERO},
     parse::{parsers, Parser},
@@ -5,10 +9,14 @@ use advent_of_code::{
 
 use std::collections::{HashSet, VecDeque};
 
+const WALL: Style = Style::new('#', [120, 120, 130]);
+const BLIZZARD: Style = Style::new('*', [150, 200, 255]);
+const EXPEDITION: Style = Style::new('E', [255, 200, 40]);
+
 #[derive(Debug, Clone, Copy)]
-struct Blizzard {
-    direction: Direction,
-    location: GridPoint<isize>,
+pub struct Blizzard {
+    pub direction: Direction,
+    pub location: GridPoint<isize>,
 }
 
 impl Blizzard {
@@ -73,11 +81,16 @@ impl BlizzardStateCache {
             .for_each(|b| *b = b.step(self.min_row, self.max_row, self.min_col, self.max_col));
     }
 
-    fn occupied(&mut self, round: usize, point: GridPoint<isize>) -> bool {
+    /// Where the blizzards are in `round`.
+    fn at(&mut self, round: usize) -> &HashSet<GridPoint<isize>> {
         while self.location_states.len() <= round {
             self.iter();
         }
-        self.location_states[round].contains(&point)
+        &self.location_states[round]
+    }
+
+    fn occupied(&mut self, round: usize, point: GridPoint<isize>) -> bool {
+        self.at(round).contains(&point)
     }
 }
 
@@ -88,57 +101,114 @@ enum Cell {
     Blizard(Direction),
 }
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::char('.')
-            .map(|_| Cell::Empty)
-            .or(parsers::char('#').map(|_| Cell::Wall))
-            .or(parsers::char('>').map(|_| Cell::Blizard(Direction::East)))
-            .or(parsers::char('v').map(|_| Cell::Blizard(Direction::South)))
-            .or(parsers::char('<').map(|_| Cell::Blizard(Direction::West)))
-            .or(parsers::char('^').map(|_| Cell::Blizard(Direction::North)))
-            .many()
-            .map(|r| {
-                (
-                    r.clone().enumerate().filter_map(|(idx, cell)| match cell {
-                        Cell::Blizard(d) => Some((idx, d)),
-                        _ => None,
-                    }),
-                    r.clone().enumerate().filter_map(|(idx, cell)| match cell {
-                        Cell::Wall => Some(idx),
-                        _ => None,
-                    }),
-                    r.count(),
-                )
-            })
-            .many_lines("\n")
-            .map(|g| {
-                let grid = g.enumerate();
-                (
-                    grid.clone()
-                        .flat_map(|(row_idx, (r, _, _))| {
-                            r.map(move |(col_idx, direction)| Blizzard {
-                                location: GridPoint::new(row_idx as isize, col_idx as isize),
-                                direction,
-                            })
+pub fn parse(
+    input: &str,
+) -> Result<
+    (
+        Vec<Blizzard>,
+        HashSet<GridPoint<isize>>,
+        (isize, isize, isize, isize),
+    ),
+    SolveError,
+> {
+    let parser = parsers::char('.')
+        .map(|_| Cell::Empty)
+        .or(parsers::char('#').map(|_| Cell::Wall))
+        .or(parsers::char('>').map(|_| Cell::Blizard(Direction::East)))
+        .or(parsers::char('v').map(|_| Cell::Blizard(Direction::South)))
+        .or(parsers::char('<').map(|_| Cell::Blizard(Direction::West)))
+        .or(parsers::char('^').map(|_| Cell::Blizard(Direction::North)))
+        .many()
+        .map(|r| {
+            (
+                r.clone().enumerate().filter_map(|(idx, cell)| match cell {
+                    Cell::Blizard(d) => Some((idx, d)),
+                    _ => None,
+                }),
+                r.clone().enumerate().filter_map(|(idx, cell)| match cell {
+                    Cell::Wall => Some(idx),
+                    _ => None,
+                }),
+                r.count(),
+            )
+        })
+        .many_lines("\n")
+        .map(|g| {
+            let grid = g.enumerate();
+            (
+                grid.clone()
+                    .flat_map(|(row_idx, (r, _, _))| {
+                        r.map(move |(col_idx, direction)| Blizzard {
+                            location: GridPoint::new(row_idx as isize, col_idx as isize),
+                            direction,
                         })
-                        .collect::<Vec<Blizzard>>(),
-                    grid.clone()
-                        .flat_map(|(row_idx, (_, w, _))| {
-                            w.map(move |col_idx| GridPoint::new(row_idx as isize, col_idx as isize))
-                        })
-                        .collect::<HashSet<GridPoint<isize>>>(),
-                    grid.map(|(row_idx, (_, _, l))| {
-                        (0_isize, row_idx as isize + 1, 0_isize, l as isize)
                     })
-                    .last()
-                    .unwrap(),
-                )
-            })
-            .parse($input)
-            .finish()
-            .unwrap()
-    };
+                    .collect::<Vec<Blizzard>>(),
+                grid.clone()
+                    .flat_map(|(row_idx, (_, w, _))| {
+                        w.map(move |col_idx| GridPoint::new(row_idx as isize, col_idx as isize))
+                    })
+                    .collect::<HashSet<GridPoint<isize>>>(),
+                grid.map(|(row_idx, (_, _, l))| {
+                    (0_isize, row_idx as isize + 1, 0_isize, l as isize)
+                })
+                .last(),
+            )
+        });
+    let (blizzards, walls, bounds) = parse_input(parser, input)?;
+    let bounds = bounds.ok_or(SolveError::EmptyInput)?;
+    let (_, max_row, _, max_col) = bounds;
+    if max_row < 3 || max_col < 3 {
+        return Err(SolveError::Invalid(format!(
+            "a {}x{} valley has no room inside its walls",
+            max_row, max_col
+        )));
+    }
+    Ok((blizzards, walls, bounds))
+}
+
+/// A valley `size` rows high and four times as wide, a quarter of it blizzards. None blow up
+/// or down the entrance and exit columns, where they'd leave the valley.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let (rows, cols) = (size.max(1), 4 * size.max(1));
+    let mut valley = format!("#.{}\n", "#".repeat(cols));
+    for _ in 0..rows {
+        valley.push('#');
+        for col in 0..cols {
+            let vertical = col != 0 && col != cols - 1;
+            valley.push(match rng.range(0, 15) {
+                0 => '>',
+                1 => '<',
+                2 if vertical => '^',
+                3 if vertical => 'v',
+                _ => '.',
+            });
+        }
+        valley.push_str("#\n");
+    }
+    valley + &"#".repeat(cols) + ".#\n"
+}
+
+/// The valley in `round`, with every place the expedition could have reached by then.
+fn valley_frame<'a>(
+    walls: &HashSet<GridPoint<isize>>,
+    blizzards: &HashSet<GridPoint<isize>>,
+    expedition: impl Iterator<Item = &'a GridPoint<isize>>,
+    bounds: Bounds,
+) -> Frame {
+    let mut frame = Frame::draw(bounds, |point| {
+        if walls.contains(&point) {
+            WALL
+        } else if blizzards.contains(&point) {
+            BLIZZARD
+        } else {
+            EMPTY
+        }
+    });
+    for point in expedition {
+        frame.set(*point, EXPEDITION);
+    }
+    frame
 }
 
 fn find_exit(
@@ -151,11 +221,23 @@ fn find_exit(
     max_row: isize,
     min_col: isize,
     max_col: isize,
-) -> usize {
+) -> Result<usize, SolveError> {
     let mut to_search: VecDeque<(usize, GridPoint<isize>)> = VecDeque::new();
     let mut seen: HashSet<(usize, GridPoint<isize>)> = HashSet::new();
     to_search.push_back((initial_round, start));
+    let mut drawn_round = None;
     while let Some((round, point)) = to_search.pop_front() {
+        deadline::check()?;
+        // The search goes a round at a time, so the rest of this round is still queued.
+        if drawn_round != Some(round) {
+            drawn_round = Some(round);
+            let bounds = Bounds::new(min_row, max_row, min_col, max_col);
+            let locations = blizzards.at(round);
+            render::frame(round, || {
+                let reached = to_search.iter().map(|(_, point)| point);
+                valley_frame(walls, locations, reached.chain([&point]), bounds)
+            });
+        }
         let next_round = round + 1;
         let adjacent: Vec<(usize, GridPoint<isize>)> = PLUS_ADJACENT
             .iter()
@@ -174,18 +256,18 @@ fn find_exit(
             .collect();
         for next in adjacent {
             if next.1 == end {
-                return next.0;
+                return Ok(next.0);
             }
             seen.insert(next);
             to_search.push_back(next);
         }
     }
-    panic!("failed to find exit");
+    Err(SolveError::NoSolution("failed to find exit".to_owned()))
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> usize {
-    let (blizards, walls, (min_row, max_row, min_col, max_col)) = parse!(input);
+pub fn part1(input: &str) -> Result<usize, SolveError> {
+    let (blizards, walls, (min_row, max_row, min_col, max_col)) = parse(input)?;
     let mut blizzard_locations: BlizzardStateCache =
         BlizzardStateCache::new(blizards, min_row + 1, max_row - 1, min_col + 1, max_col - 1);
     find_exit(
@@ -202,8 +284,8 @@ pub fn part1(input: &str) -> usize {
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> usize {
-    let (blizards, walls, (min_row, max_row, min_col, max_col)) = parse!(input);
+pub fn part2(input: &str) -> Result<usize, SolveError> {
+    let (blizards, walls, (min_row, max_row, min_col, max_col)) = parse(input)?;
     let mut blizzard_locations: BlizzardStateCache =
         BlizzardStateCache::new(blizards, min_row + 1, max_row - 1, min_col + 1, max_col - 1);
     let start = GridPoint::new(0, 1);
@@ -218,7 +300,7 @@ pub fn part2(input: &str) -> usize {
         max_row,
         min_col,
         max_col,
-    );
+    )?;
     let second = find_exit(
         &mut blizzard_locations,
         &walls,
@@ -229,7 +311,7 @@ pub fn part2(input: &str) -> usize {
         max_row,
         min_col,
         max_col,
-    );
+    )?;
     find_exit(
         &mut blizzard_locations,
         &walls,
@@ -242,27 +324,3 @@ pub fn part2(input: &str) -> usize {
         max_col,
     )
 }
-
-#[test]
-fn part1_test() {
-    let input = "#.######
-#>>.<^<#
-#.<..<<#
-#>v.><>#
-#<^v^^>#
-######.#
-";
-    assert_eq!(part1(input), 18);
-}
-
-#[test]
-fn part2_test() {
-    let input = "#.######
-#>>.<^<#
-#.<..<<#
-#>v.><>#
-#<^v^^>#
-######.#
-";
-    assert_eq!(part2(input), 54);
-}
@@ -1,3 +1,5 @@
+use crate::error::{parse_input, SolveError};
+use crate::rng::Rng;
 use advent_of_code::parse::{parsers, Parser};
 use std::{fmt::Debug, ops::Neg};
 
@@ -75,7 +77,7 @@ impl Neg for Digit {
 }
 
 #[derive(Clone, PartialEq, Eq)]
-struct Snafu(Vec<Digit>);
+pub struct Snafu(Vec<Digit>);
 
 impl ToString for Snafu {
     fn to_string(&self) -> String {
@@ -98,11 +100,11 @@ impl Neg for Snafu {
 }
 
 impl Snafu {
-    fn to_num(self) -> i64 {
+    pub fn to_num(self) -> i64 {
         self.0.iter().fold(0, |acc, d| acc * 5 + d.to_num())
     }
 
-    fn of_num(mut n: i64) -> Self {
+    pub fn of_num(mut n: i64) -> Self {
         if n == 0 {
             return Snafu(vec![Digit::Zero]);
         } else if n < 0 {
@@ -129,34 +131,43 @@ impl Snafu {
     }
 }
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::char('2')
-            .map(|_| Digit::Two)
-            .or(parsers::char('1').map(|_| Digit::One))
-            .or(parsers::char('0').map(|_| Digit::Zero))
-            .or(parsers::char('-').map(|_| Digit::Minus))
-            .or(parsers::char('=').map(|_| Digit::DoubleMinus))
-            .many()
-            .map(|v| Snafu(v.collect::<Vec<Digit>>()))
-            .many_lines("\n")
-            .map(|v| v.collect::<Vec<Snafu>>())
-            .parse($input)
-            .finish()
-            .unwrap()
-    };
+pub fn parse(input: &str) -> Result<Vec<Snafu>, SolveError> {
+    let parser = parsers::char('2')
+        .map(|_| Digit::Two)
+        .or(parsers::char('1').map(|_| Digit::One))
+        .or(parsers::char('0').map(|_| Digit::Zero))
+        .or(parsers::char('-').map(|_| Digit::Minus))
+        .or(parsers::char('=').map(|_| Digit::DoubleMinus))
+        .many()
+        .map(|v| Snafu(v.collect::<Vec<Digit>>()))
+        .many_lines("\n")
+        .map(|v| v.collect::<Vec<Snafu>>());
+    parse_input(parser, input)
+}
+
+/// `size` SNAFU numbers of up to 15 digits.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let mut number = rng.choose(&['1', '2']).to_string();
+            for _ in 1..rng.range(1, 15) {
+                number.push(*rng.choose(&['=', '-', '0', '1', '2']));
+            }
+            number + "\n"
+        })
+        .collect()
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> String {
-    let snafus = parse!(input);
+pub fn part1(input: &str) -> Result<String, SolveError> {
+    let snafus = parse(input)?;
     let total: i64 = snafus.into_iter().map(|v| v.to_num()).sum();
-    Snafu::of_num(total).to_string()
+    Ok(Snafu::of_num(total).to_string())
 }
 
 #[allow(dead_code)]
-pub fn part2(_: &str) -> u32 {
-    0
+pub fn part2(_: &str) -> Result<u32, SolveError> {
+    Ok(0)
 }
 
 #[test]
@@ -217,28 +228,3 @@ fn snafu_of_num() {
     .collect();
     assert_eq!(snafus, computed_snafus);
 }
-
-#[test]
-fn part1_test() {
-    let input = "1=-0-2
-12111
-2=0=
-21
-2=01
-111
-20012
-112
-1=-1=
-1-12
-12
-1=
-122
-";
-    assert_eq!(part1(input), "2=-1=0");
-}
-
-#[test]
-fn part2_test() {
-    let input = "";
-    assert_eq!(part2(input), 0);
-}
@@ -1,4 +1,6 @@
-use advent_of_code::parse::{parsers, ParseError, Parser};
+use crate::error::{parse_input, SolveError};
+use crate::rng::Rng;
+use advent_of_code::parse::{parsers, Parser};
 use itertools::Itertools;
 use std::collections::BTreeSet;
 
@@ -10,36 +12,63 @@ fn score_char(c: u8) -> u32 {
     }
 }
 
+pub fn parse(input: &str) -> Result<Vec<String>, SolveError> {
+    Ok(parse_input(parsers::many_chars(|c| c != '\n').many_lines("\n"), input)?.collect())
+}
+
+/// `size` groups of three rucksacks. The items are shared out afresh for each group so that
+/// every rucksack has exactly one item in both compartments, and every group exactly one badge.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut items: Vec<u8> = (b'a'..=b'z').chain(b'A'..=b'Z').collect();
+    let mut rucksacks = String::new();
+    for _ in 0..size.max(1) {
+        rng.shuffle(&mut items);
+        let (badge, rest) = items.split_first().unwrap();
+        for own in rest.chunks(17) {
+            let (shared, own) = own.split_first().unwrap();
+            let (first_only, second_only) = own.split_at(8);
+            let len = rng.range(2, 16);
+            let mut first: Vec<u8> = (1..len).map(|_| *rng.choose(first_only)).collect();
+            first.push(*shared);
+            let mut second: Vec<u8> = (2..len).map(|_| *rng.choose(second_only)).collect();
+            second.extend([*shared, *badge]);
+            rng.shuffle(&mut first);
+            rng.shuffle(&mut second);
+            rucksacks.extend(first.iter().chain(&second).map(|item| *item as char));
+            rucksacks.push('\n');
+        }
+    }
+    rucksacks
+}
+
+fn common_item(sets: &[&BTreeSet<u8>]) -> Result<u8, SolveError> {
+    let (first, rest) = sets.split_first().ok_or(SolveError::EmptyInput)?;
+    rest.iter()
+        .fold((*first).clone(), |acc, set| &acc & set)
+        .first()
+        .copied()
+        .ok_or_else(|| SolveError::Invalid("no item is common to the group".to_owned()))
+}
+
 #[allow(dead_code)]
-pub fn part1(input: &str) -> u32 {
-    parsers::many_chars(|c| c != '\n')
-        .bind(|s: String| {
+pub fn part1(input: &str) -> Result<u32, SolveError> {
+    parse(input)?
+        .into_iter()
+        .map(|s| {
             let first_half: BTreeSet<u8> = s[..(s.len() / 2)].bytes().collect();
             let second_half: BTreeSet<u8> = s[(s.len() / 2)..].bytes().collect();
-            Ok(score_char(
-                *(&first_half & &second_half)
-                    .first()
-                    .ok_or(ParseError::EndOfString)?,
-            ))
+            common_item(&[&first_half, &second_half]).map(score_char)
         })
-        .many_lines("\n")
-        .parse(input)
-        .finish()
-        .unwrap()
         .sum()
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> u32 {
-    parsers::many_chars(|c| c != '\n')
-        .map(|l: String| l.bytes().collect::<BTreeSet<u8>>())
-        .many_lines("\n")
-        .parse(input)
-        .finish()
-        .unwrap()
+pub fn part2(input: &str) -> Result<u32, SolveError> {
+    parse(input)?
+        .into_iter()
+        .map(|l| l.bytes().collect::<BTreeSet<u8>>())
         .tuple_windows::<(_, _, _)>()
         .step_by(3)
-        .map(|(a, b, c)| *(&(&a & &b) & &c).first().unwrap())
-        .map(score_char)
+        .map(|(a, b, c)| common_item(&[&a, &b, &c]).map(score_char))
         .sum()
 }
@@ -1,20 +1,35 @@
+use crate::error::{parse_input, SolveError};
+use crate::rng::Rng;
 use advent_of_code::parse::{parsers, Parser};
 
-macro_rules! parse {
-    ($input: ident) => {
+pub fn parse(input: &str) -> Result<Vec<((u32, u32), (u32, u32))>, SolveError> {
+    Ok(parse_input(
         parsers::number()
             .pair("-", parsers::number())
             .pair(",", parsers::number().pair("-", parsers::number()))
-            .many_lines("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-    };
+            .many_lines("\n"),
+        input,
+    )?
+    .collect())
+}
+
+/// `size` pairs of section assignments.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let first = rng.range(1, 99);
+            let first_end = rng.range(first, 99);
+            let second = rng.range(1, 99);
+            let second_end = rng.range(second, 99);
+            format!("{}-{},{}-{}\n", first, first_end, second, second_end)
+        })
+        .collect()
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> u32 {
-    parse!(input)
+pub fn part1(input: &str) -> Result<u32, SolveError> {
+    Ok(parse(input)?
+        .into_iter()
         .map(|((al, ar), (bl, br))| {
             if (al <= bl && ar >= br) || (al >= bl && ar <= br) {
                 1
@@ -22,12 +37,13 @@ pub fn part1(input: &str) -> u32 {
                 0
             }
         })
-        .sum()
+        .sum())
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> u32 {
-    parse!(input)
+pub fn part2(input: &str) -> Result<u32, SolveError> {
+    Ok(parse(input)?
+        .into_iter()
         .map(|((al, ar), (bl, br))| {
             if al <= br && ar >= bl || al >= br && ar <= bl {
                 1
@@ -35,5 +51,16 @@ pub fn part2(input: &str) -> u32 {
                 0
             }
         })
-        .sum()
+        .sum())
+}
+
+#[test]
+fn parse_error_location() {
+    let input = "2-4,6-8
+2-x,4-5
+";
+    assert!(matches!(
+        part1(input),
+        Err(SolveError::Parse { line: 2, .. })
+    ));
 }
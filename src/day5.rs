@@ -1,88 +1,170 @@
+use crate::error::{parse_input, SolveError};
+use crate::rng::Rng;
 use advent_of_code::parse::{parsers, Parser};
 
-macro_rules! parse {
-    ($input: ident) => {{
-        let (crates, instrs) = parsers::char('[')
-            .ignore(parsers::char_any())
-            .skip(parsers::char_any())
-            .map(|c: char| Some(c))
-            .or(parsers::tag("   ").map(|_| Option::<char>::None))
-            .list(" ")
-            .list("\n")
-            .skip(parsers::many_chars(|c| c != '\n').line("\n").repeat(2))
-            .pair(
-                "\n",
-                parsers::tag("move ")
-                    .ignore(parsers::number())
-                    .skip_tag(" from ")
-                    .and_then(parsers::number())
-                    .skip_tag(" to ")
-                    .and_then(parsers::number())
-                    .skip_tag("\n")
-                    .map(|((a, b), c)| (a as usize, (b - 1) as usize, (c - 1) as usize))
-                    .many(),
-            )
-            .parse($input)
-            .finish()
-            .unwrap();
-        let crates: Vec<Vec<Option<char>>> =
-            crates.map(|v| v.collect::<Vec<Option<char>>>()).collect();
-        (
-            (0..crates[0].len())
-                .map(|col_idx| {
-                    (0..crates.len())
-                        .rev()
-                        .filter_map(|row_idx| crates[row_idx][col_idx])
-                        .collect::<Vec<char>>()
+pub fn parse(input: &str) -> Result<(Vec<Vec<char>>, Vec<(usize, usize, usize)>), SolveError> {
+    let parser = parsers::char('[')
+        .ignore(parsers::char_any())
+        .skip(parsers::char_any())
+        .map(|c: char| Some(c))
+        .or(parsers::tag("   ").map(|_| Option::<char>::None))
+        .list(" ")
+        .list("\n")
+        .skip(parsers::many_chars(|c| c != '\n').line("\n").repeat(2))
+        .pair(
+            "\n",
+            parsers::tag("move ")
+                .ignore(parsers::number())
+                .skip_tag(" from ")
+                .and_then(parsers::number())
+                .skip_tag(" to ")
+                .and_then(parsers::number())
+                .skip_tag("\n")
+                .map(|((a, b), c)| (a as usize, b as usize, c as usize))
+                .many(),
+        );
+    let (crates, instrs) = parse_input(parser, input)?;
+    let crates: Vec<Vec<Option<char>>> = crates.map(|v| v.collect::<Vec<Option<char>>>()).collect();
+    // Rows with their trailing spaces trimmed off are shorter than the rest.
+    let width = crates
+        .iter()
+        .map(Vec::len)
+        .max()
+        .ok_or(SolveError::EmptyInput)?;
+    let instrs: Vec<(usize, usize, usize)> = instrs.collect();
+    if let Some((_, from, to)) = instrs
+        .iter()
+        .find(|(_, from, to)| !(1..=width).contains(from) || !(1..=width).contains(to))
+    {
+        return Err(SolveError::Invalid(format!(
+            "move from stack {} to stack {}, but the stacks are numbered 1 to {}",
+            from, to, width
+        )));
+    }
+    Ok((
+        (0..width)
+            .map(|col_idx| {
+                crates
+                    .iter()
+                    .rev()
+                    .filter_map(|row| row.get(col_idx).copied().flatten())
+                    .collect::<Vec<char>>()
+            })
+            .collect::<Vec<Vec<char>>>(),
+        instrs
+            .into_iter()
+            .map(|(count, from, to)| (count, from - 1, to - 1))
+            .collect(),
+    ))
+}
+
+/// Nine stacks of crates rearranged by `size` moves. The moves are played out as they're made,
+/// and never take a stack's last crate, so every stack still has a crate on top at the end.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut stacks: Vec<Vec<char>> = (0..9)
+        .map(|idx| {
+            (0..rng.range(if idx == 0 { 2 } else { 1 }, 8))
+                .map(|_| (b'A' + rng.range(0, 25) as u8) as char)
+                .collect()
+        })
+        .collect();
+    let height = stacks.iter().map(Vec::len).max().unwrap();
+    let mut drawing: String = (0..height)
+        .rev()
+        .map(|level| {
+            let row: Vec<String> = stacks
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(c) => format!("[{}]", c),
+                    None => "   ".to_owned(),
                 })
-                .collect::<Vec<Vec<char>>>(),
-            instrs,
-        )
-    }};
+                .collect();
+            row.join(" ") + "\n"
+        })
+        .collect();
+    let labels: Vec<String> = (1..=stacks.len()).map(|n| format!(" {} ", n)).collect();
+    drawing.push_str(&labels.join(" "));
+    drawing.push_str("\n\n");
+    for _ in 0..size {
+        // There are more crates than stacks, so some stack always has one to spare.
+        let spare: Vec<usize> = (0..stacks.len())
+            .filter(|idx| stacks[*idx].len() > 1)
+            .collect();
+        let from = *rng.choose(&spare);
+        let to = (from + rng.range(1, stacks.len() as i64 - 1) as usize) % stacks.len();
+        let len = stacks[from].len();
+        let count = rng.range(1, len as i64 - 1) as usize;
+        let moved = stacks[from].split_off(len - count);
+        stacks[to].extend(moved);
+        drawing.push_str(&format!("move {} from {} to {}\n", count, from + 1, to + 1));
+    }
+    drawing
+}
+
+fn tops(crates: Vec<Vec<char>>) -> Result<String, SolveError> {
+    crates
+        .into_iter()
+        .enumerate()
+        .map(|(idx, stack)| {
+            stack
+                .last()
+                .copied()
+                .ok_or(SolveError::EmptyStack { stack: idx + 1 })
+        })
+        .collect()
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> String {
-    let (mut crates, instrs) = parse!(input);
+pub fn part1(input: &str) -> Result<String, SolveError> {
+    let (mut crates, instrs) = parse(input)?;
     for (count, from, to) in instrs {
         for _ in 0..count {
-            let popped = crates[from].pop().unwrap();
+            let popped = crates[from]
+                .pop()
+                .ok_or(SolveError::EmptyStack { stack: from + 1 })?;
             crates[to].push(popped);
         }
     }
 
-    crates
-        .into_iter()
-        .map(|stack| stack.last().unwrap().clone())
-        .collect()
+    tops(crates)
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> String {
-    let (mut crates, instrs) = parse!(input);
+pub fn part2(input: &str) -> Result<String, SolveError> {
+    let (mut crates, instrs) = parse(input)?;
     for (count, from, to) in instrs {
-        let at = crates[from].len() - count;
+        let at = crates[from]
+            .len()
+            .checked_sub(count)
+            .ok_or(SolveError::EmptyStack { stack: from + 1 })?;
         let popped = crates[from].split_off(at);
         crates[to].extend(popped);
     }
 
-    crates
-        .into_iter()
-        .map(|stack| stack.last().unwrap().clone())
-        .collect()
+    tops(crates)
 }
 
 #[test]
-fn test_part1() {
+fn empty_stack() {
     let input = "    [D]    
 [N] [C]    
 [Z] [M] [P]
  1   2   3 
 
-move 1 from 2 to 1
-move 3 from 1 to 3
-move 2 from 2 to 1
-move 1 from 1 to 2
+move 4 from 2 to 1
 ";
-    assert_eq!(part1(&input), "CMZ");
+    assert_eq!(part1(input), Err(SolveError::EmptyStack { stack: 2 }));
+    assert_eq!(part2(input), Err(SolveError::EmptyStack { stack: 2 }));
+}
+
+#[test]
+fn trimmed_rows_and_unknown_stacks() {
+    let input = "    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3\n\nmove 1 from 2 to 1\n";
+    assert_eq!(part1(input), Ok("DCP".to_owned()));
+    assert_eq!(
+        parse(&input.replace("from 2", "from 0")),
+        Err(SolveError::Invalid(
+            "move from stack 0 to stack 1, but the stacks are numbered 1 to 3".to_owned()
+        ))
+    );
 }
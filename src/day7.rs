@@ -1,3 +1,6 @@
+use crate::error::{parse_input, SolveError};
+use crate::explain;
+use crate::rng::Rng;
 use advent_of_code::parse::{parsers, Parser};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -28,7 +31,9 @@ fn directory_sizes(
     directory: &Path,
     directories: &HashMap<PathBuf, Vec<File>>,
 ) -> HashMap<PathBuf, u32> {
-    let (mut map, size) = directories.get(directory).unwrap().iter().fold(
+    // A directory we never ran `ls` in has nothing we know of inside it.
+    let files = directories.get(directory).map_or(&[][..], Vec::as_slice);
+    let (mut map, size) = files.iter().fold(
         (HashMap::new(), 0_u32),
         |(mut map, this_size), file| match file {
             File::Directory { name } => {
@@ -44,91 +49,161 @@ fn directory_sizes(
 }
 
 #[derive(Debug, PartialEq, Eq)]
-enum FileSpec {
+pub enum FileSpec {
     File { name: String, size: u32 },
     Directory { name: String },
 }
 
 #[derive(Debug, PartialEq, Eq)]
-enum Command {
+pub enum Command {
     Ls(Vec<FileSpec>),
     Cd(String),
 }
 
-fn populate_directory(commands: Vec<Command>) -> HashMap<PathBuf, Vec<File>> {
+fn populate_directory(commands: Vec<Command>) -> Result<HashMap<PathBuf, Vec<File>>, SolveError> {
     let mut directories: HashMap<PathBuf, Vec<File>> = HashMap::new();
     let mut current_directory = PathBuf::new();
-    commands.into_iter().for_each(|command| match command {
-        Command::Cd(s) => {
-            current_directory = if s.as_str() == ".." {
-                current_directory.parent().unwrap().to_owned()
-            } else {
-                current_directory.join(s)
+    for command in commands {
+        match command {
+            Command::Cd(s) => {
+                current_directory = if s.as_str() == ".." {
+                    current_directory
+                        .parent()
+                        .ok_or_else(|| {
+                            SolveError::Invalid("`cd ..` out of the root directory".to_owned())
+                        })?
+                        .to_owned()
+                } else {
+                    current_directory.join(s)
+                }
             }
+            Command::Ls(v) => v.into_iter().for_each(|file_spec| {
+                directories
+                    .entry(current_directory.clone())
+                    .or_insert_with(Vec::new)
+                    .push(match file_spec {
+                        FileSpec::Directory { name } => File::directory(&current_directory, &name),
+                        FileSpec::File { name, size } => {
+                            File::file(&current_directory, &name, size)
+                        }
+                    })
+            }),
         }
-        Command::Ls(v) => v.into_iter().for_each(|file_spec| {
-            directories
-                .entry(current_directory.clone())
-                .or_insert_with(Vec::new)
-                .push(match file_spec {
-                    FileSpec::Directory { name } => File::directory(&current_directory, &name),
-                    FileSpec::File { name, size } => File::file(&current_directory, &name, size),
-                })
-        }),
-    });
-    directories
+    }
+    Ok(directories)
+}
+
+pub fn parse(input: &str) -> Result<Vec<Command>, SolveError> {
+    let parser = parsers::tag("$ ")
+        .ignore(
+            parsers::tag("ls\n")
+                .ignore(
+                    parsers::tag("dir ")
+                        .ignore(
+                            parsers::many_chars(|c| c != '\n')
+                                .map(|name| FileSpec::Directory { name }),
+                        )
+                        .or(parsers::number()
+                            .pair(" ", parsers::many_chars(|c| c != '\n'))
+                            .map(|(size, name)| FileSpec::File { name, size }))
+                        .many_lines("\n")
+                        .map(|iter| Command::Ls(iter.collect::<Vec<FileSpec>>())),
+                )
+                .or(parsers::tag("cd ")
+                    .ignore(parsers::many_chars(|c| c != '\n'))
+                    .line("\n")
+                    .map(|s| Command::Cd(s))),
+        )
+        .many();
+    Ok(parse_input(parser, input)?.collect::<Vec<Command>>())
+}
+
+/// A terminal session exploring `size` directories with a few files in each. New directories
+/// usually go in the one made before, so the tree gets deep as well as wide.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let dirs = size.max(1);
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); dirs];
+    for dir in 1..dirs {
+        let parent = match rng.range(0, 1) {
+            0 => dir - 1,
+            _ => rng.range(0, dir as i64 - 1) as usize,
+        };
+        children[parent].push(dir);
+    }
+    // At most three files a directory, keeping the total under the 70000000 the disk holds.
+    let max_file = (60_000_000 / (3 * dirs as i64)).max(1);
+    let mut session = String::from("$ cd /\n");
+    session.push_str(&listing(rng, &children[0], max_file));
+    let mut to_visit: Vec<(usize, usize)> = vec![(0, 0)];
+    while let Some((dir, next)) = to_visit.pop() {
+        match children[dir].get(next) {
+            Some(&child) => {
+                to_visit.push((dir, next + 1));
+                session.push_str(&format!("$ cd d{}\n", child));
+                session.push_str(&listing(rng, &children[child], max_file));
+                to_visit.push((child, 0));
+            }
+            None if !to_visit.is_empty() => session.push_str("$ cd ..\n"),
+            None => {}
+        }
+    }
+    session
 }
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::tag("$ ")
-            .ignore(
-                parsers::tag("ls\n")
-                    .ignore(
-                        parsers::tag("dir ")
-                            .ignore(
-                                parsers::many_chars(|c| c != '\n')
-                                    .map(|name| FileSpec::Directory { name }),
-                            )
-                            .or(parsers::number()
-                                .pair(" ", parsers::many_chars(|c| c != '\n'))
-                                .map(|(size, name)| FileSpec::File { name, size }))
-                            .many_lines("\n")
-                            .map(|iter| Command::Ls(iter.collect::<Vec<FileSpec>>())),
-                    )
-                    .or(parsers::tag("cd ")
-                        .ignore(parsers::many_chars(|c| c != '\n'))
-                        .line("\n")
-                        .map(|s| Command::Cd(s))),
-            )
-            .many()
-            .parse($input)
-            .finish()
-            .unwrap()
-            .collect::<Vec<Command>>()
-    };
+fn listing(rng: &mut Rng, children: &[usize], max_file: i64) -> String {
+    let mut listing = String::from("$ ls\n");
+    for child in children {
+        listing.push_str(&format!("dir d{}\n", child));
+    }
+    for file in 0..rng.range(1, 3) {
+        listing.push_str(&format!("{} f{}.txt\n", rng.range(1, max_file), file));
+    }
+    listing
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> u32 {
-    let directories = populate_directory(parse!(input));
+pub fn part1(input: &str) -> Result<u32, SolveError> {
+    let directories = populate_directory(parse(input)?)?;
     let directory_sizes = directory_sizes(Path::new("/"), &directories);
-    directory_sizes
+    let mut small: Vec<(PathBuf, u32)> = directory_sizes
         .into_iter()
-        .filter_map(|(_, size)| if size < 100000 { Some(size) } else { None })
-        .sum()
+        .filter(|(_, size)| *size < 100000)
+        .collect();
+    small.sort();
+    for (directory, size) in small.iter() {
+        explain::step(
+            "counted",
+            format_args!("{} holds {} bytes", directory.display(), size),
+        );
+    }
+    Ok(small.into_iter().map(|(_, size)| size).sum())
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> u32 {
-    let directories = populate_directory(parse!(input));
+pub fn part2(input: &str) -> Result<u32, SolveError> {
+    let directories = populate_directory(parse(input)?)?;
     let directory_sizes = directory_sizes(Path::new("/"), &directories);
-    let threshold = 30000000 - (70000000 - directory_sizes.get(&PathBuf::from("/")).unwrap());
-    directory_sizes
+    let used = directory_sizes.get(&PathBuf::from("/")).unwrap();
+    let threshold = 70000000_u32
+        .checked_sub(*used)
+        .map(|free| 30000000_u32.saturating_sub(free))
+        .ok_or_else(|| {
+            SolveError::Invalid(format!("{} bytes used on a 70000000 byte disk", used))
+        })?;
+    explain::step(
+        "needed",
+        format_args!("{} bytes used, so {} more must be freed", used, threshold),
+    );
+    let (directory, size) = directory_sizes
         .into_iter()
-        .filter_map(|(_, size)| if size > threshold { Some(size) } else { None })
-        .min()
-        .unwrap()
+        .filter(|(_, size)| *size > threshold)
+        .min_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)))
+        .ok_or_else(|| SolveError::NoSolution("no directory frees enough space".to_owned()))?;
+    explain::step(
+        "deleted",
+        format_args!("{} frees {} bytes", directory.display(), size),
+    );
+    Ok(size)
 }
 
 #[test]
@@ -151,30 +226,19 @@ fn simple() {
 }
 
 #[test]
-fn test_part1() {
-    let input = "$ cd /
-$ ls
-dir a
-14848514 b.txt
-8504156 c.dat
-dir d
-$ cd a
-$ ls
-dir e
-29116 f
-2557 g
-62596 h.lst
-$ cd e
-$ ls
-584 i
-$ cd ..
-$ cd ..
-$ cd d
-$ ls
-4060174 j
-8033020 d.log
-5626152 d.ext
-7214296 k
-";
-    assert_eq!(part1(input), 95437);
+fn explains_which_directories_count() {
+    let input = include_str!("../fixtures/day7/example.txt");
+    let (answer, steps) = explain::trace(|| part1(input));
+    assert_eq!(answer, Ok(95437));
+    let steps: Vec<String> = steps.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        steps,
+        vec![
+            "counted: /a holds 94853 bytes",
+            "counted: /a/e holds 584 bytes"
+        ]
+    );
+    let (answer, steps) = explain::trace(|| part2(input));
+    assert_eq!(answer, Ok(24933642));
+    assert_eq!(steps[1].detail, "/d frees 24933642 bytes");
 }
@@ -1,3 +1,7 @@
+#[cfg(test)]
+use crate::differential;
+use crate::error::{check_rectangular, parse_input, SolveError};
+use crate::rng::Rng;
 use advent_of_code::{
     grid::{Grid, GridPoint, EAST, NORTH, SOUTH, WEST},
     parse::{parsers, Parser},
@@ -18,22 +22,32 @@ fn mark_visible<I: Iterator<Item = GridPoint<usize>>>(
     }
 }
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::chars(|c| c.is_numeric())
-            .many()
-            .many_lines("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-            .map(|l| l.collect::<Vec<char>>())
-            .collect::<Vec<Vec<char>>>()
-    };
+pub fn parse(input: &str) -> Result<Grid<char>, SolveError> {
+    let vec_of_vecs = parse_input(
+        parsers::chars(|c| c.is_numeric()).many().many_lines("\n"),
+        input,
+    )?
+    .map(|l| l.collect::<Vec<char>>())
+    .collect::<Vec<Vec<char>>>();
+    check_rectangular(&vec_of_vecs)?;
+    Ok(Grid::of_vec_of_vecs(vec_of_vecs).unwrap())
+}
+
+/// A forest `size` trees on each side.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let row: String = (0..size.max(1))
+                .map(|_| char::from_digit(rng.range(0, 9) as u32, 10).unwrap())
+                .collect();
+            row + "\n"
+        })
+        .collect()
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> u32 {
-    let grid = Grid::of_vec_of_vecs(parse!(input)).unwrap();
+pub fn part1(input: &str) -> Result<u32, SolveError> {
+    let grid = parse(input)?;
     let rows = grid.rows();
     let cols = grid.cols();
     let mut visible: Grid<u32> = Grid::init(0, rows, cols);
@@ -75,7 +89,7 @@ pub fn part1(input: &str) -> u32 {
             )
         });
 
-    visible.into_iter().sum()
+    Ok(visible.into_iter().sum())
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -120,8 +134,8 @@ fn mark_visible_direction<I: Iterator<Item = GridPoint<usize>>>(
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> usize {
-    let grid = Grid::of_vec_of_vecs(parse!(input)).unwrap();
+pub fn part2(input: &str) -> Result<usize, SolveError> {
+    let grid = parse(input)?;
     let rows = grid.rows();
     let cols = grid.cols();
     let mut visible: Grid<Tree> = Grid::init(Tree::new(), rows, cols);
@@ -167,31 +181,66 @@ pub fn part2(input: &str) -> usize {
             )
         });
 
-    visible
+    Ok(visible
         .into_iter()
         .map(|tree| tree.product())
         .max()
-        .unwrap()
+        .unwrap())
 }
 
-#[test]
-fn test_part1() {
-    let input = "30373
-25512
-65332
-33549
-35390
-";
-    assert_eq!(part1(input), 21);
+/// The heights seen looking up, down, left and right from a tree, nearest first.
+#[cfg(test)]
+fn lines_of_sight(heights: &[Vec<u8>], row: usize, col: usize) -> [Vec<u8>; 4] {
+    [
+        (0..row).rev().map(|r| heights[r][col]).collect(),
+        (row + 1..heights.len()).map(|r| heights[r][col]).collect(),
+        heights[row][..col].iter().rev().copied().collect(),
+        heights[row][col + 1..].to_vec(),
+    ]
+}
+
+/// Checks every direction from every tree rather than sweeping each line once.
+#[cfg(test)]
+fn naive_trees<T>(
+    input: &str,
+    score: impl Fn(u8, [Vec<u8>; 4]) -> T,
+) -> Result<Vec<T>, SolveError> {
+    parse(input)?;
+    let heights: Vec<Vec<u8>> = input.lines().map(|line| line.bytes().collect()).collect();
+    Ok((0..heights.len())
+        .flat_map(|row| (0..heights[row].len()).map(move |col| (row, col)))
+        .map(|(row, col)| score(heights[row][col], lines_of_sight(&heights, row, col)))
+        .collect())
+}
+
+#[cfg(test)]
+fn naive_visible(input: &str) -> Result<u32, SolveError> {
+    let visible = naive_trees(input, |height, sight| {
+        sight
+            .iter()
+            .any(|line| line.iter().all(|other| *other < height))
+    })?;
+    Ok(visible.into_iter().filter(|visible| *visible).count() as u32)
+}
+
+#[cfg(test)]
+fn naive_scenic_score(input: &str) -> Result<usize, SolveError> {
+    let scores = naive_trees(input, |height, sight| {
+        sight
+            .iter()
+            .map(
+                |line| match line.iter().position(|other| *other >= height) {
+                    Some(blocked) => blocked + 1,
+                    None => line.len(),
+                },
+            )
+            .product()
+    })?;
+    Ok(scores.into_iter().max().unwrap())
 }
 
 #[test]
-fn test_part2() {
-    let input = "30373
-25512
-65332
-33549
-35390
-";
-    assert_eq!(part2(input), 8);
+fn matches_naive() {
+    differential::compare(300, generate, naive_visible, part1);
+    differential::compare(300, generate, naive_scenic_score, part2);
 }
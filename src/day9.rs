@@ -1,24 +1,31 @@
 use std::collections::HashSet;
 
+use crate::error::{parse_input, SolveError};
+use crate::rng::Rng;
 use advent_of_code::grid::{GridPoint, GridPointDelta, ADJACENT, EAST, NORTH, SOUTH, WEST, ZERO};
 use advent_of_code::parse::{parsers, Parser};
 
-macro_rules! parse {
-    ($input: ident) => {
-        parsers::char_any()
-            .map(|c| match c {
-                'U' => NORTH,
-                'R' => EAST,
-                'D' => SOUTH,
-                'L' => WEST,
-                _ => ZERO,
-            })
-            .pair(" ", parsers::number())
-            .many_lines("\n")
-            .parse($input)
-            .finish()
-            .unwrap()
-    };
+pub fn parse(input: &str) -> Result<Vec<(GridPointDelta<isize>, u32)>, SolveError> {
+    let direction = parsers::char('U')
+        .map(|_| NORTH)
+        .or(parsers::char('R').map(|_| EAST))
+        .or(parsers::char('D').map(|_| SOUTH))
+        .or(parsers::char('L').map(|_| WEST));
+    Ok(parse_input(
+        direction.pair(" ", parsers::number()).many_lines("\n"),
+        input,
+    )?
+    .collect())
+}
+
+/// `size` moves of the rope's head.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let direction = *rng.choose(&['U', 'D', 'L', 'R']);
+            format!("{} {}\n", direction, rng.range(1, 20))
+        })
+        .collect()
 }
 
 fn normalized_delta(d: GridPointDelta<isize>) -> GridPointDelta<isize> {
@@ -36,11 +43,11 @@ fn normalized_delta(d: GridPointDelta<isize>) -> GridPointDelta<isize> {
 }
 
 #[allow(dead_code)]
-pub fn part1(input: &str) -> usize {
+pub fn part1(input: &str) -> Result<usize, SolveError> {
     let mut head: GridPoint<isize> = GridPoint::new(0, 0);
     let mut tail: GridPoint<isize> = GridPoint::new(0, 0);
     let mut seen: HashSet<GridPoint<isize>> = HashSet::from([tail]);
-    parse!(input).for_each(|(dir, count)| {
+    parse(input)?.into_iter().for_each(|(dir, count)| {
         (0..count).for_each(|_| {
             head = (head + dir).unwrap();
             let delta = head.sub(tail).unwrap();
@@ -50,14 +57,14 @@ pub fn part1(input: &str) -> usize {
             }
         })
     });
-    seen.len()
+    Ok(seen.len())
 }
 
 #[allow(dead_code)]
-pub fn part2(input: &str) -> usize {
+pub fn part2(input: &str) -> Result<usize, SolveError> {
     let mut rope: [GridPoint<isize>; 10] = [GridPoint::new(0, 0); 10];
     let mut seen: HashSet<GridPoint<isize>> = HashSet::from([rope[9]]);
-    parse!(input).for_each(|(dir, count)| {
+    parse(input)?.into_iter().for_each(|(dir, count)| {
         (0..count).for_each(|_| {
             rope[0] = (rope[0] + dir).unwrap();
             for idx in 1..rope.len() {
@@ -69,19 +76,5 @@ pub fn part2(input: &str) -> usize {
             seen.insert(rope[9]);
         })
     });
-    seen.len()
-}
-
-#[test]
-fn part1_test() {
-    let input = "R 4
-U 4
-L 3
-D 1
-R 4
-D 1
-L 5
-R 2
-";
-    assert_eq!(part1(input), 13);
+    Ok(seen.len())
 }
@@ -1,23 +1,24 @@
+use crate::error::{parse_input, SolveError};
+use crate::rng::Rng;
 use advent_of_code::parse::{parsers, Parser};
 
-#[allow(dead_code)]
-pub fn part1(input: &str) -> u32 {
-    todo!()
+pub fn parse(input: &str) -> Result<Vec<u32>, SolveError> {
+    Ok(parse_input(parsers::number().many_lines("\n"), input)?.collect())
 }
 
-#[allow(dead_code)]
-pub fn part2(input: &str) -> u32 {
-    todo!()
+/// `size` numbers, one to a line.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| format!("{}\n", rng.range(0, 1000)))
+        .collect()
 }
 
-#[test]
-fn part1_test() {
-    let input = "";
-    assert_eq!(part1(input), 0);
+#[allow(dead_code)]
+pub fn part1(input: &str) -> Result<u32, SolveError> {
+    todo!()
 }
 
-#[test]
-fn part2_test() {
-    let input = "";
-    assert_eq!(part2(input), 0);
+#[allow(dead_code)]
+pub fn part2(input: &str) -> Result<u32, SolveError> {
+    todo!()
 }
@@ -0,0 +1,83 @@
+//! Time limits for solvers. The runner sets a limit for the current thread, every solver
+//! started on it gets that long, and the long-running searches call `check` as they go so
+//! they can give up once their time is up. Solvers which never check run to completion.
+
+use crate::error::SolveError;
+use crate::scoped;
+use std::{
+    cell::Cell,
+    time::{Duration, Instant},
+};
+
+/// Reading the clock on every call would slow down the tightest loops, so only one call in
+/// this many looks at it.
+const CHECK_EVERY: u32 = 1024;
+
+thread_local! {
+    static LIMIT: Cell<Option<Duration>> = const { Cell::new(None) };
+    /// The running solver's deadline, and the limit it was set from.
+    static DEADLINE: Cell<Option<(Instant, Duration)>> = const { Cell::new(None) };
+    static CALLS: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Runs `f`, with every solver it starts on this thread limited to `limit`.
+pub fn with_limit<R>(limit: Option<Duration>, f: impl FnOnce() -> R) -> R {
+    scoped::replace(&LIMIT, limit, f).0
+}
+
+/// Runs the solver `f` against the current limit. A solver started by another keeps to the
+/// outer one's deadline.
+pub fn start<R>(f: impl FnOnce() -> R) -> R {
+    let previous = DEADLINE.with(Cell::get);
+    let deadline = match (previous, LIMIT.with(Cell::get)) {
+        (Some(previous), _) => Some(previous),
+        (None, limit) => limit.map(|limit| (Instant::now() + limit, limit)),
+    };
+    scoped::replace(&DEADLINE, deadline, f).0
+}
+
+/// Fails with `SolveError::TimedOut` once the running solver is out of time.
+pub fn check() -> Result<(), SolveError> {
+    let Some((deadline, limit)) = DEADLINE.with(Cell::get) else {
+        return Ok(());
+    };
+    let calls = CALLS.with(|calls| {
+        calls.set(calls.get().wrapping_add(1));
+        calls.get()
+    });
+    if calls % CHECK_EVERY == 0 && Instant::now() >= deadline {
+        return Err(SolveError::TimedOut(limit));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spin() -> Result<(), SolveError> {
+        loop {
+            check()?;
+        }
+    }
+
+    #[test]
+    fn stops_solvers_past_their_limit() {
+        let limit = Duration::from_millis(20);
+        let started = Instant::now();
+        assert_eq!(
+            with_limit(Some(limit), || start(spin)),
+            Err(SolveError::TimedOut(limit))
+        );
+        assert!(started.elapsed() >= limit);
+        // Outside a solver, or without a limit, there's nothing to stop.
+        assert_eq!(with_limit(Some(limit), check), Ok(()));
+        assert_eq!(start(|| (0..10_000).try_for_each(|_| check())), Ok(()));
+        // A solver started by another reports the outer limit it ran out of.
+        let nested = || with_limit(Some(Duration::from_secs(3600)), || start(spin));
+        assert_eq!(
+            with_limit(Some(limit), || start(nested)),
+            Err(SolveError::TimedOut(limit))
+        );
+    }
+}
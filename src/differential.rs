@@ -0,0 +1,159 @@
+//! Differential tests: a day's optimised solver is run against a brute-force reference on
+//! many generated inputs. Generators are handed a size which grows over the run, so the first
+//! disagreement found is already small, and it's then shrunk further by deleting lines,
+//! columns and characters for as long as the two still disagree.
+
+use crate::error::SolveError;
+use crate::rng::Rng;
+use std::{
+    fmt::Debug,
+    panic::{self, AssertUnwindSafe},
+};
+
+const SEED: u64 = 0x2022_1225;
+const MAX_SIZE: usize = 20;
+
+/// What a solver made of an input. Errors are compared by kind only, since the reference has
+/// no reason to word its errors the same way.
+#[derive(Debug, PartialEq)]
+enum Outcome<T> {
+    Answer(T),
+    Failed,
+    Panicked,
+}
+
+fn outcome<T>(solver: &impl Fn(&str) -> Result<T, SolveError>, input: &str) -> Outcome<T> {
+    match panic::catch_unwind(AssertUnwindSafe(|| solver(input))) {
+        Ok(Ok(answer)) => Outcome::Answer(answer),
+        Ok(Err(_)) => Outcome::Failed,
+        Err(_) => Outcome::Panicked,
+    }
+}
+
+/// Smaller inputs to try in place of `input`: each line removed, each column removed from
+/// every line, then each character removed. Only strictly shorter ones are kept, as removing
+/// the only line of "\n" gives "\n" back, and shrinking would never end.
+fn shrink_candidates(input: &str) -> Vec<String> {
+    let lines: Vec<&str> = input.lines().collect();
+    let mut candidates = Vec::new();
+    for skip in 0..lines.len() {
+        let kept: Vec<&str> = (0..lines.len())
+            .filter(|idx| *idx != skip)
+            .map(|idx| lines[idx])
+            .collect();
+        candidates.push(kept.join("\n") + "\n");
+    }
+    let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+    if lines.len() > 1 {
+        for column in 0..width {
+            let kept: Vec<String> = lines
+                .iter()
+                .map(|line| {
+                    line.char_indices()
+                        .filter(|(idx, _)| *idx != column)
+                        .map(|(_, c)| c)
+                        .collect()
+                })
+                .collect();
+            candidates.push(kept.join("\n") + "\n");
+        }
+    }
+    for (idx, c) in input.char_indices() {
+        if c != '\n' {
+            candidates.push(format!("{}{}", &input[..idx], &input[idx + c.len_utf8()..]));
+        }
+    }
+    candidates.retain(|candidate| candidate.len() < input.len());
+    candidates
+}
+
+/// Runs `optimised` and `reference` on `cases` inputs from `generate`, panicking with the
+/// smallest input they disagree on. A panic on either side always counts as a disagreement.
+pub fn compare<T: PartialEq + Debug>(
+    cases: usize,
+    generate: impl Fn(&mut Rng, usize) -> String,
+    reference: impl Fn(&str) -> Result<T, SolveError>,
+    optimised: impl Fn(&str) -> Result<T, SolveError>,
+) {
+    let disagree = |input: &str| {
+        let expected = outcome(&reference, input);
+        expected == Outcome::Panicked || expected != outcome(&optimised, input)
+    };
+    let mut rng = Rng::new(SEED);
+    for case in 0..cases {
+        let input = generate(&mut rng, 1 + case * MAX_SIZE / cases.max(1));
+        if !disagree(&input) {
+            continue;
+        }
+        let mut smallest = input;
+        while let Some(smaller) = shrink_candidates(&smallest)
+            .into_iter()
+            .find(|candidate| disagree(candidate))
+        {
+            smallest = smaller;
+        }
+        panic!(
+            "case {} (seed {:#x}) disagrees, shrunk to {:?}\nreference: {:?}\noptimised: {:?}",
+            case,
+            SEED,
+            smallest,
+            outcome(&reference, &smallest),
+            outcome(&optimised, &smallest)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_smallest_disagreement() {
+        let result = panic::catch_unwind(|| {
+            compare(
+                50,
+                |rng, size| {
+                    let line: String = (0..size).map(|_| *rng.choose(&['a', 'b'])).collect();
+                    line + "\n"
+                },
+                |input| Ok(input.matches('b').count() > 2),
+                |input| Ok(input.contains("bbb")),
+            )
+        });
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        // Three b's, but never three in a row.
+        assert!(
+            ["\"bbab\\n\"", "\"babb\\n\""]
+                .iter()
+                .any(|shrunk| message.contains(&format!("shrunk to {}", shrunk))),
+            "{}",
+            message
+        );
+
+        compare(
+            50,
+            |rng, size| format!("{}\n", rng.range(0, size as i64)),
+            |input| {
+                input
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| SolveError::EmptyInput)
+            },
+            |input| Ok(input.trim().parse::<u32>().unwrap()),
+        );
+    }
+
+    #[test]
+    fn shrinks_down_to_a_blank_line() {
+        let result = panic::catch_unwind(|| {
+            compare(
+                10,
+                |rng, size| format!("{}\n", "x".repeat(rng.range(0, size as i64) as usize)),
+                |input| Ok(input.len()),
+                |input| Ok(input.trim().len()),
+            )
+        });
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("shrunk to \"\\n\""), "{}", message);
+    }
+}
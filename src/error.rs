@@ -0,0 +1,168 @@
+use advent_of_code::parse::{ParseError, Parser};
+use std::{any::Any, error::Error, fmt, time::Duration};
+
+/// Why a solver couldn't produce an answer for its input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// The input doesn't match the day's format. `line` and `column` are 1-based and point at
+    /// the first character that can't be part of a valid input.
+    Parse {
+        line: usize,
+        column: usize,
+        reason: String,
+    },
+    /// The input parsed but has nothing in it to work with.
+    EmptyInput,
+    /// Day 5: a move takes a crate from a stack that has run out.
+    EmptyStack { stack: usize },
+    /// Day 21: a monkey's job refers to a monkey nobody declared.
+    MissingMonkey(String),
+    /// The search for the answer finished without finding one.
+    NoSolution(String),
+    /// The input is well formed but breaks one of the puzzle's rules.
+    Invalid(String),
+    /// The solver gave up when it ran out of the time it was given.
+    TimedOut(Duration),
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Parse {
+                line,
+                column,
+                reason,
+            } => write!(
+                f,
+                "parse error at line {}, column {}: {}",
+                line, column, reason
+            ),
+            SolveError::EmptyInput => write!(f, "input is empty"),
+            SolveError::EmptyStack { stack } => {
+                write!(f, "tried to move a crate off empty stack {}", stack)
+            }
+            SolveError::MissingMonkey(name) => write!(f, "no monkey named `{}`", name),
+            SolveError::NoSolution(what) => write!(f, "no solution: {}", what),
+            SolveError::Invalid(what) => write!(f, "invalid input: {}", what),
+            SolveError::TimedOut(limit) => {
+                write!(f, "timed out after {}", format_duration(*limit))
+            }
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+/// A duration in the largest unit that keeps it readable, such as `1.234ms`.
+pub fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_micros();
+    if micros == 0 {
+        format!("{}ns", duration.as_nanos())
+    } else if micros < 1_000 {
+        format!("{}µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.3}ms", micros as f64 / 1_000.0)
+    } else {
+        format!("{:.3}s", duration.as_secs_f64())
+    }
+}
+
+/// The message a caught panic was raised with.
+pub fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast::<&str>().map_or_else(
+            |_| "unknown panic".to_owned(),
+            |message| (*message).to_owned(),
+        ),
+    }
+}
+
+/// Whether `prefix` could still be the start of an input `parser` accepts.
+fn viable<P: Parser + Copy>(parser: P, prefix: &str) -> bool {
+    match parser.parse(prefix).finish() {
+        Ok(_) | Err(ParseError::EndOfString) => true,
+        Err(_) => false,
+    }
+}
+
+/// Turns a byte offset into a 1-based line and column.
+fn locate(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// Runs `parser` over the whole input. The parsers don't report where they gave up, so on
+/// failure we search for the shortest prefix that no longer looks like the start of a valid
+/// input; its last character is the one to blame. If every prefix is fine the input was cut
+/// short, and the error points just past its end.
+pub fn parse_input<P: Parser + Copy>(parser: P, input: &str) -> Result<P::Output, SolveError> {
+    let err = match parser.parse(input).finish() {
+        Ok(output) => return Ok(output),
+        Err(err) => err,
+    };
+    let chars: Vec<(usize, usize)> = input
+        .char_indices()
+        .map(|(idx, c)| (idx, idx + c.len_utf8()))
+        .collect();
+    let first_bad = chars.partition_point(|(_, end)| viable(parser, &input[..*end]));
+    let offset = chars
+        .get(first_bad)
+        .map_or(input.len(), |(start, _)| *start);
+    let (line, column) = locate(input, offset);
+    Err(SolveError::Parse {
+        line,
+        column,
+        reason: format!("{:?}", err),
+    })
+}
+
+/// Checks that parsed rows form a non-empty rectangle, so they can safely become a `Grid`.
+pub fn check_rectangular<T>(rows: &[Vec<T>]) -> Result<(), SolveError> {
+    let width = rows.first().map_or(0, Vec::len);
+    if width == 0 {
+        return Err(SolveError::EmptyInput);
+    }
+    match rows.iter().position(|row| row.len() != width) {
+        Some(idx) => Err(SolveError::Parse {
+            line: idx + 1,
+            column: rows[idx].len().min(width) + 1,
+            reason: format!("expected {} columns, found {}", width, rows[idx].len()),
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locate_offsets() {
+        let input = "ab\ncd\n\nef";
+        assert_eq!(locate(input, 0), (1, 1));
+        assert_eq!(locate(input, 1), (1, 2));
+        assert_eq!(locate(input, 3), (2, 1));
+        assert_eq!(locate(input, 6), (3, 1));
+        assert_eq!(locate(input, 8), (4, 2));
+        assert_eq!(locate(input, input.len()), (4, 3));
+    }
+
+    #[test]
+    fn rectangular_rows() {
+        assert_eq!(check_rectangular(&[vec![1, 2], vec![3, 4]]), Ok(()));
+        assert_eq!(check_rectangular::<u8>(&[]), Err(SolveError::EmptyInput));
+        assert_eq!(
+            check_rectangular(&[vec![1, 2], vec![3, 4], vec![5]]),
+            Err(SolveError::Parse {
+                line: 3,
+                column: 2,
+                reason: "expected 2 columns, found 1".to_owned()
+            })
+        );
+    }
+}
@@ -0,0 +1,76 @@
+//! Traces of how a solver reached its answer, such as which directories day 7 added up. As
+//! with `progress`, solvers report each step to whoever is tracing the current thread, and a
+//! step is only formatted when someone is.
+
+use crate::scoped;
+use std::{cell::RefCell, fmt};
+
+/// One intermediate result: what kind of result it is, and the result itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub kind: &'static str,
+    pub detail: String,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind, self.detail)
+    }
+}
+
+thread_local! {
+    static TRACE: RefCell<Option<Vec<Step>>> = const { RefCell::new(None) };
+}
+
+/// Whether anyone is tracing this thread, for solvers which have extra work to do to explain
+/// themselves.
+pub fn tracing() -> bool {
+    TRACE.with(|trace| trace.borrow().is_some())
+}
+
+/// Adds a step to the trace.
+pub fn step(kind: &'static str, detail: fmt::Arguments) {
+    TRACE.with(|trace| {
+        if let Some(steps) = trace.borrow_mut().as_mut() {
+            steps.push(Step {
+                kind,
+                detail: detail.to_string(),
+            });
+        }
+    });
+}
+
+/// Runs `f`, returning its result along with the steps it reported. Any trace already being
+/// kept for this thread is suspended until `f` returns.
+pub fn trace<R>(f: impl FnOnce() -> R) -> (R, Vec<Step>) {
+    let (result, steps) = scoped::replace(&TRACE, Some(Vec::new()), f);
+    (result, steps.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_reach_the_innermost_trace() {
+        step("ignored", format_args!("nobody is tracing"));
+        assert!(!tracing());
+        let ((inner, was_tracing), outer) = trace(|| {
+            step("outer", format_args!("{}", 1));
+            let inner = trace(|| step("inner", format_args!("{}", 2)));
+            step("outer", format_args!("{}", 3));
+            (inner, tracing())
+        });
+        assert!(was_tracing);
+        assert!(!tracing());
+        assert_eq!(
+            inner.1,
+            vec![Step {
+                kind: "inner",
+                detail: "2".to_owned()
+            }]
+        );
+        let outer: Vec<String> = outer.iter().map(Step::to_string).collect();
+        assert_eq!(outer, vec!["outer: 1", "outer: 3"]);
+    }
+}
@@ -0,0 +1,19 @@
+//! Example inputs live in `fixtures/day<n>/<name>.txt`, with their expected answers in
+//! `<name>.answers` using the same `day/part = answer` lines as `answers.txt`. The build
+//! script generates a test for each of them.
+
+use crate::{answers::Answers, cli::Part, solution};
+use std::{fs, path::Path};
+
+const FIXTURES_PATH: &str = "fixtures";
+
+fn check_fixture(day: u8, name: &str, part: Part) {
+    let dir = Path::new(FIXTURES_PATH).join(format!("day{}", day));
+    let input = fs::read_to_string(dir.join(format!("{}.txt", name))).unwrap();
+    let answers = Answers::load(dir.join(format!("{}.answers", name))).unwrap();
+    let expected = answers.get(day, part).unwrap();
+    let entry = solution::find(day).unwrap();
+    assert_eq!(entry.solve(part, &input), Ok(expected.to_owned()));
+}
+
+include!(concat!(env!("OUT_DIR"), "/fixture_tests.rs"));
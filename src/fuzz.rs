@@ -0,0 +1,236 @@
+//! Fuzzing the day parsers. Whatever bytes they're given, they should return an error rather
+//! than panic, overflow or never finish. `parse` is the entry point for an external fuzzer,
+//! such as a cargo-fuzz target of
+//!
+//! ```text
+//! fuzz_target!(|data: &[u8]| advent_of_code_2022::fuzz::parse(7, data));
+//! ```
+//!
+//! and `run` is a small built-in driver that feeds a parser mutated copies of its example.
+
+use crate::error::{panic_message, SolveError};
+use crate::progress;
+use crate::rng::Rng;
+use crate::solution::{self, Entry};
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+pub const DEFAULT_RUNS: usize = 2000;
+pub const DEFAULT_SEED: u64 = 0x2022_1201;
+/// How long a parser may spend on one input before we decide it's never going to finish.
+pub const TIMEOUT: Duration = Duration::from_secs(2);
+/// Keeps mutated inputs small enough that a slow parser isn't mistaken for a stuck one.
+const MAX_LEN: usize = 4096;
+
+/// Pieces of input worth splicing in: the separators parsers look for, numbers too big for
+/// their types, and characters that take more than one byte.
+const FRAGMENTS: &[&str] = &[
+    "\n",
+    "\n\n",
+    "\r\n",
+    " ",
+    ",",
+    ":",
+    "-",
+    "=",
+    "[",
+    "]",
+    "0",
+    "-1",
+    "4294967296",
+    "99999999999999999999",
+    "é",
+    "🎄",
+    "\0",
+];
+
+/// Runs day `day`'s parser on `data`, read as UTF-8 with anything invalid replaced. A panic in
+/// the parser is passed on, which is what fuzzers look for.
+pub fn parse(day: u8, data: &[u8]) {
+    if let Some(entry) = solution::find(day) {
+        let _ = entry.parse(&String::from_utf8_lossy(data));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    Panicked(String),
+    Hung,
+}
+
+/// The first input a parser failed on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub input: String,
+    pub problem: Problem,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.problem {
+            Problem::Panicked(message) => write!(f, "panicked with `{}`", message)?,
+            Problem::Hung => write!(f, "still running after {:?}", TIMEOUT)?,
+        }
+        write!(f, " on input {:?}", self.input)
+    }
+}
+
+/// How many inputs were tried, and how many of them the parser accepted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub runs: usize,
+    pub accepted: usize,
+}
+
+/// Applies a few random edits to `seed`: overwriting a byte, splicing in a fragment, deleting
+/// or repeating a stretch, or cutting it short. Bytes which no longer make valid UTF-8 end up
+/// as replacement characters.
+fn mutate(rng: &mut Rng, seed: &str) -> String {
+    let mut bytes = seed.as_bytes().to_vec();
+    for _ in 0..rng.range(1, 4) {
+        let len = bytes.len() as i64;
+        let at = rng.range(0, len) as usize;
+        let end = rng.range(at as i64, len.min(at as i64 + 16)) as usize;
+        match rng.range(0, 4) {
+            0 if at < bytes.len() => bytes[at] = rng.next_u64() as u8,
+            1 => {
+                let fragment = rng.choose(FRAGMENTS).as_bytes();
+                bytes.splice(at..at, fragment.iter().copied());
+            }
+            2 => {
+                bytes.drain(at..end);
+            }
+            3 => {
+                let stretch = bytes[at..end].to_vec();
+                bytes.splice(at..at, stretch);
+            }
+            _ => bytes.truncate(at),
+        }
+    }
+    bytes.truncate(MAX_LEN);
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Feeds `parse` `runs` mutations of the `seeds`, stopping at the first input it panics on or
+/// takes longer than `timeout` over. The parser runs on a thread of its own so that it can be
+/// timed out; one that never finishes is left running there, since there's no way to stop it.
+pub fn run(
+    parse: impl Fn(&str) -> Result<(), SolveError> + Send + 'static,
+    seeds: &[&str],
+    runs: usize,
+    seed: u64,
+    timeout: Duration,
+) -> Result<Stats, Failure> {
+    let (inputs, to_parse) = mpsc::channel::<String>();
+    let (results, parsed) = mpsc::channel();
+    thread::spawn(move || {
+        for input in to_parse {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| parse(&input)));
+            if results.send(result.map(|r| r.is_ok())).is_err() {
+                break;
+            }
+        }
+    });
+    let mut rng = Rng::new(seed);
+    let mut stats = Stats::default();
+    for _ in 0..runs {
+        let base = *rng.choose(seeds);
+        let input = mutate(&mut rng, base);
+        inputs
+            .send(input.clone())
+            .expect("the parser thread only stops once we do");
+        let problem = match parsed.recv_timeout(timeout) {
+            Ok(Ok(accepted)) => {
+                stats.runs += 1;
+                stats.accepted += usize::from(accepted);
+                progress::count("inputs", 1);
+                continue;
+            }
+            Ok(Err(payload)) => Problem::Panicked(panic_message(payload)),
+            Err(_) => Problem::Hung,
+        };
+        return Err(Failure { input, problem });
+    }
+    Ok(stats)
+}
+
+/// Fuzzes a day's parser, starting from its example and from nothing at all.
+pub fn run_day(entry: &'static Entry, runs: usize, seed: u64) -> Result<Stats, Failure> {
+    run(
+        move |input| entry.parse(input),
+        &[entry.example, ""],
+        runs,
+        seed ^ u64::from(entry.number),
+        TIMEOUT,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::REGISTRY;
+
+    #[test]
+    fn catches_panics_and_hangs() {
+        let seeds = ["1,2,3\n"];
+        assert_eq!(
+            run(|_| Ok(()), &seeds, 100, DEFAULT_SEED, TIMEOUT),
+            Ok(Stats {
+                runs: 100,
+                accepted: 100
+            })
+        );
+        let stats = run(
+            |input| match input.parse::<u8>() {
+                Ok(_) => Ok(()),
+                Err(_) => Err(SolveError::EmptyInput),
+            },
+            &seeds,
+            100,
+            DEFAULT_SEED,
+            TIMEOUT,
+        );
+        assert_eq!(stats.map(|stats| stats.runs), Ok(100));
+        let failure = run(
+            |input| match input.len() {
+                6 => Ok(()),
+                _ => panic!("unexpected length"),
+            },
+            &seeds,
+            100,
+            DEFAULT_SEED,
+            TIMEOUT,
+        )
+        .unwrap_err();
+        assert_eq!(
+            failure.problem,
+            Problem::Panicked("unexpected length".to_owned())
+        );
+        assert_ne!(failure.input.len(), 6);
+        let failure = run(
+            |_| loop {
+                thread::sleep(Duration::from_millis(10));
+            },
+            &seeds,
+            100,
+            DEFAULT_SEED,
+            Duration::from_millis(50),
+        )
+        .unwrap_err();
+        assert_eq!(failure.problem, Problem::Hung);
+    }
+
+    #[test]
+    fn every_parser_survives() {
+        for entry in REGISTRY {
+            if let Err(failure) = run_day(entry, 300, DEFAULT_SEED) {
+                panic!("day {} parser {}", entry.number, failure);
+            }
+        }
+    }
+}
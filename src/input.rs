@@ -0,0 +1,91 @@
+use crate::solution::Entry;
+use crate::{COOKIE_PATH, INPUT_CACHE, YEAR};
+use advent_of_code::web_api::load_question_input;
+use std::{
+    error::Error,
+    fmt, fs,
+    io::{self, Read},
+    path::PathBuf,
+};
+
+/// Where a solver's puzzle input comes from. Only `Cache` needs the session cookie, the
+/// other sources never touch the network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    Cache,
+    File(PathBuf),
+    Stdin,
+}
+
+impl InputSource {
+    /// Interprets a `--input` argument, where `-` means stdin.
+    pub fn of_arg(arg: &str) -> Self {
+        if arg == "-" {
+            InputSource::Stdin
+        } else {
+            InputSource::File(PathBuf::from(arg))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InputError {
+    source: InputSource,
+    err: io::Error,
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            InputSource::Cache => write!(f, "failed to load cached input: {}", self.err),
+            InputSource::File(path) => write!(f, "failed to read {}: {}", path.display(), self.err),
+            InputSource::Stdin => write!(f, "failed to read stdin: {}", self.err),
+        }
+    }
+}
+
+impl Error for InputError {}
+
+/// Puzzle inputs use `\n` line endings and end in a newline, which hand-supplied files
+/// (saved on Windows, or pasted without the final line break) don't always do.
+pub fn normalize(input: String) -> String {
+    let mut input = if input.contains('\r') {
+        input.replace("\r\n", "\n")
+    } else {
+        input
+    };
+    if !input.is_empty() && !input.ends_with('\n') {
+        input.push('\n');
+    }
+    input
+}
+
+pub fn load(source: &InputSource, entry: &Entry) -> Result<String, InputError> {
+    let read = match source {
+        InputSource::Cache => {
+            return Ok(load_question_input(
+                YEAR,
+                COOKIE_PATH,
+                INPUT_CACHE,
+                entry.question,
+            ))
+        }
+        InputSource::File(path) => fs::read_to_string(path),
+        InputSource::Stdin => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map(|_| input)
+        }
+    };
+    read.map(normalize).map_err(|err| InputError {
+        source: source.clone(),
+        err,
+    })
+}
+
+#[test]
+fn normalize_line_endings() {
+    assert_eq!(normalize("1\r\n2\r\n".to_owned()), "1\n2\n");
+    assert_eq!(normalize("1\n2".to_owned()), "1\n2\n");
+    assert_eq!(normalize("1\n2\n".to_owned()), "1\n2\n");
+    assert_eq!(normalize(String::new()), "");
+}
@@ -0,0 +1,104 @@
+//! Solutions to Advent of Code 2022, usable from other tools as well as the runner.
+//!
+//! Each `day<n>` module has a `parse` function giving the day's parsed input, and `part1` and
+//! `part2` solving it. Some days also export the pieces worth reusing on their own, such as
+//! `day13::compare_packets`, `day21::attempt_solve` and `day25::Snafu`. `solution::REGISTRY`
+//! has every day behind one interface, and solvers report their progress, draw frames,
+//! explain their answers and stop at their time limit through `progress`, `render`,
+//! `explain` and `deadline`. `fuzz` has entry
+//! points for fuzzing each day's parser, and each day's `generate` makes random puzzle inputs
+//! of any size for stress testing. `tuning` lists the puzzle constants, such as day 15's row,
+//! which can be changed for a run.
+//!
+//! `memory` can only count allocations made through `memory::Counting`, so a program which
+//! wants the heap usage of its solvers must install it as its `#[global_allocator]`; without
+//! it every `memory::Usage` is zero.
+//!
+//! The remaining modules make up the command line runner. They're public so that `main.rs`
+//! can use them, but aren't meant for anyone else and may change at any time.
+
+#![feature(return_position_impl_trait_in_trait)]
+#![allow(incomplete_features)]
+
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day2;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod deadline;
+pub mod error;
+pub mod explain;
+pub mod fuzz;
+pub mod memory;
+pub mod progress;
+pub mod render;
+pub mod rng;
+pub mod scoped;
+pub mod solution;
+pub mod tuning;
+
+#[doc(hidden)]
+pub mod answers;
+#[doc(hidden)]
+pub mod bench;
+#[doc(hidden)]
+pub mod cache;
+#[doc(hidden)]
+pub mod cli;
+#[doc(hidden)]
+pub mod input;
+#[doc(hidden)]
+pub mod output;
+#[doc(hidden)]
+pub mod pool;
+#[doc(hidden)]
+pub mod regress;
+#[doc(hidden)]
+pub mod repl;
+#[doc(hidden)]
+pub mod scaffold;
+#[doc(hidden)]
+pub mod serve;
+#[doc(hidden)]
+pub mod submit;
+#[doc(hidden)]
+pub mod timing;
+
+// The runner installs this in `main.rs`; the tests need it too, to have anything to measure.
+#[cfg(test)]
+#[global_allocator]
+static ALLOCATOR: memory::Counting = memory::Counting;
+
+#[cfg(test)]
+mod differential;
+#[cfg(test)]
+mod fixtures;
+
+pub use cli::{Part, PARTS};
+pub use error::SolveError;
+pub use solution::{Entry, Solution, REGISTRY};
+
+pub const YEAR: &str = "2022";
+pub const COOKIE_PATH: &str = "../session.cookie";
+pub const INPUT_CACHE: &str = "inputs";
+pub const ANSWERS_PATH: &str = "answers.txt";
@@ -1,117 +1,517 @@
-#![feature(return_position_impl_trait_in_trait)]
-#![allow(incomplete_features)]
-#![feature(test)]
-
-use advent_of_code::{day::Day, web_api::load_question_input};
-use std::error::Error;
-
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day2;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-
-const YEAR: &str = "2022";
-const COOKIE_PATH: &str = "../session.cookie";
-const INPUT_CACHE: &str = "inputs";
-
-fn main() -> Result<(), Box<dyn Error>> {
-    let input = load_question_input(YEAR, COOKIE_PATH, INPUT_CACHE, Day::Day25);
-
-    println!("{}", day25::part1(&input));
+use advent_of_code_2022::answers::{escape, Answers, Verdict};
+use advent_of_code_2022::bench::{self, Baseline};
+use advent_of_code_2022::cli::{
+    self, CacheCommand, CliError, Command, Format, Frames, Part, Selection, PARTS,
+};
+use advent_of_code_2022::input::{self, InputSource};
+use advent_of_code_2022::output::{self, Record};
+use advent_of_code_2022::repl::{self, Session};
+use advent_of_code_2022::rng::Rng;
+use advent_of_code_2022::solution::{self, Entry, REGISTRY};
+use advent_of_code_2022::submit::{self, Outcome};
+use advent_of_code_2022::timing::{self, DayTiming};
+use advent_of_code_2022::{
+    cache, deadline, explain, fuzz, memory, pool, progress, regress, render, scaffold, serve,
+    ANSWERS_PATH, COOKIE_PATH, INPUT_CACHE, YEAR,
+};
+use std::{
+    error::Error,
+    fs, io,
+    net::{Ipv4Addr, TcpListener},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
+};
+
+#[global_allocator]
+static ALLOCATOR: memory::Counting = memory::Counting;
+
+const SUBMISSIONS_PATH: &str = "submissions.txt";
+const BASE_URL: &str = "https://adventofcode.com";
+
+fn select(
+    selection: Selection,
+    part: Option<Part>,
+) -> Result<(Vec<&'static Entry>, Vec<Part>), CliError> {
+    let entries = match selection {
+        Selection::All => REGISTRY.iter().collect(),
+        Selection::Day(day) => vec![solution::find(day).ok_or(CliError::UnknownDay(day))?],
+    };
+    let parts = match part {
+        Some(part) => vec![part],
+        None => PARTS.to_vec(),
+    };
+    Ok((entries, parts))
+}
+
+/// Solves one part, writing the frames its simulation draws when `frames` asks for them and
+/// keeping its trace when `explain` is set.
+fn solve_part(
+    entry: &Entry,
+    part: Part,
+    input: &str,
+    frames: Option<&Frames>,
+    explain: bool,
+) -> Record {
+    let solve = || {
+        if !explain {
+            return Record::solve(entry, part, input);
+        }
+        let (mut record, trace) = explain::trace(|| Record::solve(entry, part, input));
+        record.trace = Some(trace);
+        record
+    };
+    let Some(frames) = frames else {
+        return solve();
+    };
+    let recording = render::recording_for(
+        &frames.dir,
+        entry.number,
+        part,
+        frames.format,
+        frames.steps.clone(),
+    );
+    let (record, written) = render::record(recording, solve);
+    match written {
+        Ok(0) => {}
+        Ok(written) => eprintln!(
+            "day {} part {}: wrote {} frame(s) to {}",
+            entry.number,
+            part,
+            written,
+            frames.dir.display()
+        ),
+        Err(err) => eprintln!(
+            "day {} part {}: failed to write frames: {}",
+            entry.number, part, err
+        ),
+    }
+    record
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run(
+    selection: Selection,
+    part: Option<Part>,
+    source: InputSource,
+    format: Format,
+    timings: bool,
+    jobs: usize,
+    quiet: bool,
+    memory: bool,
+    frames: Option<Frames>,
+    time_limit: Option<Duration>,
+    explain: bool,
+) -> Result<(), Box<dyn Error>> {
+    let (entries, parts) = select(selection, part)?;
+    // Days running at once would all draw their progress over the same line.
+    let show_progress = !quiet && jobs == 1;
+    let mut failures = 0;
+    if timings && format == Format::Text {
+        let mut results = Vec::new();
+        pool::run_ordered(
+            &entries,
+            jobs,
+            |entry| {
+                let input = input::load(&source, entry)?;
+                Ok(progress::show(
+                    show_progress,
+                    &format!("day {}", entry.number),
+                    || deadline::with_limit(time_limit, || timing::time_day(entry, &parts, &input)),
+                ))
+            },
+            |timing| results.push(timing),
+        );
+        let timings = results
+            .into_iter()
+            .collect::<Result<Vec<DayTiming>, input::InputError>>()?;
+        print!("{}", timing::format_table(&timings));
+        if memory {
+            print!("\n{}", timing::format_memory_table(&timings));
+        }
+        failures = timings
+            .iter()
+            .flat_map(|timing| timing.parts.iter())
+            .filter(|p| p.answer.is_err())
+            .count();
+    } else {
+        print!("{}", output::begin(format));
+        let mut printed = 0;
+        let mut load_error = None;
+        pool::run_ordered(
+            &entries,
+            jobs,
+            |entry| {
+                let input = input::load(&source, entry)?;
+                Ok(progress::show(
+                    show_progress,
+                    &format!("day {}", entry.number),
+                    || {
+                        deadline::with_limit(time_limit, || {
+                            parts
+                                .iter()
+                                .map(|part| {
+                                    solve_part(entry, *part, &input, frames.as_ref(), explain)
+                                })
+                                .collect::<Vec<Record>>()
+                        })
+                    },
+                ))
+            },
+            |records: Result<Vec<Record>, input::InputError>| match records {
+                Ok(records) => {
+                    for record in records {
+                        if record.answer.is_err() {
+                            failures += 1;
+                        }
+                        print!("{}", output::format_record(format, &record, printed == 0));
+                        printed += 1;
+                    }
+                }
+                Err(err) => {
+                    load_error.get_or_insert(err);
+                }
+            },
+        );
+        print!("{}", output::end(format, printed == 0));
+        if let Some(err) = load_error {
+            return Err(err.into());
+        }
+    }
+    if failures > 0 {
+        return Err(format!("{} part(s) failed", failures).into());
+    }
     Ok(())
 }
 
-macro_rules! tests {
-    (@test_module) => {};
+fn verify(
+    selection: Selection,
+    part: Option<Part>,
+    source: InputSource,
+    answers: Option<String>,
+) -> Result<(), Box<dyn Error>> {
+    let (entries, parts) = select(selection, part)?;
+    let answers = Answers::load(answers.as_deref().unwrap_or(ANSWERS_PATH))?;
+    let mut failures = 0;
+    for entry in entries {
+        let input = input::load(&source, entry)?;
+        for part in parts.iter() {
+            let answer = match entry.solve(*part, &input) {
+                Ok(answer) => answer,
+                Err(err) => {
+                    failures += 1;
+                    println!("day {} part {}: error: {}", entry.number, part, err);
+                    continue;
+                }
+            };
+            let verdict = answers.check(entry.number, *part, &answer);
+            let outcome = match verdict {
+                Verdict::Correct => "ok".to_owned(),
+                Verdict::Unknown => format!("no known answer, got {}", escape(&answer)),
+                Verdict::Incorrect { expected } => {
+                    failures += 1;
+                    format!(
+                        "WRONG, expected {} but got {}",
+                        escape(&expected),
+                        escape(&answer)
+                    )
+                }
+            };
+            println!("day {} part {}: {}", entry.number, part, outcome);
+        }
+    }
+    if failures > 0 {
+        return Err(format!("{} answer(s) did not match or failed", failures).into());
+    }
+    Ok(())
+}
 
-    (@test_module $day: ident, $day_test: ident, $question: expr, $part1: expr, $part2: expr) => {
-        tests!(@test_module $day, $day_test, $question, $part1, $part2;);
-    };
+/// Benchmarks the selected days. A solver which fails is reported but doesn't stop the
+/// run, since some days (such as day 22's hard-coded cube) can't solve their example.
+fn bench(
+    selection: Selection,
+    part: Option<Part>,
+    source: InputSource,
+    examples: bool,
+    samples: usize,
+    save: Option<String>,
+    baseline: Option<String>,
+) -> Result<(), Box<dyn Error>> {
+    let (entries, parts) = select(selection, part)?;
+    let baseline = baseline.map(Baseline::load).transpose()?;
+    let mut results = Vec::new();
+    for entry in entries {
+        let input = match examples {
+            true => None,
+            false => Some(input::load(&source, entry)?),
+        };
+        let mut inputs = vec![];
+        if let Some(input) = input.as_deref() {
+            inputs.push(("input", input));
+        }
+        inputs.push(("example", entry.example));
+        results.extend(bench::bench_day(entry, &parts, &inputs, samples));
+    }
+    print!("{}", bench::format_report(&results, baseline.as_ref()));
+    if let Some(path) = save {
+        Baseline::of_results(&results).save(&path)?;
+        println!("saved baseline to {}", path);
+    }
+    Ok(())
+}
 
-    (@test_module $day: ident, $day_test: ident, $question: expr, $part1: expr, $part2: expr; $($rest:tt)*) => {
-        mod $day_test {
-            use super::*;
+fn submit(
+    day: u8,
+    part: Part,
+    source: InputSource,
+    base_url: Option<String>,
+) -> Result<(), Box<dyn Error>> {
+    let entry = solution::find(day).ok_or(CliError::UnknownDay(day))?;
+    let answer = entry.solve(part, &input::load(&source, entry)?)?;
+    println!("day {} part {}: submitting {}", day, part, answer);
+    let (outcome, logged) = submit::submit(
+        base_url.as_deref().unwrap_or(BASE_URL),
+        YEAR,
+        COOKIE_PATH,
+        &submit::Log(SUBMISSIONS_PATH),
+        day,
+        part,
+        &answer,
+    )?;
+    if logged {
+        println!("already submitted, see {}", SUBMISSIONS_PATH);
+    }
+    println!("day {} part {}: {}", day, part, outcome);
+    match outcome {
+        Outcome::Correct => Ok(()),
+        _ => Err(format!("answer was not accepted: {}", outcome).into()),
+    }
+}
 
-            #[test]
-            fn part1_test() {
-                assert_eq!(
-                    $part1,
-                    $day::part1(&load_question_input(YEAR, COOKIE_PATH, INPUT_CACHE, $question))
-                );
-            }
+/// Runs the selected days over every input set in `dir`. Sets run in parallel, each on its
+/// own thread.
+fn regress(
+    dir: PathBuf,
+    selection: Selection,
+    part: Option<Part>,
+    jobs: usize,
+    quiet: bool,
+    time_limit: Option<Duration>,
+) -> Result<(), Box<dyn Error>> {
+    let (entries, parts) = select(selection, part)?;
+    let sets = regress::discover(&dir)?;
+    let show_progress = !quiet && jobs == 1;
+    let mut checks = Vec::new();
+    pool::run_ordered(
+        &sets,
+        jobs,
+        |set| {
+            progress::show(show_progress, &set.name, || {
+                deadline::with_limit(time_limit, || regress::run_set(set, &entries, &parts))
+            })
+        },
+        |set_checks| checks.extend(set_checks),
+    );
+    print!("{}", regress::format_report(&sets, &checks));
+    let failures = checks
+        .iter()
+        .filter(|check| check.outcome.is_failure())
+        .count();
+    if failures > 0 {
+        return Err(format!("{} part(s) failed on at least one input", failures).into());
+    }
+    Ok(())
+}
 
-            #[test]
-            fn part2_test() {
-                assert_eq!(
-                    $part2,
-                    $day::part2(&load_question_input(YEAR, COOKIE_PATH, INPUT_CACHE, $question))
-                );
+fn fuzz(selection: Selection, runs: usize, seed: u64, quiet: bool) -> Result<(), Box<dyn Error>> {
+    let (entries, _) = select(selection, None)?;
+    let mut failures = 0;
+    for entry in entries {
+        let label = format!("day {}", entry.number);
+        match progress::show(!quiet, &label, || fuzz::run_day(entry, runs, seed)) {
+            Ok(stats) => println!(
+                "day {}: ok, {} input(s) of which {} parsed",
+                entry.number, stats.runs, stats.accepted
+            ),
+            Err(failure) => {
+                failures += 1;
+                println!("day {}: parser {}", entry.number, failure);
             }
         }
+    }
+    if failures > 0 {
+        return Err(format!("{} parser(s) failed", failures).into());
+    }
+    Ok(())
+}
 
-        tests!(@test_module $($rest)*);
-    };
+fn generate(day: u8, size: usize, seed: u64, out: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    let entry = solution::find(day).ok_or(CliError::UnknownDay(day))?;
+    let input = entry.generate(&mut Rng::new(seed), size);
+    match out {
+        Some(path) => fs::write(path, input)?,
+        None => print!("{}", input),
+    }
+    Ok(())
+}
 
-    ($($list:tt)*) => {
-        #[cfg(test)]
-        mod test {
-            use super::*;
+fn repl(day: Option<u8>, time_limit: Option<Duration>) -> Result<(), Box<dyn Error>> {
+    let mut session = Session::new(day, time_limit)?;
+    repl::run(&mut session, io::stdin().lock(), &mut io::stdout())?;
+    Ok(())
+}
 
-            tests!(@test_module $($list)*);
+fn serve(port: u16, time_limit: Option<Duration>) -> Result<(), Box<dyn Error>> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    eprintln!("listening on http://{}", listener.local_addr()?);
+    serve::serve(listener, time_limit);
+    Ok(())
+}
+
+fn cache(command: CacheCommand) -> Result<(), Box<dyn Error>> {
+    let dir = Path::new(INPUT_CACHE);
+    match command {
+        CacheCommand::List => {
+            let inputs = cache::discover(dir)?;
+            let mut rows = vec![["file", "year", "day", "bytes", "lines"]
+                .map(str::to_owned)
+                .to_vec()];
+            for input in inputs.iter() {
+                let contents = fs::read(&input.path)?;
+                rows.push(vec![
+                    input.path.display().to_string(),
+                    input.year.clone().unwrap_or_else(|| "-".to_owned()),
+                    input.day.map_or("-".to_owned(), |day| day.to_string()),
+                    contents.len().to_string(),
+                    contents.iter().filter(|b| **b == b'\n').count().to_string(),
+                ]);
+            }
+            print!("{}", timing::format_rows(rows));
+            let missing: Vec<String> = (1..=25)
+                .filter(|day| {
+                    !inputs
+                        .iter()
+                        .any(|input| input.is_for(YEAR) && input.day == Some(*day))
+                })
+                .map(|day| day.to_string())
+                .collect();
+            if !missing.is_empty() {
+                println!("no {} input cached for day(s) {}", YEAR, missing.join(", "));
+            }
         }
-    };
+        CacheCommand::Verify => {
+            let mut failures = 0;
+            for input in cache::discover(dir)?
+                .iter()
+                .filter(|input| input.is_for(YEAR))
+            {
+                let problems = cache::check_file(input, YEAR)?;
+                if problems.is_empty() {
+                    println!("{}: ok", input.path.display());
+                } else {
+                    failures += 1;
+                    let problems: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+                    println!("{}: {}", input.path.display(), problems.join("; "));
+                }
+            }
+            if failures > 0 {
+                return Err(format!("{} cached input(s) have problems", failures).into());
+            }
+        }
+        CacheCommand::Import(from) => {
+            for (path, imported) in cache::import(&from, dir, YEAR)? {
+                println!("{}: {}", path.display(), imported);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn new_day(day: u8) -> Result<(), Box<dyn Error>> {
+    for path in scaffold::new_day(Path::new("."), day)? {
+        println!("wrote {}", path.display());
+    }
+    Ok(())
 }
 
-tests!(
-day1, day1_test, Day::Day01, 67633, 199628;
-day2, day2_test, Day::Day02, 12276, 9975;
-day3, day3_test, Day::Day03, 7848, 2616;
-day4, day4_test, Day::Day04, 534, 841;
-day5, day5_test, Day::Day05, "FJSRQCFTN", "CJVLJQPHS";
-day6, day6_test, Day::Day06, 1198, 3120;
-day7, day7_test, Day::Day07, 1513699, 7991939;
-day8, day8_test, Day::Day08, 1698, 672280;
-day9, day9_test, Day::Day09, 6023, 2533;
-day10, day10_test, Day::Day10, 16880, "
-###..#..#..##..####..##....##.###..###..
-#..#.#.#..#..#....#.#..#....#.#..#.#..#.
-#..#.##...#..#...#..#..#....#.###..#..#.
-###..#.#..####..#...####....#.#..#.###..
-#.#..#.#..#..#.#....#..#.#..#.#..#.#.#..
-#..#.#..#.#..#.####.#..#..##..###..#..#.";
-day11, day11_test, Day::Day11, 99840, 20683044837;
-day12, day12_test, Day::Day12, 391, 386;
-day13, day13_test, Day::Day13, 5825, 24477;
-day14, day14_test, Day::Day14, 618, 26358;
-day15, day15_test, Day::Day15, 5083287, 13134039205729;
-day16, day16_test, Day::Day16, 1617, 2171;
-day17, day17_test, Day::Day17, 3130, 1556521739139;
-day18, day18_test, Day::Day18, 3498, 2008;
-day19, day19_test, Day::Day19, 1981, 10962;
-day20, day20_test, Day::Day20, 2215, 8927480683;
-day21, day21_test, Day::Day21, 194501589693264, 3887609741189;
-day22, day22_test, Day::Day22, 126350, 129339;
-day23, day23_test, Day::Day23, 3815, 893;
-day24, day24_test, Day::Day24, 225, 711;
-day25, day25_test, Day::Day25, "", 0;);
+fn main() -> ExitCode {
+    let result = cli::parse_args(std::env::args().skip(1))
+        .map_err(Box::<dyn Error>::from)
+        .and_then(|command| match command {
+            Command::Help => {
+                print!("{}", cli::USAGE);
+                Ok(())
+            }
+            Command::Run {
+                selection,
+                part,
+                input,
+                format,
+                timings,
+                jobs,
+                quiet,
+                memory,
+                frames,
+                time_limit,
+                explain,
+            } => run(
+                selection, part, input, format, timings, jobs, quiet, memory, frames, time_limit,
+                explain,
+            ),
+            Command::Verify {
+                selection,
+                part,
+                input,
+                answers,
+            } => verify(selection, part, input, answers),
+            Command::Bench {
+                selection,
+                part,
+                input,
+                examples,
+                samples,
+                save,
+                baseline,
+            } => bench(selection, part, input, examples, samples, save, baseline),
+            Command::Submit {
+                day,
+                part,
+                input,
+                base_url,
+            } => submit(day, part, input, base_url),
+            Command::Regress {
+                dir,
+                selection,
+                part,
+                jobs,
+                quiet,
+                time_limit,
+            } => regress(dir, selection, part, jobs, quiet, time_limit),
+            Command::Fuzz {
+                selection,
+                runs,
+                seed,
+                quiet,
+            } => fuzz(selection, runs, seed, quiet),
+            Command::Generate {
+                day,
+                size,
+                seed,
+                out,
+            } => generate(day, size, seed, out),
+            Command::Repl { day, time_limit } => repl(day, time_limit),
+            Command::Serve { port, time_limit } => serve(port, time_limit),
+            Command::Cache(command) => cache(command),
+            Command::NewDay { day } => new_day(day),
+        });
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            if err.is::<CliError>() {
+                eprint!("{}", cli::USAGE);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
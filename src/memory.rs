@@ -0,0 +1,181 @@
+//! Heap accounting for the solvers. The global allocator counts what each thread allocates,
+//! so days running at once on the thread pool don't see each other's allocations. Nothing is
+//! counted unless the program installs `Counting` as its `#[global_allocator]`, which the
+//! library can't do for it.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    fmt,
+};
+
+/// The system allocator, counting allocations as it goes.
+pub struct Counting;
+
+#[derive(Debug, Clone, Copy)]
+struct Counters {
+    allocations: u64,
+    allocated: u64,
+    /// Signed, since memory freed on this thread may have been allocated on another.
+    live: i64,
+    peak: i64,
+}
+
+thread_local! {
+    // Const initialised and without a destructor, so it's safe to use while allocating.
+    static COUNTERS: Cell<Counters> = const {
+        Cell::new(Counters {
+            allocations: 0,
+            allocated: 0,
+            live: 0,
+            peak: 0,
+        })
+    };
+}
+
+fn note_alloc(size: usize) {
+    let _ = COUNTERS.try_with(|counters| {
+        let mut c = counters.get();
+        c.allocations += 1;
+        c.allocated += size as u64;
+        c.live += size as i64;
+        c.peak = c.peak.max(c.live);
+        counters.set(c);
+    });
+}
+
+fn note_free(size: usize) {
+    let _ = COUNTERS.try_with(|counters| {
+        let mut c = counters.get();
+        c.live -= size as i64;
+        counters.set(c);
+    });
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            note_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            note_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        note_free(layout.size());
+    }
+
+    /// Counted as a new allocation of `new_size` replacing the old one, which is what a
+    /// growing `Vec` costs.
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            note_free(layout.size());
+            note_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// What a piece of code allocated. `peak` is the most it had live at once on top of what was
+/// already allocated when it started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub allocations: u64,
+    pub allocated: u64,
+    pub peak: u64,
+}
+
+/// Runs `f`, returning its result along with what it allocated on this thread, or zeros if
+/// `Counting` isn't the global allocator.
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, Usage) {
+    let before = COUNTERS.with(Cell::get);
+    // Start the peak again from here, putting back any enclosing measurement's peak after.
+    COUNTERS.with(|counters| {
+        counters.set(Counters {
+            peak: before.live,
+            ..before
+        })
+    });
+    let result = f();
+    let after = COUNTERS.with(Cell::get);
+    COUNTERS.with(|counters| {
+        counters.set(Counters {
+            peak: after.peak.max(before.peak),
+            ..after
+        })
+    });
+    let usage = Usage {
+        allocations: after.allocations - before.allocations,
+        allocated: after.allocated - before.allocated,
+        peak: (after.peak - before.live).max(0) as u64,
+    };
+    (result, usage)
+}
+
+/// A byte count in the largest binary unit that keeps it at least 1, such as `1.50MiB`.
+pub struct Bytes(pub u64);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+        if self.0 < 1024 {
+            return write!(f, "{}B", self.0);
+        }
+        let mut value = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        write!(f, "{:.2}{}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hint::black_box;
+
+    #[test]
+    fn counts_allocations_and_peak() {
+        let (kept, outer) = measure(|| {
+            let (_, inner) = measure(|| drop(black_box(Vec::<u8>::with_capacity(4000))));
+            assert_eq!(
+                inner,
+                Usage {
+                    allocations: 1,
+                    allocated: 4000,
+                    peak: 4000,
+                }
+            );
+            black_box(vec![0u8; 100])
+        });
+        assert_eq!(kept.len(), 100);
+        assert_eq!(
+            outer,
+            Usage {
+                allocations: 2,
+                allocated: 4100,
+                peak: 4000,
+            }
+        );
+    }
+
+    #[test]
+    fn byte_units() {
+        assert_eq!(Bytes(0).to_string(), "0B");
+        assert_eq!(Bytes(1023).to_string(), "1023B");
+        assert_eq!(Bytes(1536).to_string(), "1.50KiB");
+        assert_eq!(Bytes(5 << 30).to_string(), "5.00GiB");
+    }
+}
@@ -0,0 +1,269 @@
+use crate::cli::{Format, Part};
+use crate::error::SolveError;
+use crate::explain::Step;
+use crate::memory::{self, Usage};
+use crate::solution::Entry;
+use crate::timing::time;
+use std::time::Duration;
+
+/// The outcome of running one part of one day.
+pub struct Record {
+    pub day: u8,
+    pub part: Part,
+    pub answer: Result<String, SolveError>,
+    pub elapsed: Duration,
+    pub memory: Usage,
+    /// How the answer was reached, when the part was run with `--explain`.
+    pub trace: Option<Vec<Step>>,
+}
+
+impl Record {
+    pub fn solve(entry: &Entry, part: Part, input: &str) -> Self {
+        let ((answer, elapsed), memory) = memory::measure(|| time(|| entry.solve(part, input)));
+        Record {
+            day: entry.number,
+            part,
+            answer,
+            elapsed,
+            memory,
+            trace: None,
+        }
+    }
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut escaped = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Quotes a field when it contains a delimiter, quote or line break, as in RFC 4180.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+/// Everything printed before the first record.
+pub fn begin(format: Format) -> &'static str {
+    match format {
+        Format::Text => "",
+        Format::Json => "[\n",
+        Format::Csv => "day,part,answer,elapsed_ns,allocations,allocated_bytes,peak_bytes,error\n",
+    }
+}
+
+/// Renders one record, `first` being whether it is the first one printed, so JSON output
+/// knows where to put the separating commas.
+pub fn format_record(format: Format, record: &Record, first: bool) -> String {
+    let elapsed = record.elapsed.as_nanos();
+    let Usage {
+        allocations,
+        allocated,
+        peak,
+    } = record.memory;
+    match format {
+        Format::Text => {
+            let mut text = match &record.answer {
+                // Multi-line answers already start on their own line.
+                Ok(answer) if answer.starts_with('\n') => {
+                    format!("day {} part {}:{}\n", record.day, record.part, answer)
+                }
+                Ok(answer) => format!("day {} part {}: {}\n", record.day, record.part, answer),
+                Err(err) => format!("day {} part {}: error: {}\n", record.day, record.part, err),
+            };
+            for step in record.trace.iter().flatten() {
+                text.push_str(&format!("    {}\n", step));
+            }
+            text
+        }
+        Format::Json => {
+            let (answer, error) = match &record.answer {
+                Ok(answer) => (json_string(answer), "null".to_owned()),
+                Err(err) => ("null".to_owned(), json_string(&err.to_string())),
+            };
+            let trace = match &record.trace {
+                Some(steps) => {
+                    let steps: Vec<String> = steps
+                        .iter()
+                        .map(|step| {
+                            format!(
+                                "{{\"kind\": {}, \"detail\": {}}}",
+                                json_string(step.kind),
+                                json_string(&step.detail)
+                            )
+                        })
+                        .collect();
+                    format!(", \"trace\": [{}]", steps.join(", "))
+                }
+                None => String::new(),
+            };
+            format!(
+                "{}  {{\"day\": {}, \"part\": {}, \"answer\": {}, \"elapsed_ns\": {}, \"allocations\": {}, \"allocated_bytes\": {}, \"peak_bytes\": {}, \"error\": {}{}}}",
+                if first { "" } else { ",\n" },
+                record.day,
+                record.part,
+                answer,
+                elapsed,
+                allocations,
+                allocated,
+                peak,
+                error,
+                trace
+            )
+        }
+        Format::Csv => {
+            let (answer, error) = match &record.answer {
+                Ok(answer) => (csv_field(answer), String::new()),
+                Err(err) => (String::new(), csv_field(&err.to_string())),
+            };
+            format!(
+                "{},{},{},{},{},{},{},{}\n",
+                record.day, record.part, answer, elapsed, allocations, allocated, peak, error
+            )
+        }
+    }
+}
+
+/// Everything printed after the last record.
+pub fn end(format: Format, empty: bool) -> &'static str {
+    match format {
+        Format::Json if empty => "]\n",
+        Format::Json => "\n]\n",
+        Format::Text | Format::Csv => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record {
+                day: 5,
+                part: Part::One,
+                answer: Ok("CMZ".to_owned()),
+                elapsed: Duration::from_micros(12),
+                memory: Usage {
+                    allocations: 4,
+                    allocated: 96,
+                    peak: 64,
+                },
+                trace: None,
+            },
+            Record {
+                day: 10,
+                part: Part::Two,
+                answer: Ok("\n#.\"\n.#".to_owned()),
+                elapsed: Duration::from_nanos(800),
+                memory: Usage::default(),
+                trace: None,
+            },
+            Record {
+                day: 21,
+                part: Part::One,
+                answer: Err(SolveError::MissingMonkey("root".to_owned())),
+                elapsed: Duration::from_nanos(50),
+                memory: Usage::default(),
+                trace: None,
+            },
+        ]
+    }
+
+    fn render(format: Format) -> String {
+        let mut output = begin(format).to_owned();
+        for (idx, record) in records().iter().enumerate() {
+            output.push_str(&format_record(format, record, idx == 0));
+        }
+        output.push_str(end(format, false));
+        output
+    }
+
+    #[test]
+    fn json_output() {
+        assert_eq!(
+            render(Format::Json),
+            r#"[
+  {"day": 5, "part": 1, "answer": "CMZ", "elapsed_ns": 12000, "allocations": 4, "allocated_bytes": 96, "peak_bytes": 64, "error": null},
+  {"day": 10, "part": 2, "answer": "\n#.\"\n.#", "elapsed_ns": 800, "allocations": 0, "allocated_bytes": 0, "peak_bytes": 0, "error": null},
+  {"day": 21, "part": 1, "answer": null, "elapsed_ns": 50, "allocations": 0, "allocated_bytes": 0, "peak_bytes": 0, "error": "no monkey named `root`"}
+]
+"#
+        );
+        assert_eq!(json_string("a\u{1}\tb"), r#""a\u0001\tb""#);
+        assert_eq!(
+            format!("{}{}", begin(Format::Json), end(Format::Json, true)),
+            "[\n]\n"
+        );
+    }
+
+    #[test]
+    fn csv_output() {
+        assert_eq!(
+            render(Format::Csv),
+            "day,part,answer,elapsed_ns,allocations,allocated_bytes,peak_bytes,error
+5,1,CMZ,12000,4,96,64,
+10,2,\"
+#.\"\"
+.#\",800,0,0,0,
+21,1,,50,0,0,0,no monkey named `root`
+"
+        );
+    }
+
+    #[test]
+    fn text_output() {
+        assert_eq!(
+            render(Format::Text),
+            "day 5 part 1: CMZ
+day 10 part 2:
+#.\"
+.#
+day 21 part 1: error: no monkey named `root`
+"
+        );
+    }
+
+    #[test]
+    fn explained_output() {
+        let record = Record {
+            day: 13,
+            part: Part::One,
+            answer: Ok("4".to_owned()),
+            elapsed: Duration::from_nanos(10),
+            memory: Usage::default(),
+            trace: Some(vec![
+                Step {
+                    kind: "in order",
+                    detail: "pair 1".to_owned(),
+                },
+                Step {
+                    kind: "in order",
+                    detail: "pair \"3\"".to_owned(),
+                },
+            ]),
+        };
+        assert_eq!(
+            format_record(Format::Text, &record, true),
+            "day 13 part 1: 4\n    in order: pair 1\n    in order: pair \"3\"\n"
+        );
+        assert_eq!(
+            format_record(Format::Json, &record, true),
+            r#"  {"day": 13, "part": 1, "answer": "4", "elapsed_ns": 10, "allocations": 0, "allocated_bytes": 0, "peak_bytes": 0, "error": null, "trace": [{"kind": "in order", "detail": "pair 1"}, {"kind": "in order", "detail": "pair \"3\""}]}"#
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+/// Runs `work` on every item using up to `jobs` threads. The results are handed to `emit` on
+/// the calling thread in the same order as `items`, each one as soon as it and everything
+/// before it has finished, so output doesn't depend on which thread is fastest.
+pub fn run_ordered<T, R, W, E>(items: &[T], jobs: usize, work: W, mut emit: E)
+where
+    T: Sync,
+    R: Send,
+    W: Fn(&T) -> R + Sync,
+    E: FnMut(R),
+{
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            let sender = sender.clone();
+            let (next, work) = (&next, &work);
+            scope.spawn(move || loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(idx) else {
+                    break;
+                };
+                if sender.send((idx, work(item))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+        let mut pending = BTreeMap::new();
+        let mut emitted = 0;
+        for (idx, result) in receiver {
+            pending.insert(idx, result);
+            while let Some(result) = pending.remove(&emitted) {
+                emit(result);
+                emitted += 1;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn results_keep_item_order() {
+        let items: Vec<u64> = (0..20).collect();
+        for jobs in [1, 3, 8, 100] {
+            let mut results = Vec::new();
+            run_ordered(
+                &items,
+                jobs,
+                |n| {
+                    // Make early items the slowest, so they finish last.
+                    thread::sleep(Duration::from_millis(20 - n));
+                    n * n
+                },
+                |n| results.push(n),
+            );
+            assert_eq!(results, items.iter().map(|n| n * n).collect::<Vec<u64>>());
+        }
+        let mut called = false;
+        run_ordered(&[] as &[u8], 4, |_| (), |_| called = true);
+        assert!(!called);
+    }
+}
@@ -0,0 +1,148 @@
+//! Progress and counters reported from inside solvers. The solver signatures stay plain
+//! `fn(&str) -> Result<..>`, so reports go to whoever is observing the current thread and
+//! are dropped when nobody is.
+
+use crate::scoped;
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt,
+    io::{self, IsTerminal, Write},
+};
+
+/// Named counters, such as the number of states a search explored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Counters(BTreeMap<&'static str, u64>);
+
+impl Counters {
+    /// The counter's value, which is 0 if it was never reported.
+    #[cfg(test)]
+    pub fn get(&self, name: &str) -> u64 {
+        self.0.get(name).copied().unwrap_or(0)
+    }
+}
+
+impl fmt::Display for Counters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counters: Vec<String> = self
+            .0
+            .iter()
+            .map(|(name, value)| format!("{} {}", name, value))
+            .collect();
+        write!(f, "{}", counters.join(", "))
+    }
+}
+
+type OnStage = Box<dyn FnMut(&str, &Counters)>;
+
+struct Observer {
+    counters: Counters,
+    on_stage: OnStage,
+}
+
+thread_local! {
+    // Only ever borrowed for the length of a single report.
+    static OBSERVER: RefCell<Option<Observer>> = const { RefCell::new(None) };
+}
+
+/// Adds `n` to the named counter.
+pub fn count(name: &'static str, n: u64) {
+    OBSERVER.with(|observer| {
+        if let Some(observer) = observer.borrow_mut().as_mut() {
+            *observer.counters.0.entry(name).or_insert(0) += n;
+        }
+    });
+}
+
+/// Reports that the solver moved on to a new stage of its work, such as the next blueprint.
+/// The message is only formatted when someone is observing.
+pub fn stage(message: fmt::Arguments) {
+    OBSERVER.with(|observer| {
+        if let Some(observer) = observer.borrow_mut().as_mut() {
+            (observer.on_stage)(&message.to_string(), &observer.counters);
+        }
+    });
+}
+
+/// Runs `f`, calling `on_stage` with each stage it reports and the counters so far, and
+/// returns its result along with the final counters. Any observer already watching this
+/// thread is suspended until `f` returns.
+pub fn observe<R>(
+    on_stage: impl FnMut(&str, &Counters) + 'static,
+    f: impl FnOnce() -> R,
+) -> (R, Counters) {
+    let observer = Observer {
+        counters: Counters::default(),
+        on_stage: Box::new(on_stage),
+    };
+    let (result, observer) = scoped::replace(&OBSERVER, Some(observer), f);
+    (
+        result,
+        observer
+            .map(|observer| observer.counters)
+            .unwrap_or_default(),
+    )
+}
+
+/// Runs `f`, only keeping the counters it reports.
+#[cfg(test)]
+pub fn counted<R>(f: impl FnOnce() -> R) -> (R, Counters) {
+    observe(|_, _| {}, f)
+}
+
+/// Runs `f`, showing its stages as a single line on stderr which is cleared once it's done.
+/// Nothing is shown unless `show` is set and stderr is a terminal.
+pub fn show<R>(show: bool, label: &str, f: impl FnOnce() -> R) -> R {
+    if !show || !io::stderr().is_terminal() {
+        return f();
+    }
+    let label = label.to_owned();
+    let (result, _) = observe(
+        move |message, counters| {
+            let mut line = format!("{}: {}", label, message);
+            if counters.0.values().any(|value| *value > 0) {
+                line.push_str(&format!(" ({})", counters));
+            }
+            let _ = write!(io::stderr(), "\r\x1b[2K{}", line);
+        },
+        f,
+    );
+    let _ = write!(io::stderr(), "\r\x1b[2K");
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn reports_reach_the_innermost_observer() {
+        count("ignored", 1);
+        let stages = Rc::new(RefCell::new(Vec::new()));
+        let seen = stages.clone();
+        let (inner, outer) = observe(
+            move |message, counters| {
+                seen.borrow_mut()
+                    .push(format!("{} [{}]", message, counters))
+            },
+            || {
+                count("states", 2);
+                stage(format_args!("blueprint {}", 1));
+                let (_, inner) = counted(|| {
+                    count("states", 5);
+                    stage(format_args!("not seen"));
+                });
+                count("states", 3);
+                count("memo hits", 1);
+                inner
+            },
+        );
+        assert_eq!(*stages.borrow(), vec!["blueprint 1 [states 2]"]);
+        assert_eq!(inner.get("states"), 5);
+        assert_eq!(outer.get("states"), 5);
+        assert_eq!(outer.get("memo hits"), 1);
+        assert_eq!(outer.get("rounds"), 0);
+        assert_eq!(outer.to_string(), "memo hits 1, states 5");
+    }
+}
@@ -0,0 +1,287 @@
+use crate::answers::{escape, Answers, AnswersError, Verdict};
+use crate::cache;
+use crate::cli::Part;
+use crate::error::{panic_message, SolveError};
+use crate::input::normalize;
+use crate::solution::Entry;
+use crate::timing::format_rows;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt, fs, io,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+};
+
+/// Each set's known answers, in the same format as `answers.txt`.
+pub const SET_ANSWERS: &str = "answers.txt";
+
+#[derive(Debug)]
+pub enum RegressError {
+    Io(PathBuf, io::Error),
+    Answers(PathBuf, AnswersError),
+    NoSets(PathBuf),
+}
+
+impl fmt::Display for RegressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegressError::Io(path, err) => write!(f, "failed to read {}: {}", path.display(), err),
+            RegressError::Answers(_, err @ AnswersError::Io(..)) => write!(f, "{}", err),
+            RegressError::Answers(path, err) => write!(f, "{}: {}", path.display(), err),
+            RegressError::NoSets(dir) => write!(
+                f,
+                "{} has no input sets, expected a directory per input with {}",
+                dir.display(),
+                SET_ANSWERS
+            ),
+        }
+    }
+}
+
+impl Error for RegressError {}
+
+/// One person's puzzle inputs, kept in a directory of their own alongside their answers.
+/// Inputs are named for their day as in the input cache (such as `day07.txt`), and a day
+/// without an input is skipped.
+#[derive(Debug)]
+pub struct InputSet {
+    pub name: String,
+    pub answers: Answers,
+    pub inputs: BTreeMap<u8, PathBuf>,
+}
+
+fn load_set(dir: &Path) -> Result<InputSet, RegressError> {
+    let answers_path = dir.join(SET_ANSWERS);
+    let answers =
+        Answers::load(&answers_path).map_err(|err| RegressError::Answers(answers_path, err))?;
+    let inputs = cache::discover(dir)
+        .map_err(|err| RegressError::Io(dir.to_owned(), err))?
+        .into_iter()
+        .filter_map(|input| Some((input.day?, input.path)))
+        .collect();
+    Ok(InputSet {
+        name: dir.file_name().map_or_else(
+            || dir.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        ),
+        answers,
+        inputs,
+    })
+}
+
+/// Every input set in `dir`, sorted by name. A file directly in `dir` isn't part of any set
+/// and is ignored.
+pub fn discover(dir: &Path) -> Result<Vec<InputSet>, RegressError> {
+    let read_error = |err| RegressError::Io(dir.to_owned(), err);
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(dir).map_err(read_error)? {
+        let path = entry.map_err(read_error)?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    if dirs.is_empty() {
+        return Err(RegressError::NoSets(dir.to_owned()));
+    }
+    dirs.iter().map(|dir| load_set(dir)).collect()
+}
+
+/// How a solver did on one set's input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    /// The set has no answer to compare with.
+    Unchecked(String),
+    Wrong {
+        expected: String,
+        answer: String,
+    },
+    Failed(SolveError),
+    /// The solver panicked, which is how a broken assumption about the input often shows.
+    Panicked(String),
+    Unreadable(String),
+}
+
+impl Outcome {
+    pub fn is_failure(&self) -> bool {
+        !matches!(self, Outcome::Correct | Outcome::Unchecked(_))
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Correct => write!(f, "ok"),
+            Outcome::Unchecked(answer) => write!(f, "no known answer, got {}", escape(answer)),
+            Outcome::Wrong { expected, answer } => write!(
+                f,
+                "WRONG, expected {} but got {}",
+                escape(expected),
+                escape(answer)
+            ),
+            Outcome::Failed(err) => write!(f, "error: {}", err),
+            Outcome::Panicked(message) => write!(f, "panicked: {}", message),
+            Outcome::Unreadable(err) => write!(f, "unreadable input: {}", err),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub set: String,
+    pub day: u8,
+    pub part: Part,
+    pub outcome: Outcome,
+}
+
+fn judge(set: &InputSet, entry: &Entry, part: Part, input: &str) -> Outcome {
+    match panic::catch_unwind(AssertUnwindSafe(|| entry.solve(part, input))) {
+        Err(payload) => Outcome::Panicked(panic_message(payload)),
+        Ok(Err(err)) => Outcome::Failed(err),
+        Ok(Ok(answer)) => match set.answers.check(entry.number, part, &answer) {
+            Verdict::Correct => Outcome::Correct,
+            Verdict::Unknown => Outcome::Unchecked(answer),
+            Verdict::Incorrect { expected } => Outcome::Wrong { expected, answer },
+        },
+    }
+}
+
+/// Runs the selected days and parts over every input in `set`.
+pub fn run_set(set: &InputSet, entries: &[&Entry], parts: &[Part]) -> Vec<Check> {
+    let mut checks = Vec::new();
+    for entry in entries {
+        let Some(path) = set.inputs.get(&entry.number) else {
+            continue;
+        };
+        let input = fs::read_to_string(path).map(normalize);
+        for part in parts {
+            let outcome = match &input {
+                Ok(input) => judge(set, entry, *part, input),
+                Err(err) => Outcome::Unreadable(err.to_string()),
+            };
+            checks.push(Check {
+                set: set.name.clone(),
+                day: entry.number,
+                part: *part,
+                outcome,
+            });
+        }
+    }
+    checks
+}
+
+/// A table with a row per day and part and a column per set, followed by the details of
+/// every failure grouped by day and part.
+pub fn format_report(sets: &[InputSet], checks: &[Check]) -> String {
+    let mut by_part: BTreeMap<(u8, Part), BTreeMap<&str, &Outcome>> = BTreeMap::new();
+    for check in checks {
+        by_part
+            .entry((check.day, check.part))
+            .or_default()
+            .insert(&check.set, &check.outcome);
+    }
+    let mut rows = vec![["day".to_owned(), "part".to_owned()]
+        .into_iter()
+        .chain(sets.iter().map(|set| set.name.clone()))
+        .collect::<Vec<String>>()];
+    for ((day, part), outcomes) in by_part.iter() {
+        let cells = sets.iter().map(|set| {
+            match outcomes.get(set.name.as_str()) {
+                None => "-",
+                Some(Outcome::Correct) => "ok",
+                Some(Outcome::Unchecked(_)) => "?",
+                Some(_) => "FAIL",
+            }
+            .to_owned()
+        });
+        rows.push(
+            [day.to_string(), part.to_string()]
+                .into_iter()
+                .chain(cells)
+                .collect(),
+        );
+    }
+    let mut report = format_rows(rows);
+    for ((day, part), outcomes) in by_part.iter() {
+        let failures: Vec<String> = outcomes
+            .iter()
+            .filter(|(_, outcome)| outcome.is_failure())
+            .map(|(set, outcome)| format!("  {}: {}", set, outcome))
+            .collect();
+        if !failures.is_empty() {
+            report.push_str(&format!(
+                "\nday {} part {} fails on {} input(s):\n{}\n",
+                day,
+                part,
+                failures.len(),
+                failures.join("\n")
+            ));
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution;
+
+    #[test]
+    fn reports_failing_inputs() {
+        let root = std::env::temp_dir().join(format!("aoc_regress_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (name, signal, answers) in [
+            (
+                "alice",
+                "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+                "6/1 = 7\n6/2 = 19\n",
+            ),
+            ("bob", "bvwbjplbgvbhsrlpgdmjqwftvncz", "6/1 = 5\n6/2 = 24\n"),
+            ("carol", "aaaa", "6/1 = 4\n"),
+        ] {
+            fs::create_dir_all(root.join(name)).unwrap();
+            fs::write(root.join(name).join("day06.txt"), format!("{}\n", signal)).unwrap();
+            fs::write(root.join(name).join(SET_ANSWERS), answers).unwrap();
+        }
+        fs::write(root.join("README"), "not a set").unwrap();
+
+        let sets = discover(&root).unwrap();
+        let entries = [solution::find(6).unwrap(), solution::find(7).unwrap()];
+        let checks: Vec<Check> = sets
+            .iter()
+            .flat_map(|set| run_set(set, &entries, &[Part::One, Part::Two]))
+            .collect();
+        let outcomes: Vec<(&str, Part, bool)> = checks
+            .iter()
+            .map(|check| (check.set.as_str(), check.part, check.outcome.is_failure()))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("alice", Part::One, false),
+                ("alice", Part::Two, false),
+                ("bob", Part::One, false),
+                ("bob", Part::Two, true),
+                ("carol", Part::One, true),
+                ("carol", Part::Two, true),
+            ]
+        );
+        assert_eq!(
+            format_report(&sets, &checks),
+            "day  part  alice  bob   carol
+6    1     ok     ok    FAIL
+6    2     ok     FAIL  FAIL
+
+day 6 part 1 fails on 1 input(s):
+  carol: error: no solution: no 4 consecutive characters are all different
+
+day 6 part 2 fails on 2 input(s):
+  bob: WRONG, expected 24 but got 23
+  carol: error: no solution: no 14 consecutive characters are all different
+"
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
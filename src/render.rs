@@ -0,0 +1,368 @@
+//! Frames drawn from the grid simulations, as ASCII text or PPM/PGM images. Solvers call
+//! `frame` with each step of their simulation and, as with `progress`, the frame is only
+//! drawn when someone is recording the current thread and wants that step.
+
+use crate::cli::CliError;
+use crate::scoped;
+use advent_of_code::grid::{Grid, GridPoint, Lattice};
+use std::{
+    cell::RefCell,
+    fmt, fs, io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// How a single cell is drawn: a character in ASCII frames and a colour in images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    pub glyph: char,
+    pub rgb: [u8; 3],
+}
+
+impl Style {
+    pub const fn new(glyph: char, rgb: [u8; 3]) -> Self {
+        Style { glyph, rgb }
+    }
+
+    /// Perceived brightness, for greyscale images.
+    fn grey(&self) -> u8 {
+        let [r, g, b] = self.rgb.map(u32::from);
+        ((r * 299 + g * 587 + b * 114) / 1000) as u8
+    }
+}
+
+pub const EMPTY: Style = Style::new('.', [16, 16, 24]);
+
+/// The rows `min_row..max_row` and columns `min_col..max_col` a frame covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min_row: isize,
+    pub max_row: isize,
+    pub min_col: isize,
+    pub max_col: isize,
+}
+
+impl Bounds {
+    pub fn new(min_row: isize, max_row: isize, min_col: isize, max_col: isize) -> Self {
+        Bounds {
+            min_row,
+            max_row,
+            min_col,
+            max_col,
+        }
+    }
+
+    /// The smallest bounds containing every point, which are empty when there are none.
+    pub fn around<'a>(points: impl IntoIterator<Item = &'a GridPoint<isize>>) -> Self {
+        points
+            .into_iter()
+            .fold(None, |bounds: Option<Bounds>, point| {
+                let (row, col) = (*point.row(), *point.col());
+                Some(match bounds {
+                    None => Bounds::new(row, row + 1, col, col + 1),
+                    Some(b) => Bounds::new(
+                        b.min_row.min(row),
+                        b.max_row.max(row + 1),
+                        b.min_col.min(col),
+                        b.max_col.max(col + 1),
+                    ),
+                })
+            })
+            .unwrap_or(Bounds::new(0, 0, 0, 0))
+    }
+
+    fn rows(&self) -> usize {
+        (self.max_row - self.min_row).max(0) as usize
+    }
+
+    fn cols(&self) -> usize {
+        (self.max_col - self.min_col).max(0) as usize
+    }
+}
+
+/// A rectangle of styled cells, addressed by the points of the simulation it was drawn from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    bounds: Bounds,
+    cells: Vec<Style>,
+}
+
+impl Frame {
+    /// Draws every point in `bounds` with `style`.
+    pub fn draw(bounds: Bounds, style: impl Fn(GridPoint<isize>) -> Style) -> Self {
+        let cells = (bounds.min_row..bounds.max_row)
+            .flat_map(|row| (bounds.min_col..bounds.max_col).map(move |col| (row, col)))
+            .map(|(row, col)| style(GridPoint::new(row, col)))
+            .collect();
+        Frame { bounds, cells }
+    }
+
+    pub fn of_grid<T>(grid: &Grid<T>, style: impl Fn(&T) -> Style) -> Self {
+        let bounds = Bounds::new(0, grid.rows() as isize, 0, grid.cols() as isize);
+        Self::draw(bounds, |point| {
+            style(&grid[GridPoint::new(*point.row() as usize, *point.col() as usize)])
+        })
+    }
+
+    /// A lattice has no edges of its own, so the caller picks the part of it to draw.
+    pub fn of_lattice<T>(
+        lattice: &Lattice<T>,
+        bounds: Bounds,
+        style: impl Fn(Option<&T>) -> Style,
+    ) -> Self {
+        Self::draw(bounds, |point| style(lattice.get(point)))
+    }
+
+    /// Draws the points as `on` against `EMPTY`, just covering all of them.
+    pub fn of_points<'a>(
+        points: impl IntoIterator<Item = &'a GridPoint<isize>> + Clone,
+        on: Style,
+    ) -> Self {
+        let mut frame = Self::draw(Bounds::around(points.clone()), |_| EMPTY);
+        for point in points {
+            frame.set(*point, on);
+        }
+        frame
+    }
+
+    /// Restyles a single cell, such as to mark where the walker is. Points outside the frame
+    /// are ignored.
+    pub fn set(&mut self, point: GridPoint<isize>, style: Style) {
+        let b = self.bounds;
+        let (row, col) = (*point.row(), *point.col());
+        if (b.min_row..b.max_row).contains(&row) && (b.min_col..b.max_col).contains(&col) {
+            let idx = (row - b.min_row) as usize * b.cols() + (col - b.min_col) as usize;
+            self.cells[idx] = style;
+        }
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &[Style]> {
+        self.cells.chunks(self.bounds.cols().max(1))
+    }
+
+    pub fn to_ascii(&self) -> String {
+        let mut text = String::new();
+        for row in self.rows() {
+            text.extend(row.iter().map(|style| style.glyph));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// A binary PPM (or PGM when `grey`) image with each cell drawn as a `scale` pixel square.
+    pub fn to_image(&self, grey: bool, scale: usize) -> Vec<u8> {
+        let (width, height) = (self.bounds.cols() * scale, self.bounds.rows() * scale);
+        let magic = if grey { "P5" } else { "P6" };
+        let mut image = format!("{}\n{} {}\n255\n", magic, width, height).into_bytes();
+        for row in self.rows() {
+            let mut line = Vec::with_capacity(width * 3);
+            for style in row {
+                for _ in 0..scale {
+                    if grey {
+                        line.push(style.grey());
+                    } else {
+                        line.extend(style.rgb);
+                    }
+                }
+            }
+            for _ in 0..scale {
+                image.extend(&line);
+            }
+        }
+        image
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ascii,
+    Ppm,
+    Pgm,
+}
+
+impl ImageFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ascii => "txt",
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Pgm => "pgm",
+        }
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ascii" => Ok(ImageFormat::Ascii),
+            "ppm" => Ok(ImageFormat::Ppm),
+            "pgm" => Ok(ImageFormat::Pgm),
+            _ => Err(CliError::InvalidImageFormat(s.to_owned())),
+        }
+    }
+}
+
+/// Pixels along each side of a cell in images.
+const SCALE: usize = 4;
+
+/// Where and which frames to write. Each frame goes in its own file, named from `prefix`
+/// and the step so that the files sort in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recording {
+    pub dir: PathBuf,
+    pub prefix: String,
+    pub format: ImageFormat,
+    pub steps: RangeInclusive<usize>,
+}
+
+impl Recording {
+    fn path(&self, step: usize) -> PathBuf {
+        self.dir.join(format!(
+            "{}_{:06}.{}",
+            self.prefix,
+            step,
+            self.format.extension()
+        ))
+    }
+
+    fn write(&self, step: usize, frame: &Frame) -> io::Result<()> {
+        let contents = match self.format {
+            ImageFormat::Ascii => frame.to_ascii().into_bytes(),
+            ImageFormat::Ppm => frame.to_image(false, SCALE),
+            ImageFormat::Pgm => frame.to_image(true, SCALE),
+        };
+        fs::write(self.path(step), contents)
+    }
+}
+
+struct Recorder {
+    recording: Recording,
+    written: usize,
+    /// The first failed write, after which nothing more is written.
+    error: Option<io::Error>,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+/// Hands the frame for `step` to the recorder, only drawing it when the step is wanted.
+pub fn frame(step: usize, draw: impl FnOnce() -> Frame) {
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        let Some(recorder) = recorder.as_mut() else {
+            return;
+        };
+        if recorder.error.is_some() || !recorder.recording.steps.contains(&step) {
+            return;
+        }
+        match recorder.recording.write(step, &draw()) {
+            Ok(()) => recorder.written += 1,
+            Err(err) => recorder.error = Some(err),
+        }
+    });
+}
+
+/// Runs `f`, writing the frames it draws, and returns its result along with the number of
+/// frames written. Any recording already in progress on this thread is suspended meanwhile.
+pub fn record<R>(recording: Recording, f: impl FnOnce() -> R) -> (R, io::Result<usize>) {
+    if let Err(err) = fs::create_dir_all(&recording.dir) {
+        return (f(), Err(err));
+    }
+    let recorder = Recorder {
+        recording,
+        written: 0,
+        error: None,
+    };
+    let (result, recorder) = scoped::replace(&RECORDER, Some(recorder), f);
+    let recorder = recorder.unwrap();
+    let written = match recorder.error {
+        Some(err) => Err(err),
+        None => Ok(recorder.written),
+    };
+    (result, written)
+}
+
+/// Where a recording for one day and part is written.
+pub fn recording_for(
+    dir: &Path,
+    day: u8,
+    part: impl fmt::Display,
+    format: ImageFormat,
+    steps: RangeInclusive<usize>,
+) -> Recording {
+    Recording {
+        dir: dir.to_owned(),
+        prefix: format!("day{:02}_part{}", day, part),
+        format,
+        steps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROCK: Style = Style::new('#', [255, 255, 255]);
+
+    #[test]
+    fn draws_points_and_lattices() {
+        let points = [GridPoint::new(-1, 2), GridPoint::new(1, 0)];
+        let mut frame = Frame::of_points(points.iter(), ROCK);
+        assert_eq!(frame.to_ascii(), "..#\n...\n#..\n");
+        frame.set(GridPoint::new(0, 1), Style::new('@', [255, 0, 0]));
+        frame.set(GridPoint::new(5, 5), ROCK);
+        assert_eq!(frame.to_ascii(), "..#\n.@.\n#..\n");
+
+        let mut lattice: Lattice<char> = Lattice::empty();
+        lattice.set(GridPoint::new(0, 0), '#');
+        let frame = Frame::of_lattice(&lattice, Bounds::new(-1, 1, 0, 2), |cell| match cell {
+            Some(_) => ROCK,
+            None => EMPTY,
+        });
+        assert_eq!(frame.to_ascii(), "..\n#.\n");
+    }
+
+    #[test]
+    fn images() {
+        let frame = Frame::draw(Bounds::new(0, 1, 0, 2), |point| match *point.col() {
+            0 => ROCK,
+            _ => Style::new('x', [0, 0, 0]),
+        });
+        assert_eq!(
+            frame.to_image(false, 1),
+            b"P6\n2 1\n255\n\xff\xff\xff\x00\x00\x00".to_vec()
+        );
+        assert_eq!(
+            frame.to_image(true, 2),
+            b"P5\n4 2\n255\n\xff\xff\x00\x00\xff\xff\x00\x00".to_vec()
+        );
+    }
+
+    #[test]
+    fn records_the_chosen_steps() {
+        let dir = std::env::temp_dir().join(format!("aoc_render_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let recording = recording_for(&dir, 14, 1, ImageFormat::Ascii, 2..=3);
+        let mut drawn = Vec::new();
+        let (_, written) = record(recording, || {
+            for step in 0..5 {
+                frame(step, || {
+                    drawn.push(step);
+                    Frame::of_points([GridPoint::new(0, step as isize)].iter(), ROCK)
+                });
+            }
+        });
+        assert_eq!(written.unwrap(), 2);
+        assert_eq!(drawn, vec![2, 3]);
+        assert_eq!(
+            fs::read_to_string(dir.join("day14_part1_000003.txt")).unwrap(),
+            "#\n"
+        );
+        // Nothing is drawn once the recording has finished.
+        frame(2, || panic!("not recording"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,351 @@
+//! An interactive session for trying a day's solvers on ad-hoc input: pick a day, load or
+//! paste its input, run either part with timing, then change the day's parameters or the
+//! time limit and run it again.
+
+use crate::cli::{CliError, Part, PARTS};
+use crate::deadline;
+use crate::error::{format_duration, panic_message};
+use crate::input::{self, InputSource};
+use crate::solution::{self, Entry};
+use crate::timing::time;
+use crate::tuning::{self, Settings};
+use std::{
+    error::Error,
+    fmt,
+    io::{self, BufRead, Write},
+    panic::{self, AssertUnwindSafe},
+    time::Duration,
+};
+
+pub const HELP: &str = "commands:
+    day <1-25>          work on a day, forgetting the previous day's input
+    load <path>         read the input from a file
+    load example        use the day's worked example
+    load cached         use the cached puzzle input
+    paste               type or paste the input, ending it with a line holding only .
+    run [1|2]           run both parts, or just one, and show how long each took
+    params              list the day's parameters and their values
+    set <name> <value>  change a parameter for the runs that follow
+    reset [<name>]      put one parameter, or all of the day's, back to the puzzle's
+    limit <secs|off>    stop each part after that many seconds, or let it run
+    help                show this again
+    quit                leave, as does the end of input
+";
+
+/// The line which ends a pasted input.
+pub const PASTE_END: &str = ".";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplError {
+    UnknownCommand(String),
+    Usage(&'static str),
+    NoDay,
+    NoInput,
+}
+
+impl fmt::Display for ReplError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplError::UnknownCommand(command) => {
+                write!(f, "unknown command `{}`, try `help`", command)
+            }
+            ReplError::Usage(usage) => write!(f, "usage: {}", usage),
+            ReplError::NoDay => write!(f, "pick a day first, such as `day 7`"),
+            ReplError::NoInput => write!(f, "no input yet, `load` or `paste` one"),
+        }
+    }
+}
+
+impl Error for ReplError {}
+
+/// What the session is working on. Parameters are kept for every day visited, so going back
+/// to a day picks up where it was left.
+#[derive(Default)]
+pub struct Session {
+    entry: Option<&'static Entry>,
+    input: Option<String>,
+    settings: Settings,
+    time_limit: Option<Duration>,
+}
+
+impl Session {
+    pub fn new(day: Option<u8>, time_limit: Option<Duration>) -> Result<Self, CliError> {
+        let entry = day
+            .map(|day| solution::find(day).ok_or(CliError::UnknownDay(day)))
+            .transpose()?;
+        Ok(Session {
+            entry,
+            time_limit,
+            ..Session::default()
+        })
+    }
+
+    fn prompt(&self) -> String {
+        match self.entry {
+            Some(entry) => format!("day {}> ", entry.number),
+            None => "> ".to_owned(),
+        }
+    }
+
+    fn entry(&self) -> Result<&'static Entry, ReplError> {
+        self.entry.ok_or(ReplError::NoDay)
+    }
+
+    /// Carries out one command, returning what to print. `lines` is where `paste` reads the
+    /// input from.
+    fn execute(
+        &mut self,
+        words: &[&str],
+        lines: &mut impl BufRead,
+    ) -> Result<String, Box<dyn Error>> {
+        match words {
+            [] => Ok(String::new()),
+            ["help"] => Ok(HELP.to_owned()),
+            ["day", day] => {
+                let number = day
+                    .parse()
+                    .map_err(|_| CliError::InvalidDay(day.to_string()))?;
+                let entry = solution::find(number).ok_or(CliError::UnknownDay(number))?;
+                self.entry = Some(entry);
+                self.input = None;
+                Ok(format!("working on day {}\n", number))
+            }
+            ["day", ..] => Err(ReplError::Usage("day <1-25>").into()),
+            ["load", what] => {
+                let entry = self.entry()?;
+                let input = match *what {
+                    "example" => entry.example.to_owned(),
+                    "cached" => input::load(&InputSource::Cache, entry)?,
+                    path => input::load(&InputSource::of_arg(path), entry)?,
+                };
+                Ok(self.loaded(input))
+            }
+            ["load", ..] => Err(ReplError::Usage("load <path|example|cached>").into()),
+            ["paste"] => {
+                self.entry()?;
+                let mut input = String::new();
+                let mut line = String::new();
+                while lines.read_line(&mut line)? > 0 && line.trim_end() != PASTE_END {
+                    input.push_str(&line);
+                    line.clear();
+                }
+                Ok(self.loaded(input::normalize(input)))
+            }
+            ["run"] => self.run(&PARTS),
+            ["run", part] | [part @ ("1" | "2")] => self.run(&[part.parse()?]),
+            ["run", ..] => Err(ReplError::Usage("run [1|2]").into()),
+            ["params"] => {
+                let entry = self.entry()?;
+                let params: String = tuning::for_day(entry.number)
+                    .map(|tunable| {
+                        let value = self.settings.get(&(entry.number, tunable.name));
+                        format!(
+                            "{} = {}{} ({})\n",
+                            tunable.name,
+                            value.unwrap_or(&tunable.default),
+                            if value.is_some() { "" } else { " by default" },
+                            tunable.about
+                        )
+                    })
+                    .collect();
+                match params.is_empty() {
+                    true => Ok(format!("day {} has no parameters\n", entry.number)),
+                    false => Ok(params),
+                }
+            }
+            ["set", name, value] => {
+                let entry = self.entry()?;
+                tuning::set(&mut self.settings, entry.number, name, value)?;
+                Ok(format!("{} = {}\n", name, value))
+            }
+            ["set", ..] => Err(ReplError::Usage("set <name> <value>").into()),
+            ["reset"] => {
+                let day = self.entry()?.number;
+                self.settings
+                    .retain(|(setting_day, _), _| *setting_day != day);
+                Ok(format!("day {} is back to the puzzle's parameters\n", day))
+            }
+            ["reset", name] => {
+                let tunable = tuning::find(self.entry()?.number, name)?;
+                self.settings.remove(&(tunable.day, tunable.name));
+                Ok(format!("{} = {}\n", tunable.name, tunable.default))
+            }
+            ["limit", "off"] => {
+                self.time_limit = None;
+                Ok("no time limit\n".to_owned())
+            }
+            ["limit", secs] => {
+                let invalid = || CliError::InvalidTimeLimit(secs.to_string());
+                self.time_limit = match secs.parse::<f64>() {
+                    Ok(secs) if secs > 0.0 => {
+                        Some(Duration::try_from_secs_f64(secs).map_err(|_| invalid())?)
+                    }
+                    _ => return Err(invalid().into()),
+                };
+                Ok(format!("each part now has {} seconds\n", secs))
+            }
+            ["limit", ..] => Err(ReplError::Usage("limit <secs|off>").into()),
+            [command, ..] => Err(ReplError::UnknownCommand(command.to_string()).into()),
+        }
+    }
+
+    fn loaded(&mut self, input: String) -> String {
+        let message = format!(
+            "loaded {} line(s), {} byte(s)\n",
+            input.lines().count(),
+            input.len()
+        );
+        self.input = Some(input);
+        message
+    }
+
+    /// Runs `parts` with the session's parameters and time limit. A solver which panics on
+    /// the input is reported like one which failed, rather than ending the session.
+    fn run(&self, parts: &[Part]) -> Result<String, Box<dyn Error>> {
+        let entry = self.entry()?;
+        let input = self.input.as_deref().ok_or(ReplError::NoInput)?;
+        let mut report = String::new();
+        for part in parts {
+            let (answer, elapsed) = tuning::with(&self.settings, || {
+                deadline::with_limit(self.time_limit, || {
+                    time(|| panic::catch_unwind(AssertUnwindSafe(|| entry.solve(*part, input))))
+                })
+            });
+            let answer = match answer {
+                // Multi-line answers already start on their own line.
+                Ok(Ok(answer)) if answer.starts_with('\n') => answer,
+                Ok(Ok(answer)) => format!(" {}", answer),
+                Ok(Err(err)) => format!(" error: {}", err),
+                Err(payload) => format!(" panicked: {}", panic_message(payload)),
+            };
+            report.push_str(&format!(
+                "day {} part {} in {}:{}\n",
+                entry.number,
+                part,
+                format_duration(elapsed),
+                answer
+            ));
+        }
+        Ok(report)
+    }
+}
+
+/// Reads commands from `lines` until `quit` or the end of the input, writing a prompt
+/// before each and the command's output or error after it.
+pub fn run(session: &mut Session, mut lines: impl BufRead, out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "type `help` for the commands")?;
+    loop {
+        write!(out, "{}", session.prompt())?;
+        out.flush()?;
+        let mut line = String::new();
+        if lines.read_line(&mut line)? == 0 {
+            return writeln!(out);
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if matches!(words[..], ["quit" | "exit"]) {
+            return Ok(());
+        }
+        match session.execute(&words, &mut lines) {
+            Ok(output) => write!(out, "{}", output)?,
+            Err(err) => writeln!(out, "error: {}", err)?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::stub;
+
+    fn session(commands: &str) -> Vec<String> {
+        let mut out = Vec::new();
+        run(&mut Session::default(), commands.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    /// The answer at the end of a `run` line, without the timing that comes before it.
+    fn answer(line: &str) -> &str {
+        line.split_once(": ").map_or(line, |(_, answer)| answer)
+    }
+
+    #[test]
+    fn runs_pasted_input_with_changed_parameters() {
+        let example = include_str!("../fixtures/day15/example.txt");
+        let commands = format!(
+            "day 15\npaste\n{}.\nset row 10\nset max 20\nparams\nrun\nreset\n1\nquit\nday 1\n",
+            example
+        );
+        let lines = session(&commands);
+        assert_eq!(lines[0], "type `help` for the commands");
+        assert_eq!(lines[1], "> working on day 15");
+        assert_eq!(
+            lines[2],
+            format!("day 15> loaded {} line(s), {} byte(s)", 14, example.len())
+        );
+        assert_eq!(lines[3], "day 15> row = 10");
+        assert_eq!(lines[4], "day 15> max = 20");
+        assert_eq!(
+            lines[5],
+            "day 15> row = 10 (row part 1 counts the positions a beacon can't be in)"
+        );
+        assert!(lines[7].starts_with("day 15> day 15 part 1 in "));
+        assert_eq!(answer(&lines[7]), "26");
+        assert_eq!(answer(&lines[8]), "56000011");
+        assert_eq!(
+            lines[9],
+            "day 15> day 15 is back to the puzzle's parameters"
+        );
+        assert!(lines[10].starts_with("day 15> day 15 part 1 in "));
+        assert_eq!(answer(&lines[10]), "0");
+        assert_eq!(lines.len(), 12);
+    }
+
+    #[test]
+    fn reports_mistakes_and_carries_on() {
+        let lines =
+            session("run\nday 26\nday 11\nrun 1\nset rounds soon\nload example\nrun 3\nfly\n");
+        let errors: Vec<&str> = lines
+            .iter()
+            .filter_map(|line| line.split_once("error: ").map(|(_, error)| error))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                ReplError::NoDay.to_string(),
+                CliError::UnknownDay(26).to_string(),
+                ReplError::NoInput.to_string(),
+                "`soon` is not a number".to_owned(),
+                CliError::InvalidPart("3".to_owned()).to_string(),
+                ReplError::UnknownCommand("fly".to_owned()).to_string(),
+            ]
+        );
+        assert_eq!(lines.last().map(String::as_str), Some("day 11> "));
+    }
+
+    #[test]
+    fn solver_failures_end_the_run_not_the_session() {
+        // A lone monkey has nobody to throw to.
+        let monkey = include_str!("../fixtures/day11/example.txt")
+            .split("\n\n")
+            .next()
+            .unwrap();
+        let lines = session(&format!("day 11\npaste\n{}\n.\nrun 1\nday 1\n", monkey));
+        assert!(lines[3].contains(" error: "), "{}", lines[3]);
+        assert_eq!(lines[4], "day 11> working on day 1");
+
+        let mut panicking = Session {
+            entry: Some(&stub::PANICKING),
+            input: Some(String::new()),
+            ..Session::default()
+        };
+        let report = panicking.execute(&["run"], &mut "".as_bytes()).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" panicked: solver bug"), "{}", lines[0]);
+        assert!(lines[1].starts_with("day 0 part 2 in "), "{}", lines[1]);
+    }
+}
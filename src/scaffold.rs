@@ -0,0 +1,282 @@
+use std::{
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+const TEMPLATE: &str = "day_template";
+const MAX_WIDTH: usize = 100;
+
+#[derive(Debug)]
+pub enum ScaffoldError {
+    DayOutOfRange(u8),
+    Exists(PathBuf),
+    Io(PathBuf, io::Error),
+    /// The file we register days in doesn't look the way we expect, so we leave it alone.
+    Unrecognized(PathBuf, &'static str),
+}
+
+impl fmt::Display for ScaffoldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScaffoldError::DayOutOfRange(day) => {
+                write!(f, "day {} is outside of the puzzle days 1 to 25", day)
+            }
+            ScaffoldError::Exists(path) => {
+                write!(
+                    f,
+                    "{} already exists, refusing to overwrite it",
+                    path.display()
+                )
+            }
+            ScaffoldError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            ScaffoldError::Unrecognized(path, what) => {
+                write!(f, "couldn't find {} in {}", what, path.display())
+            }
+        }
+    }
+}
+
+impl Error for ScaffoldError {}
+
+/// Adds `pub mod <module>;` among the other `pub mod` lines, keeping them sorted.
+fn register_module(lib: &str, module: &str) -> Option<String> {
+    let line = format!("pub mod {};", module);
+    let mut lines: Vec<&str> = lib.lines().collect();
+    let mods: Vec<usize> = (0..lines.len())
+        .filter(|idx| lines[*idx].starts_with("pub mod ") && lines[*idx].ends_with(';'))
+        .collect();
+    let position = mods
+        .iter()
+        .find(|idx| lines[**idx] > line.as_str())
+        .copied()
+        .unwrap_or(mods.last()? + 1);
+    lines.insert(position, &line);
+    Some(lines.join("\n") + "\n")
+}
+
+/// Lays out `use crate::{...};` the way rustfmt does: on one line if it fits, otherwise
+/// filling indented lines up to the maximum width.
+fn format_use_list(names: &[String]) -> String {
+    let single = format!("use crate::{{{}}};", names.join(", "));
+    if single.len() <= MAX_WIDTH {
+        return single;
+    }
+    let mut list = String::from("use crate::{\n");
+    let mut line = String::new();
+    for name in names {
+        if !line.is_empty() && 4 + line.len() + 1 + name.len() + 1 > MAX_WIDTH {
+            list.push_str(&format!("    {}\n", line));
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(name);
+        line.push(',');
+    }
+    list.push_str(&format!("    {}\n}};", line));
+    list
+}
+
+/// Adds the day's module to the `use crate::{...}` list and its line to the `solutions!`
+/// invocation, after the days before it.
+fn register_solution(solution: &str, day: u8) -> Option<String> {
+    let module = format!("day{}", day);
+    let start = solution.find("use crate::{")?;
+    let end = start + solution[start..].find("};")? + 2;
+    let mut names: Vec<String> = solution[start + "use crate::{".len()..end - 2]
+        .split(',')
+        .map(|name| name.trim().to_owned())
+        .filter(|name| !name.is_empty())
+        .collect();
+    names.push(module.clone());
+    names.sort();
+    let solution = format!(
+        "{}{}{}",
+        &solution[..start],
+        format_use_list(&names),
+        &solution[end..]
+    );
+
+    let entry = format!(
+        "    Day{}: {}, {}, Day::Day{:02}, u32, u32;\n",
+        day, module, day, day
+    );
+    let start = solution.find("\nsolutions!(\n")? + "\nsolutions!(\n".len();
+    let end = start + solution[start..].find("\n);")? + 1;
+    let mut position = start;
+    for line in solution[start..end].split_inclusive('\n') {
+        let number: u8 = line.split(',').nth(1)?.trim().parse().ok()?;
+        if number > day {
+            break;
+        }
+        position += line.len();
+    }
+    Some(format!(
+        "{}{}{}",
+        &solution[..position],
+        entry,
+        &solution[position..]
+    ))
+}
+
+fn read(path: &Path) -> Result<String, ScaffoldError> {
+    fs::read_to_string(path).map_err(|err| ScaffoldError::Io(path.to_owned(), err))
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), ScaffoldError> {
+    fs::write(path, contents).map_err(|err| ScaffoldError::Io(path.to_owned(), err))
+}
+
+/// Creates `src/day<n>.rs` from the template along with its example fixture and expected
+/// answers, and registers the day in `lib.rs` and `solution.rs`. Nothing is written unless
+/// every step can be done, and an existing day is never overwritten. Returns the files
+/// created or changed.
+pub fn new_day(root: &Path, day: u8) -> Result<Vec<PathBuf>, ScaffoldError> {
+    if !(1..=25).contains(&day) {
+        return Err(ScaffoldError::DayOutOfRange(day));
+    }
+    let module = format!("day{}", day);
+    let source = root.join("src").join(format!("{}.rs", module));
+    let fixtures = root.join("fixtures").join(&module);
+    let lib_path = root.join("src").join("lib.rs");
+    let solution_path = root.join("src").join("solution.rs");
+    for path in [&source, &fixtures] {
+        if path.exists() {
+            return Err(ScaffoldError::Exists(path.clone()));
+        }
+    }
+
+    let template = read(&root.join("src").join(format!("{}.rs", TEMPLATE)))?;
+    let lib = read(&lib_path)?;
+    if lib
+        .lines()
+        .any(|line| line == format!("pub mod {};", module))
+    {
+        return Err(ScaffoldError::Exists(source));
+    }
+    let lib = register_module(&lib, &module).ok_or(ScaffoldError::Unrecognized(
+        lib_path.clone(),
+        "the `pub mod` lines",
+    ))?;
+    let solution = register_solution(&read(&solution_path)?, day).ok_or(
+        ScaffoldError::Unrecognized(solution_path.clone(), "the `solutions!` registry"),
+    )?;
+
+    fs::create_dir_all(&fixtures).map_err(|err| ScaffoldError::Io(fixtures.clone(), err))?;
+    let example = fixtures.join("example.txt");
+    let answers = fixtures.join("example.answers");
+    write(&example, "")?;
+    // Commented out, so no fixture tests are generated until the answers are filled in.
+    write(
+        &answers,
+        &format!(
+            "# Expected answers for example.txt, as `day/part = answer`.\n# {}/1 =\n# {}/2 =\n",
+            day, day
+        ),
+    )?;
+    write(&source, &template)?;
+    write(&lib_path, &lib)?;
+    write(&solution_path, &solution)?;
+    Ok(vec![source, example, answers, lib_path, solution_path])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOLUTION: &str = "use crate::cli::Part;
+use crate::{day1, day3};
+
+solutions!(
+    Day1: day1, 1, Day::Day01, u32, u32;
+    Day3: day3, 3, Day::Day03, String, u32;
+);
+";
+
+    #[test]
+    fn registers_in_order() {
+        assert_eq!(
+            register_module(
+                "//! x\n\npub mod day1;\npub mod day3;\npub mod error;\n\n#[doc(hidden)]\npub mod cli;\n",
+                "day2"
+            ),
+            Some(
+                "//! x\n\npub mod day1;\npub mod day2;\npub mod day3;\npub mod error;\n\n#[doc(hidden)]\npub mod cli;\n"
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            register_module(
+                "pub mod day1;\n\npub const YEAR: &str = \"2022\";\n",
+                "day2"
+            ),
+            Some("pub mod day1;\npub mod day2;\n\npub const YEAR: &str = \"2022\";\n".to_owned())
+        );
+        assert_eq!(register_module("mod day1;\n", "day2"), None);
+        assert_eq!(
+            register_solution(SOLUTION, 2),
+            Some(
+                "use crate::cli::Part;
+use crate::{day1, day2, day3};
+
+solutions!(
+    Day1: day1, 1, Day::Day01, u32, u32;
+    Day2: day2, 2, Day::Day02, u32, u32;
+    Day3: day3, 3, Day::Day03, String, u32;
+);
+"
+                .to_owned()
+            )
+        );
+        assert_eq!(
+            register_solution(SOLUTION, 12).map(|s| s.lines().nth(6).unwrap().to_owned()),
+            Some("    Day12: day12, 12, Day::Day12, u32, u32;".to_owned())
+        );
+    }
+
+    #[test]
+    fn wraps_long_use_lists() {
+        let mut names: Vec<String> = (1..=25).map(|day| format!("day{}", day)).collect();
+        names.sort();
+        // Must match what rustfmt made of our own registry.
+        assert!(include_str!("solution.rs").contains(&format_use_list(&names)));
+    }
+
+    #[test]
+    fn creates_and_refuses_to_overwrite() {
+        let root = std::env::temp_dir().join(format!("aoc_scaffold_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/day_template.rs"), "pub fn parse() {}\n").unwrap();
+        fs::write(root.join("src/lib.rs"), "pub mod day1;\npub mod day3;\n").unwrap();
+        fs::write(root.join("src/solution.rs"), SOLUTION).unwrap();
+
+        assert_eq!(new_day(&root, 2).unwrap().len(), 5);
+        assert_eq!(
+            fs::read_to_string(root.join("src/day2.rs")).unwrap(),
+            "pub fn parse() {}\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+            "pub mod day1;\npub mod day2;\npub mod day3;\n"
+        );
+        assert!(
+            fs::read_to_string(root.join("fixtures/day2/example.answers"))
+                .unwrap()
+                .contains("# 2/1 =\n")
+        );
+        assert!(matches!(new_day(&root, 2), Err(ScaffoldError::Exists(_))));
+        // Already registered, even though its source is missing.
+        assert!(matches!(
+            new_day(&root, 3),
+            Err(ScaffoldError::Exists(path)) if path.ends_with("src/day3.rs")
+        ));
+        assert!(matches!(
+            new_day(&root, 26),
+            Err(ScaffoldError::DayOutOfRange(26))
+        ));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
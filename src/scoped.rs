@@ -0,0 +1,89 @@
+//! Thread-locals set for the length of a call. The observers solvers report to (progress,
+//! frames, traces, time limits and tuning) all live in one, and are swapped in around the
+//! solver and put back afterwards so that nested runs don't disturb the outer one.
+
+use std::{
+    cell::{Cell, RefCell},
+    thread::LocalKey,
+};
+
+/// A thread-local whose value can be swapped for another.
+pub trait Slot<T>: 'static {
+    fn swap(&'static self, value: T) -> T;
+}
+
+impl<T: 'static> Slot<T> for LocalKey<RefCell<T>> {
+    fn swap(&'static self, value: T) -> T {
+        self.with(|cell| cell.replace(value))
+    }
+}
+
+impl<T: 'static> Slot<T> for LocalKey<Cell<T>> {
+    fn swap(&'static self, value: T) -> T {
+        self.with(|cell| cell.replace(value))
+    }
+}
+
+/// Puts back the value that `replace` swapped out, even if `f` panics.
+struct Restore<T, S: Slot<T>> {
+    slot: &'static S,
+    previous: Option<T>,
+}
+
+impl<T, S: Slot<T>> Drop for Restore<T, S> {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            self.slot.swap(previous);
+        }
+    }
+}
+
+/// Runs `f` with `slot` holding `value`, returning its result along with what `slot` holds
+/// once `f` is done. The previous value is put back afterwards.
+pub fn replace<T, S: Slot<T>, R>(slot: &'static S, value: T, f: impl FnOnce() -> R) -> (R, T) {
+    let mut restore = Restore {
+        slot,
+        previous: Some(slot.swap(value)),
+    };
+    let result = f();
+    let last = slot.swap(restore.previous.take().unwrap());
+    (result, last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    thread_local! {
+        static NAMES: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+        static DEPTH: Cell<u32> = const { Cell::new(0) };
+    }
+
+    #[test]
+    fn values_are_put_back() {
+        let ((inner, depth), outer) = replace(&NAMES, vec!["outer"], || {
+            NAMES.with(|names| names.borrow_mut().push("seen"));
+            let inner = replace(&NAMES, Vec::new(), || {
+                NAMES.with(|names| names.borrow_mut().push("inner"))
+            });
+            (inner.1, replace(&DEPTH, 2, || DEPTH.with(Cell::get)).0)
+        });
+        assert_eq!(inner, vec!["inner"]);
+        assert_eq!(depth, 2);
+        assert_eq!(outer, vec!["outer", "seen"]);
+        assert!(NAMES.with(|names| names.borrow().is_empty()));
+        assert_eq!(DEPTH.with(Cell::get), 0);
+    }
+
+    #[test]
+    fn values_are_put_back_after_a_panic() {
+        let caught = std::panic::catch_unwind(|| {
+            replace(&NAMES, vec!["solver"], || {
+                replace(&DEPTH, 1, || panic!("solver bug"));
+            })
+        });
+        assert!(caught.is_err());
+        assert!(NAMES.with(|names| names.borrow().is_empty()));
+        assert_eq!(DEPTH.with(Cell::get), 0);
+    }
+}
@@ -0,0 +1,320 @@
+//! A small HTTP/1.1 service so tools outside Rust can call the solvers. `POST
+//! /day/<n>/part/<p>` with the puzzle input as the body answers with the same JSON record as
+//! `run --format json`. Each connection gets its own thread and carries a single request.
+
+use crate::cli::{CliError, Format, Part};
+use crate::deadline;
+use crate::error::panic_message;
+use crate::input;
+use crate::output::{self, json_string, Record};
+use crate::solution::{self, Entry};
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    panic::{self, AssertUnwindSafe},
+    thread,
+    time::Duration,
+};
+
+pub const DEFAULT_PORT: u16 = 2022;
+
+/// The request line and headers together, which is far more than any client needs.
+const MAX_HEAD: u64 = 64 * 1024;
+/// The largest puzzle inputs are around 30KB, so this leaves plenty of room for generated ones.
+const MAX_BODY: usize = 16 * 1024 * 1024;
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn error(status: u16, message: &str) -> Self {
+        Response {
+            status,
+            body: format!("{{\"error\": {}}}\n", json_string(message)),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            500 => "Internal Server Error",
+            501 => "Not Implemented",
+            _ => "Unknown",
+        }
+    }
+
+    fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        write!(
+            out,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+            self.status,
+            self.reason(),
+            self.body.len(),
+            if self.status == 405 { "Allow: POST\r\n" } else { "" },
+            self.body
+        )?;
+        out.flush()
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+/// Reads one request, or the response explaining what's wrong with it.
+fn read_request(reader: &mut impl BufRead) -> Result<Request, Response> {
+    let bad = |message: &str| Response::error(400, message);
+    let mut head = reader.take(MAX_HEAD);
+    let mut line = String::new();
+    let read_line = |head: &mut io::Take<&mut _>, line: &mut String| {
+        line.clear();
+        match head.read_line(line) {
+            Ok(0) => Err(bad("the request ended early, or its head was too long")),
+            Ok(_) => Ok(line.trim_end().to_owned()),
+            Err(err) => Err(bad(&format!("failed to read the request: {}", err))),
+        }
+    };
+    let request_line = read_line(&mut head, &mut line)?;
+    let (method, path) = match request_line.split(' ').collect::<Vec<&str>>()[..] {
+        [method, path, version] if version.starts_with("HTTP/1.") => {
+            (method.to_owned(), path.to_owned())
+        }
+        _ => return Err(bad(&format!("`{}` is not an HTTP/1 request", request_line))),
+    };
+    let mut length = 0;
+    loop {
+        let header = read_line(&mut head, &mut line)?;
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(bad(&format!("`{}` is not a header", header)));
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            length = value
+                .parse()
+                .map_err(|_| bad(&format!("`{}` is not a content length", value)))?;
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            return Err(Response::error(
+                501,
+                "send the input with a content length, not a transfer encoding",
+            ));
+        }
+    }
+    if length > MAX_BODY {
+        return Err(Response::error(
+            413,
+            &format!("inputs are limited to {} bytes", MAX_BODY),
+        ));
+    }
+    let mut body = vec![0; length];
+    head.into_inner()
+        .read_exact(&mut body)
+        .map_err(|err| bad(&format!("failed to read the input: {}", err)))?;
+    Ok(Request { method, path, body })
+}
+
+/// Solves the part the request is for.
+fn respond(request: Request, time_limit: Option<Duration>) -> Response {
+    let path = request.path.split('?').next().unwrap_or_default();
+    let (day, part) = match path.split('/').collect::<Vec<&str>>()[..] {
+        ["", "day", day, "part", part] => (day, part),
+        _ => {
+            return Response::error(
+                404,
+                &format!("no such path {}, expected /day/<n>/part/<p>", path),
+            )
+        }
+    };
+    if request.method != "POST" {
+        return Response::error(405, "post the puzzle input to solve it");
+    }
+    let entry = match day.parse() {
+        Ok(number) => solution::find(number).ok_or(CliError::UnknownDay(number)),
+        Err(_) => Err(CliError::InvalidDay(day.to_owned())),
+    };
+    let (entry, part) = match (entry, part.parse::<Part>()) {
+        (Ok(entry), Ok(part)) => (entry, part),
+        (Err(err), _) | (_, Err(err)) => return Response::error(404, &err.to_string()),
+    };
+    let Ok(input) = String::from_utf8(request.body) else {
+        return Response::error(400, "the input isn't UTF-8");
+    };
+    solve(entry, part, &input::normalize(input), time_limit)
+}
+
+/// Runs one part on `input`. A solver which fails gets a 422 and one which panics a 500, both
+/// with the reason as the `error`.
+fn solve(entry: &Entry, part: Part, input: &str, time_limit: Option<Duration>) -> Response {
+    let solved = deadline::with_limit(time_limit, || {
+        panic::catch_unwind(AssertUnwindSafe(|| Record::solve(entry, part, input)))
+    });
+    match solved {
+        Ok(record) => Response {
+            status: if record.answer.is_ok() { 200 } else { 422 },
+            body: format!(
+                "{}\n",
+                output::format_record(Format::Json, &record, true).trim_start()
+            ),
+        },
+        Err(payload) => Response::error(
+            500,
+            &format!("the solver panicked: {}", panic_message(payload)),
+        ),
+    }
+}
+
+fn handle(stream: TcpStream, time_limit: Option<Duration>) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let response = match read_request(&mut reader) {
+        Ok(request) => respond(request, time_limit),
+        Err(response) => response,
+    };
+    response.write_to(&mut &stream)
+}
+
+/// Answers requests on `listener` until the process is stopped, giving every solver
+/// `time_limit` if there is one.
+pub fn serve(listener: TcpListener, time_limit: Option<Duration>) {
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                thread::spawn(move || {
+                    if let Err(err) = handle(stream, time_limit) {
+                        eprintln!("serve: {}", err);
+                    }
+                });
+            }
+            Err(err) => eprintln!("serve: failed to accept a connection: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::stub;
+    use std::net::SocketAddr;
+
+    fn start() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, None));
+        addr
+    }
+
+    /// Sends `request` as it is, returning the status and body of the response.
+    fn send(addr: SocketAddr, request: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, body.to_owned())
+    }
+
+    fn post(addr: SocketAddr, path: &str, body: &str) -> (u16, String) {
+        send(
+            addr,
+            &format!(
+                "POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+                path,
+                body.len(),
+                body
+            ),
+        )
+    }
+
+    #[test]
+    fn solves_posted_input() {
+        let addr = start();
+        let (status, body) = post(
+            addr,
+            "/day/1/part/2",
+            include_str!("../fixtures/day1/example.txt"),
+        );
+        assert_eq!(status, 200);
+        assert!(
+            body.starts_with(r#"{"day": 1, "part": 2, "answer": "45000", "elapsed_ns": "#),
+            "{}",
+            body
+        );
+        assert!(body.ends_with("\"error\": null}\n"), "{}", body);
+    }
+
+    #[test]
+    fn reports_failures_as_json() {
+        let addr = start();
+        let (status, body) = post(addr, "/day/13/part/1", "[1,2]\n[3\n");
+        assert_eq!(status, 422);
+        assert!(body.contains("\"answer\": null"), "{}", body);
+        // A lone monkey has nobody to throw to.
+        let monkey = include_str!("../fixtures/day11/example.txt")
+            .split("\n\n")
+            .next()
+            .unwrap();
+        let (status, body) = post(addr, "/day/11/part/1", monkey);
+        assert_eq!(status, 422);
+        assert!(body.contains("\"answer\": null"), "{}", body);
+        assert!(body.contains("\"error\": \"invalid input: "), "{}", body);
+    }
+
+    #[test]
+    fn reports_panics_as_server_errors() {
+        let response = solve(&stub::PANICKING, Part::One, "", None);
+        assert_eq!(response.status, 500);
+        assert_eq!(
+            response.body,
+            "{\"error\": \"the solver panicked: solver bug\"}\n"
+        );
+    }
+
+    #[test]
+    fn rejects_other_requests() {
+        let addr = start();
+        let error = |message: String| format!("{{\"error\": {}}}\n", json_string(&message));
+        assert_eq!(
+            post(addr, "/day/26/part/1", ""),
+            (404, error(CliError::UnknownDay(26).to_string()))
+        );
+        assert_eq!(
+            post(addr, "/day/1/part/3", ""),
+            (
+                404,
+                error(CliError::InvalidPart("3".to_owned()).to_string())
+            )
+        );
+        assert_eq!(post(addr, "/days/1", "").0, 404);
+        assert_eq!(
+            send(
+                addr,
+                "GET /day/1/part/1 HTTP/1.1\r\nHost: localhost\r\n\r\n"
+            )
+            .0,
+            405
+        );
+        assert_eq!(send(addr, "hello\r\n\r\n").0, 400);
+        assert_eq!(
+            send(
+                addr,
+                "POST /day/1/part/1 HTTP/1.1\r\nContent-Length: 99999999999\r\n\r\n"
+            )
+            .0,
+            413
+        );
+    }
+}
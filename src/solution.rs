@@ -0,0 +1,238 @@
+use crate::cli::Part;
+use crate::deadline;
+use crate::error::SolveError;
+use crate::rng::Rng;
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day20, day21,
+    day22, day23, day24, day25, day3, day4, day5, day6, day7, day8, day9,
+};
+use advent_of_code::day::Day;
+use std::fmt::Display;
+
+/// A single day's puzzle. Each part keeps its own answer type so callers that know the day
+/// can compare answers directly, while the registry only needs them to be `Display`.
+pub trait Solution {
+    const NUMBER: u8;
+    const QUESTION: Day;
+
+    type Part1: Display;
+    type Part2: Display;
+
+    fn part1(input: &str) -> Result<Self::Part1, SolveError>;
+    fn part2(input: &str) -> Result<Self::Part2, SolveError>;
+}
+
+fn erased_part1<S: Solution>(input: &str) -> Result<String, SolveError> {
+    S::part1(input).map(|answer| answer.to_string())
+}
+
+fn erased_part2<S: Solution>(input: &str) -> Result<String, SolveError> {
+    S::part2(input).map(|answer| answer.to_string())
+}
+
+/// Type-erased view of a `Solution`, so every day can live in the same table.
+pub struct Entry {
+    pub number: u8,
+    pub question: Day,
+    /// The worked example from the puzzle text, `fixtures/day<n>/example.txt`.
+    pub example: &'static str,
+    parse: fn(&str) -> Result<(), SolveError>,
+    generate: fn(&mut Rng, usize) -> String,
+    part1: fn(&str) -> Result<String, SolveError>,
+    part2: fn(&str) -> Result<String, SolveError>,
+}
+
+impl Entry {
+    const fn of<S: Solution>(
+        parse: fn(&str) -> Result<(), SolveError>,
+        generate: fn(&mut Rng, usize) -> String,
+        example: &'static str,
+    ) -> Self {
+        Entry {
+            number: S::NUMBER,
+            question: S::QUESTION,
+            example,
+            parse,
+            generate,
+            part1: erased_part1::<S>,
+            part2: erased_part2::<S>,
+        }
+    }
+
+    /// Runs only the day's parsing step, discarding the result.
+    pub fn parse(&self, input: &str) -> Result<(), SolveError> {
+        (self.parse)(input)
+    }
+
+    /// A random puzzle input, larger as `size` grows. What `size` counts is up to the day.
+    pub fn generate(&self, rng: &mut Rng, size: usize) -> String {
+        (self.generate)(rng, size)
+    }
+
+    /// Runs one part, within the time limit set for this thread if there is one.
+    pub fn solve(&self, part: Part, input: &str) -> Result<String, SolveError> {
+        let solver = match part {
+            Part::One => self.part1,
+            Part::Two => self.part2,
+        };
+        deadline::start(|| solver(input))
+    }
+}
+
+macro_rules! solutions {
+    ($($solution: ident: $day: ident, $number: literal, $question: expr, $part1: ty, $part2: ty;)*) => {
+        $(
+            pub struct $solution;
+
+            impl Solution for $solution {
+                const NUMBER: u8 = $number;
+                const QUESTION: Day = $question;
+
+                type Part1 = $part1;
+                type Part2 = $part2;
+
+                fn part1(input: &str) -> Result<$part1, SolveError> {
+                    $day::part1(input)
+                }
+
+                fn part2(input: &str) -> Result<$part2, SolveError> {
+                    $day::part2(input)
+                }
+            }
+        )*
+
+        pub static REGISTRY: &[Entry] = &[$(Entry::of::<$solution>(
+            |input| $day::parse(input).map(|_| ()),
+            $day::generate,
+            include_str!(concat!("../fixtures/", stringify!($day), "/example.txt")),
+        )),*];
+
+        #[cfg(test)]
+        mod known_answers {
+            use super::*;
+
+            $(
+                mod $day {
+                    use super::*;
+
+                    #[test]
+                    fn part1_test() {
+                        check_known_answer::<$solution>(Part::One);
+                    }
+
+                    #[test]
+                    fn part2_test() {
+                        check_known_answer::<$solution>(Part::Two);
+                    }
+                }
+            )*
+        }
+    };
+}
+
+solutions!(
+    Day1: day1, 1, Day::Day01, u32, u32;
+    Day2: day2, 2, Day::Day02, u32, u32;
+    Day3: day3, 3, Day::Day03, u32, u32;
+    Day4: day4, 4, Day::Day04, u32, u32;
+    Day5: day5, 5, Day::Day05, String, String;
+    Day6: day6, 6, Day::Day06, usize, usize;
+    Day7: day7, 7, Day::Day07, u32, u32;
+    Day8: day8, 8, Day::Day08, u32, usize;
+    Day9: day9, 9, Day::Day09, usize, usize;
+    Day10: day10, 10, Day::Day10, i32, String;
+    Day11: day11, 11, Day::Day11, u64, u64;
+    Day12: day12, 12, Day::Day12, u32, u32;
+    Day13: day13, 13, Day::Day13, usize, usize;
+    Day14: day14, 14, Day::Day14, u32, u32;
+    Day15: day15, 15, Day::Day15, usize, i64;
+    Day16: day16, 16, Day::Day16, u32, u32;
+    Day17: day17, 17, Day::Day17, isize, isize;
+    Day18: day18, 18, Day::Day18, usize, usize;
+    Day19: day19, 19, Day::Day19, u32, u32;
+    Day20: day20, 20, Day::Day20, isize, isize;
+    Day21: day21, 21, Day::Day21, i64, i64;
+    Day22: day22, 22, Day::Day22, u32, u32;
+    Day23: day23, 23, Day::Day23, isize, u32;
+    Day24: day24, 24, Day::Day24, usize, usize;
+    Day25: day25, 25, Day::Day25, String, u32;
+);
+
+/// Compares a day's answer for our puzzle input against the known answers file.
+#[cfg(test)]
+fn check_known_answer<S: Solution>(part: Part) {
+    use crate::{answers::Answers, ANSWERS_PATH, COOKIE_PATH, INPUT_CACHE, YEAR};
+    use advent_of_code::web_api::load_question_input;
+
+    let answers = Answers::load(ANSWERS_PATH).unwrap();
+    let expected = answers.get(S::NUMBER, part).unwrap_or_else(|| {
+        panic!(
+            "{} has no answer for day {} part {}",
+            ANSWERS_PATH,
+            S::NUMBER,
+            part
+        )
+    });
+    let input = load_question_input(YEAR, COOKIE_PATH, INPUT_CACHE, S::QUESTION);
+    let answer = match part {
+        Part::One => S::part1(&input).map(|answer| answer.to_string()),
+        Part::Two => S::part2(&input).map(|answer| answer.to_string()),
+    };
+    assert_eq!(Ok(expected.to_owned()), answer);
+}
+
+pub fn find(day: u8) -> Option<&'static Entry> {
+    REGISTRY.iter().find(|entry| entry.number == day)
+}
+
+/// Days that misbehave on purpose, for testing how the runner copes.
+#[cfg(test)]
+pub(crate) mod stub {
+    use super::*;
+
+    pub(crate) struct Panicking;
+
+    impl Solution for Panicking {
+        const NUMBER: u8 = 0;
+        const QUESTION: Day = Day::Day01;
+
+        type Part1 = u32;
+        type Part2 = u32;
+
+        fn part1(_: &str) -> Result<u32, SolveError> {
+            panic!("solver bug")
+        }
+
+        fn part2(_: &str) -> Result<u32, SolveError> {
+            panic!("solver bug")
+        }
+    }
+
+    /// A day whose solvers panic whatever the input.
+    pub(crate) static PANICKING: Entry =
+        Entry::of::<Panicking>(|_| Ok(()), |_, _| String::new(), "");
+}
+
+#[test]
+fn registry_covers_every_day() {
+    let numbers: Vec<u8> = REGISTRY.iter().map(|entry| entry.number).collect();
+    assert_eq!(numbers, (1..=25).collect::<Vec<u8>>());
+    assert!(find(0).is_none());
+    assert!(find(26).is_none());
+}
+
+#[test]
+fn generated_inputs_parse() {
+    let mut rng = Rng::new(0x2022);
+    for entry in REGISTRY {
+        for size in [1, 2, 10, 50] {
+            let input = entry.generate(&mut rng, size);
+            if let Err(error) = entry.parse(&input) {
+                panic!(
+                    "day {} size {}: {} in\n{}",
+                    entry.number, size, error, input
+                );
+            }
+        }
+    }
+}
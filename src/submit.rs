@@ -0,0 +1,369 @@
+use crate::answers::escape;
+use crate::cli::Part;
+use std::{
+    error::Error,
+    fmt, fs,
+    io::{self, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The outcomes which judge the answer itself, so sending it again can only get the same
+/// response.
+const VERDICTS: [Outcome; 4] = [
+    Outcome::Correct,
+    Outcome::Incorrect,
+    Outcome::TooHigh,
+    Outcome::TooLow,
+];
+
+/// What the site made of a submitted answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    Incorrect,
+    TooHigh,
+    TooLow,
+    /// Submitted too soon after the last attempt, with the site's note of how long is left.
+    RateLimited(Option<String>),
+    /// The part was already solved, or part 2 isn't unlocked yet.
+    WrongLevel,
+    Unrecognized,
+}
+
+impl Outcome {
+    /// Reads the outcome from the response page, which says it in prose.
+    pub fn parse(page: &str) -> Self {
+        if page.contains("That's the right answer") {
+            Outcome::Correct
+        } else if page.contains("That's not the right answer") {
+            if page.contains("your answer is too high") {
+                Outcome::TooHigh
+            } else if page.contains("your answer is too low") {
+                Outcome::TooLow
+            } else {
+                Outcome::Incorrect
+            }
+        } else if page.contains("You gave an answer too recently") {
+            let left = page.find("You have ").and_then(|start| {
+                let rest = &page[start + "You have ".len()..];
+                Some(rest[..rest.find(" left to wait")?].to_owned())
+            });
+            Outcome::RateLimited(left)
+        } else if page.contains("You don't seem to be solving the right level") {
+            Outcome::WrongLevel
+        } else {
+            Outcome::Unrecognized
+        }
+    }
+
+    fn keyword(&self) -> &'static str {
+        match self {
+            Outcome::Correct => "correct",
+            Outcome::Incorrect => "incorrect",
+            Outcome::TooHigh => "too-high",
+            Outcome::TooLow => "too-low",
+            Outcome::RateLimited(_) => "rate-limited",
+            Outcome::WrongLevel => "wrong-level",
+            Outcome::Unrecognized => "unrecognized",
+        }
+    }
+
+    fn verdict_of_keyword(keyword: &str) -> Option<Self> {
+        VERDICTS
+            .into_iter()
+            .find(|verdict| verdict.keyword() == keyword)
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Correct => write!(f, "correct"),
+            Outcome::Incorrect => write!(f, "incorrect"),
+            Outcome::TooHigh => write!(f, "incorrect, too high"),
+            Outcome::TooLow => write!(f, "incorrect, too low"),
+            Outcome::RateLimited(Some(left)) => write!(f, "rate limited, {} left to wait", left),
+            Outcome::RateLimited(None) => write!(f, "rate limited"),
+            Outcome::WrongLevel => write!(f, "already solved or not unlocked yet"),
+            Outcome::Unrecognized => write!(f, "unrecognized response"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    Cookie(String, io::Error),
+    Http(reqwest::Error),
+    Status(reqwest::StatusCode),
+    Log(String, io::Error),
+    MultiLine,
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmitError::Cookie(path, err) => {
+                write!(f, "failed to read session cookie {}: {}", path, err)
+            }
+            SubmitError::Http(err) => write!(f, "request failed: {}", err),
+            SubmitError::Status(status) => write!(f, "server responded with {}", status),
+            SubmitError::Log(path, err) => write!(f, "failed to update {}: {}", path, err),
+            SubmitError::MultiLine => write!(
+                f,
+                "the answer is a picture, read it off and submit the letters by hand"
+            ),
+        }
+    }
+}
+
+impl Error for SubmitError {}
+
+impl From<reqwest::Error> for SubmitError {
+    fn from(err: reqwest::Error) -> Self {
+        SubmitError::Http(err)
+    }
+}
+
+/// Reads the session token, with or without a leading `session=`.
+pub fn read_session(path: &str) -> Result<String, SubmitError> {
+    let cookie =
+        fs::read_to_string(path).map_err(|err| SubmitError::Cookie(path.to_owned(), err))?;
+    let cookie = cookie.trim();
+    Ok(cookie.strip_prefix("session=").unwrap_or(cookie).to_owned())
+}
+
+/// Posts `answer` to `<base_url>/<year>/day/<day>/answer` and reads the outcome.
+pub fn post_answer(
+    base_url: &str,
+    year: &str,
+    session: &str,
+    day: u8,
+    part: Part,
+    answer: &str,
+) -> Result<Outcome, SubmitError> {
+    if answer.contains('\n') {
+        return Err(SubmitError::MultiLine);
+    }
+    let url = format!(
+        "{}/{}/day/{}/answer",
+        base_url.trim_end_matches('/'),
+        year,
+        day
+    );
+    let response = reqwest::blocking::Client::builder()
+        .user_agent(concat!(
+            env!("CARGO_PKG_NAME"),
+            "/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .build()?
+        .post(url)
+        .header(reqwest::header::COOKIE, format!("session={}", session))
+        .form(&[("level", part.to_string()), ("answer", answer.to_owned())])
+        .send()?;
+    if !response.status().is_success() {
+        return Err(SubmitError::Status(response.status()));
+    }
+    Ok(Outcome::parse(&response.text()?))
+}
+
+/// The record of past submissions, one per line as `<unix time> <day>/<part> <outcome>
+/// <answer>`, with answers escaped as in `answers.txt`.
+pub struct Log<'a>(pub &'a str);
+
+impl Log<'_> {
+    /// The verdict already given for this exact answer, if any.
+    pub fn previous_verdict(&self, day: u8, part: Part, answer: &str) -> Option<Outcome> {
+        let text = fs::read_to_string(self.0).ok()?;
+        let key = format!("{}/{}", day, part);
+        let answer = escape(answer);
+        text.lines().find_map(|line| {
+            let mut fields = line.splitn(4, ' ').skip(1);
+            let (k, outcome, a) = (fields.next()?, fields.next()?, fields.next()?);
+            if k == key && a == answer {
+                Outcome::verdict_of_keyword(outcome)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn record(
+        &self,
+        day: u8,
+        part: Part,
+        answer: &str,
+        outcome: &Outcome,
+    ) -> Result<(), SubmitError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let log_error = |err| SubmitError::Log(self.0.to_owned(), err);
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Path::new(self.0))
+            .map_err(log_error)?;
+        writeln!(
+            file,
+            "{} {}/{} {} {}",
+            now,
+            day,
+            part,
+            outcome.keyword(),
+            escape(answer)
+        )
+        .map_err(log_error)
+    }
+}
+
+/// Submits unless the log shows this answer has already been judged, returning the outcome
+/// and whether it came from the log. Every response from the site is logged.
+pub fn submit(
+    base_url: &str,
+    year: &str,
+    cookie_path: &str,
+    log: &Log,
+    day: u8,
+    part: Part,
+    answer: &str,
+) -> Result<(Outcome, bool), SubmitError> {
+    if let Some(verdict) = log.previous_verdict(day, part, answer) {
+        return Ok((verdict, true));
+    }
+    let session = read_session(cookie_path)?;
+    let outcome = post_answer(base_url, year, &session, day, part, answer)?;
+    log.record(day, part, answer, &outcome)?;
+    Ok((outcome, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Serves a single request with `page`, handing back the request line, headers and body.
+    fn stub_server(page: &'static str) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut lines = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim_end().is_empty() {
+                    break;
+                }
+                lines.push(line.trim_end().to_owned());
+            }
+            let length = lines
+                .iter()
+                .find_map(|line| {
+                    line.to_lowercase()
+                        .strip_prefix("content-length: ")?
+                        .parse()
+                        .ok()
+                })
+                .unwrap_or(0);
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            lines.push(String::from_utf8(body).unwrap());
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                page.len(),
+                page
+            )
+            .unwrap();
+            lines
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn parse_outcomes() {
+        let page = |message: &str| format!("<main><article><p>{}</p></article></main>", message);
+        for (message, outcome) in [
+            (
+                "That's the right answer! You are one gold star closer.",
+                Outcome::Correct,
+            ),
+            (
+                "That's not the right answer. If you're stuck, ...",
+                Outcome::Incorrect,
+            ),
+            (
+                "That's not the right answer; your answer is too high.",
+                Outcome::TooHigh,
+            ),
+            (
+                "That's not the right answer; your answer is too low.",
+                Outcome::TooLow,
+            ),
+            (
+                "You gave an answer too recently. You have 34s left to wait.",
+                Outcome::RateLimited(Some("34s".to_owned())),
+            ),
+            (
+                "You don't seem to be solving the right level.",
+                Outcome::WrongLevel,
+            ),
+            ("Please log in.", Outcome::Unrecognized),
+        ] {
+            assert_eq!(Outcome::parse(&page(message)), outcome, "{}", message);
+        }
+    }
+
+    #[test]
+    fn submit_to_stub_server() {
+        let dir = std::env::temp_dir().join(format!("aoc_submit_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let cookie = dir.join("session.cookie");
+        fs::write(&cookie, "session=abc123\n").unwrap();
+        let log_path = dir.join("submissions.txt");
+        let log = Log(log_path.to_str().unwrap());
+        let cookie = cookie.to_str().unwrap();
+
+        let (url, server) =
+            stub_server("<article><p>That's not the right answer; your answer is too high.</p>");
+        assert_eq!(
+            submit(&url, "2022", cookie, &log, 7, Part::Two, "1 2").unwrap(),
+            (Outcome::TooHigh, false)
+        );
+        let request = server.join().unwrap();
+        assert_eq!(request[0], "POST /2022/day/7/answer HTTP/1.1");
+        assert!(request
+            .iter()
+            .any(|line| line.to_lowercase() == "cookie: session=abc123"));
+        assert_eq!(request.last().unwrap(), "level=2&answer=1+2");
+
+        // Already judged, so no request is made.
+        assert_eq!(
+            submit(
+                "http://127.0.0.1:9",
+                "2022",
+                cookie,
+                &log,
+                7,
+                Part::Two,
+                "1 2"
+            )
+            .unwrap(),
+            (Outcome::TooHigh, true)
+        );
+        assert!(fs::read_to_string(&log_path)
+            .unwrap()
+            .ends_with(" 7/2 too-high 1 2\n"));
+        assert!(matches!(
+            submit(&url, "2022", cookie, &log, 10, Part::Two, "\n#.\n.#"),
+            Err(SubmitError::MultiLine)
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,246 @@
+use crate::cli::Part;
+use crate::error::{format_duration, SolveError};
+use crate::memory::{self, Bytes, Usage};
+use crate::solution::Entry;
+use std::time::{Duration, Instant};
+
+pub struct PartTiming {
+    pub part: Part,
+    pub answer: Result<String, SolveError>,
+    pub elapsed: Duration,
+    pub memory: Usage,
+}
+
+/// Timings for one day. Each part's time covers the whole solver, parsing included, so
+/// `parse` is the share of it spent turning the input into the day's data structures.
+/// `wall` is the day's own elapsed time from start to finish, which stays meaningful when
+/// several days run at once.
+pub struct DayTiming {
+    pub number: u8,
+    pub parse: Duration,
+    pub parts: Vec<PartTiming>,
+    pub wall: Duration,
+}
+
+pub fn time<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+pub fn time_day(entry: &Entry, parts: &[Part], input: &str) -> DayTiming {
+    let start = Instant::now();
+    let (_, parse) = time(|| entry.parse(input));
+    let parts = parts
+        .iter()
+        .map(|part| {
+            let ((answer, elapsed), memory) =
+                memory::measure(|| time(|| entry.solve(*part, input)));
+            PartTiming {
+                part: *part,
+                answer,
+                elapsed,
+                memory,
+            }
+        })
+        .collect();
+    DayTiming {
+        number: entry.number,
+        parse,
+        parts,
+        wall: start.elapsed(),
+    }
+}
+
+fn answer_cell(answer: &Result<String, SolveError>) -> &str {
+    match answer {
+        Ok(answer) if answer.contains('\n') => "(see below)",
+        Ok(answer) => answer,
+        Err(_) => "(error)",
+    }
+}
+
+/// Renders the timings as an aligned table, followed by any multi-line answers (such as
+/// day 10's CRT image) and errors which would not fit in a cell.
+pub fn format_table(timings: &[DayTiming]) -> String {
+    let header = vec![
+        "day".to_owned(),
+        "parse".to_owned(),
+        "part 1".to_owned(),
+        "time".to_owned(),
+        "part 2".to_owned(),
+        "time".to_owned(),
+        "wall".to_owned(),
+    ];
+    let mut rows = vec![header];
+    let mut total = Duration::ZERO;
+    for timing in timings {
+        let mut row = vec![timing.number.to_string(), format_duration(timing.parse)];
+        for part in [Part::One, Part::Two] {
+            match timing.parts.iter().find(|p| p.part == part) {
+                Some(p) => {
+                    total += p.elapsed;
+                    row.push(answer_cell(&p.answer).to_owned());
+                    row.push(format_duration(p.elapsed));
+                }
+                None => row.extend(["-".to_owned(), "-".to_owned()]),
+            }
+        }
+        row.push(format_duration(timing.wall));
+        rows.push(row);
+    }
+    let mut table = format_rows(rows);
+    table.push_str(&format!("total: {}\n", format_duration(total)));
+    for timing in timings {
+        for p in timing.parts.iter() {
+            match &p.answer {
+                Ok(answer) if answer.contains('\n') => table.push_str(&format!(
+                    "\nday {} part {}:{}\n",
+                    timing.number, p.part, answer
+                )),
+                Err(err) => table.push_str(&format!(
+                    "\nday {} part {}: error: {}\n",
+                    timing.number, p.part, err
+                )),
+                Ok(_) => {}
+            }
+        }
+    }
+    table
+}
+
+/// Renders what each part allocated as an aligned table, with a row per day and part. Days
+/// that ran both parts get an `all` row too, adding up their allocations and taking the
+/// higher of their peaks.
+pub fn format_memory_table(timings: &[DayTiming]) -> String {
+    let mut rows = vec![["day", "part", "allocs", "allocated", "peak"]
+        .map(str::to_owned)
+        .to_vec()];
+    let row = |day: u8, part: String, memory: Usage| {
+        vec![
+            day.to_string(),
+            part,
+            memory.allocations.to_string(),
+            Bytes(memory.allocated).to_string(),
+            Bytes(memory.peak).to_string(),
+        ]
+    };
+    for timing in timings {
+        for p in timing.parts.iter() {
+            rows.push(row(timing.number, p.part.to_string(), p.memory));
+        }
+        if timing.parts.len() > 1 {
+            let total = timing
+                .parts
+                .iter()
+                .fold(Usage::default(), |total, p| Usage {
+                    allocations: total.allocations + p.memory.allocations,
+                    allocated: total.allocated + p.memory.allocated,
+                    peak: total.peak.max(p.memory.peak),
+                });
+            rows.push(row(timing.number, "all".to_owned(), total));
+        }
+    }
+    format_rows(rows)
+}
+
+/// Lays out rows of cells in left-aligned columns. The first row is the header, and every
+/// row must have the same number of cells.
+pub fn format_rows(rows: Vec<Vec<String>>) -> String {
+    let widths: Vec<usize> = (0..rows[0].len())
+        .map(|col| {
+            rows.iter()
+                .map(|row| row[col].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut table = String::new();
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        table.push_str(cells.join("  ").trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+#[test]
+fn table_layout() {
+    let timings = vec![
+        DayTiming {
+            number: 1,
+            parse: Duration::from_micros(12),
+            parts: vec![
+                PartTiming {
+                    part: Part::One,
+                    answer: Ok("67633".to_owned()),
+                    elapsed: Duration::from_micros(40),
+                    memory: Usage {
+                        allocations: 3,
+                        allocated: 2_000,
+                        peak: 1_200,
+                    },
+                },
+                PartTiming {
+                    part: Part::Two,
+                    answer: Ok("199628".to_owned()),
+                    elapsed: Duration::from_millis(2),
+                    memory: Usage::default(),
+                },
+            ],
+            wall: Duration::from_micros(2_055),
+        },
+        DayTiming {
+            number: 5,
+            parse: Duration::from_micros(7),
+            parts: vec![PartTiming {
+                part: Part::One,
+                answer: Err(SolveError::EmptyStack { stack: 2 }),
+                elapsed: Duration::from_micros(9),
+                memory: Usage::default(),
+            }],
+            wall: Duration::from_micros(17),
+        },
+        DayTiming {
+            number: 10,
+            parse: Duration::from_micros(3),
+            parts: vec![PartTiming {
+                part: Part::Two,
+                answer: Ok("\n#..\n.#.".to_owned()),
+                elapsed: Duration::from_secs(3),
+                memory: Usage::default(),
+            }],
+            wall: Duration::from_secs(3),
+        },
+    ];
+    assert_eq!(
+        format_table(&timings),
+        "day  parse  part 1   time  part 2       time     wall
+1    12µs   67633    40µs  199628       2.000ms  2.055ms
+5    7µs    (error)  9µs   -            -        17µs
+10   3µs    -        -     (see below)  3.000s   3.000s
+total: 3.002s
+
+day 5 part 1: error: tried to move a crate off empty stack 2
+
+day 10 part 2:
+#..
+.#.
+"
+    );
+    assert_eq!(
+        format_memory_table(&timings),
+        "day  part  allocs  allocated  peak
+1    1     3       1.95KiB    1.17KiB
+1    2     0       0B         0B
+1    all   3       1.95KiB    1.17KiB
+5    1     0       0B         0B
+10   2     0       0B         0B
+"
+    );
+}
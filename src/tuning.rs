@@ -0,0 +1,170 @@
+//! Puzzle constants which can be changed between runs, such as the row day 15 counts or how
+//! many rounds the monkeys of day 11 play. Each day declares its own as a `Tunable`, and
+//! reads it with `get`, which gives the value set for the current thread by `with` or the
+//! puzzle's own value otherwise.
+
+use crate::scoped;
+use crate::{day11, day15, day17, day19, day20, day23};
+use std::{cell::RefCell, collections::BTreeMap, error::Error, fmt};
+
+/// A number a solver would otherwise have hard-coded, and the values it can sensibly take.
+#[derive(Debug)]
+pub struct Tunable {
+    pub day: u8,
+    pub name: &'static str,
+    pub about: &'static str,
+    pub default: i64,
+    pub min: i64,
+    pub max: i64,
+}
+
+/// Values set for tunables, by day and name.
+pub type Settings = BTreeMap<(u8, &'static str), i64>;
+
+/// Every tunable, in day order.
+pub static TUNABLES: &[&Tunable] = &[
+    &day11::ROUNDS,
+    &day11::WORRIED_ROUNDS,
+    &day15::ROW,
+    &day15::MAX,
+    &day17::ROCKS,
+    &day19::MINUTES,
+    &day19::LONG_MINUTES,
+    &day20::KEY,
+    &day20::MIXES,
+    &day23::ROUNDS,
+];
+
+thread_local! {
+    static SETTINGS: RefCell<Settings> = RefCell::new(Settings::new());
+}
+
+impl Tunable {
+    /// The value set for this thread, or the puzzle's.
+    pub fn get(&self) -> i64 {
+        SETTINGS
+            .with(|settings| settings.borrow().get(&(self.day, self.name)).copied())
+            .unwrap_or(self.default)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TuningError {
+    Unknown {
+        day: u8,
+        name: String,
+    },
+    NotANumber(String),
+    OutOfRange {
+        name: &'static str,
+        min: i64,
+        max: i64,
+    },
+}
+
+impl fmt::Display for TuningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TuningError::Unknown { day, name } => {
+                write!(f, "day {} has no parameter `{}`", day, name)
+            }
+            TuningError::NotANumber(value) => write!(f, "`{}` is not a number", value),
+            TuningError::OutOfRange { name, min, max } => {
+                write!(f, "{} must be from {} to {}", name, min, max)
+            }
+        }
+    }
+}
+
+impl Error for TuningError {}
+
+/// The tunables belonging to `day`.
+pub fn for_day(day: u8) -> impl Iterator<Item = &'static Tunable> {
+    TUNABLES
+        .iter()
+        .copied()
+        .filter(move |tunable| tunable.day == day)
+}
+
+pub fn find(day: u8, name: &str) -> Result<&'static Tunable, TuningError> {
+    for_day(day)
+        .find(|tunable| tunable.name == name)
+        .ok_or_else(|| TuningError::Unknown {
+            day,
+            name: name.to_owned(),
+        })
+}
+
+/// Sets the tunable `name` of `day` to `value`, once it's checked to be in range.
+pub fn set(settings: &mut Settings, day: u8, name: &str, value: &str) -> Result<(), TuningError> {
+    let tunable = find(day, name)?;
+    let value: i64 = value
+        .parse()
+        .map_err(|_| TuningError::NotANumber(value.to_owned()))?;
+    if !(tunable.min..=tunable.max).contains(&value) {
+        return Err(TuningError::OutOfRange {
+            name: tunable.name,
+            min: tunable.min,
+            max: tunable.max,
+        });
+    }
+    settings.insert((day, tunable.name), value);
+    Ok(())
+}
+
+/// Runs `f` with `settings` in place of the puzzle's values. Any settings already in place
+/// for this thread are suspended until `f` returns.
+pub fn with<R>(settings: &Settings, f: impl FnOnce() -> R) -> R {
+    scoped::replace(&SETTINGS, settings.clone(), f).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_apply_within_with() {
+        let mut settings = Settings::new();
+        set(&mut settings, 15, "row", "10").unwrap();
+        assert_eq!(day15::ROW.get(), 2_000_000);
+        assert_eq!(with(&settings, || day15::ROW.get()), 10);
+        assert_eq!(with(&settings, || day15::MAX.get()), 4_000_000);
+        assert_eq!(day15::ROW.get(), 2_000_000);
+    }
+
+    #[test]
+    fn set_checks_the_value() {
+        let mut settings = Settings::new();
+        assert_eq!(
+            set(&mut settings, 15, "rounds", "10"),
+            Err(TuningError::Unknown {
+                day: 15,
+                name: "rounds".to_owned()
+            })
+        );
+        assert_eq!(
+            set(&mut settings, 11, "rounds", "many"),
+            Err(TuningError::NotANumber("many".to_owned()))
+        );
+        assert_eq!(
+            set(&mut settings, 11, "rounds", "-1"),
+            Err(TuningError::OutOfRange {
+                name: "rounds",
+                min: 0,
+                max: day11::ROUNDS.max
+            })
+        );
+        assert!(settings.is_empty());
+    }
+
+    #[test]
+    fn tunables_are_in_day_order_and_unique() {
+        let keys: Vec<(u8, &str)> = TUNABLES.iter().map(|t| (t.day, t.name)).collect();
+        assert!(keys.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        let unique: std::collections::BTreeSet<&(u8, &str)> = keys.iter().collect();
+        assert_eq!(unique.len(), keys.len());
+        for tunable in TUNABLES {
+            assert!((tunable.min..=tunable.max).contains(&tunable.default));
+        }
+    }
+}